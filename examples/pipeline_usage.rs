@@ -91,7 +91,6 @@ async fn basic_pipeline_example() -> Result<(), Box<dyn std::error::Error>> {
                 stop_sequences: vec!["\n\n".to_string()],
                 do_sample: true,
             },
-            output_format: OutputFormat::Markdown,
             include_metadata: true,
         },
         created_at: chrono::Utc::now(),
@@ -193,7 +192,6 @@ async fn streaming_processing_example() -> Result<(), Box<dyn std::error::Error>
         options: JobOptions {
             chunking_strategy: ChunkingStrategy::Lines { max_lines: 20 },
             inference_config: InferenceParams::default(),
-            output_format: OutputFormat::PlainText,
             include_metadata: false,
         },
         created_at: chrono::Utc::now(),
@@ -455,7 +453,6 @@ fn create_text_job(filename: &str, content: &str) -> PipelineJob {
                 stop_sequences: vec!["\n\n".to_string()],
                 do_sample: true,
             },
-            output_format: OutputFormat::Markdown,
             include_metadata: true,
         },
         created_at: chrono::Utc::now(),
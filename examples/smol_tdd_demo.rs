@@ -44,13 +44,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             let lines: Vec<&str> = content.lines().take(10).collect();
             let sample_content = lines.join("\n");
 
-            let chunk = Chunk {
-                id: successful_results as u64,
-                line_start: 0,
-                line_end: lines.len(),
-                line_count: lines.len(),
-                content: sample_content.clone(),
-            };
+            let chunk = Chunk::new(0, lines.len(), sample_content.clone()).with_file_path(*chunk_path);
 
             let chunk_start = Instant::now();
             let summary = pipeline.generate_summary(&chunk)?;
@@ -0,0 +1,115 @@
+//! `bench`: time repeated summarization passes over the same chunks
+
+use std::fs;
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use log::info;
+
+use dobby_subagent_code_summarizer::parallel_agents::ParallelAgentSystem;
+use dobby_subagent_code_summarizer::chunking::{detect_language, Chunk};
+use dobby_subagent_code_summarizer::config::ChunkingMode;
+
+use crate::cli::{create_parent_dirs, fail_on, load_tokenizer, require_absolute, resolve_generation, resolve_model, BenchArgs, ChunkSettings, GlobalArgs, RunSettings};
+
+pub async fn run(args: &BenchArgs, global: &GlobalArgs) -> Result<()> {
+    let mut errors = Vec::new();
+    let settings = global.settings(&[&args.chunking, &args.run])?;
+    args.input.validate(&mut errors);
+    if args.iterations == 0 {
+        errors.push("--iterations must be greater than 0".to_string());
+    }
+    if args.max_chunks == Some(0) {
+        errors.push("--max-chunks must be greater than 0".to_string());
+    }
+    if let Some(output_file) = &args.output_file {
+        require_absolute("--output-file", output_file, &mut errors);
+    }
+    args.prompt.validate(&mut errors, true);
+    let chunking = ChunkSettings::resolve(&settings, &mut errors);
+    let run = RunSettings::resolve(&settings, &mut errors);
+    let generation_config = resolve_generation(&settings, &mut errors);
+    let model_config = resolve_model(&settings, &mut errors);
+    fail_on(errors)?;
+    create_parent_dirs(args.output_file.as_deref())?;
+
+    let prompt = args.prompt.template()?;
+    let (sources, _) = args.input.read_sources(run.max_concurrent.unwrap_or(0), |message| {
+        info!("{}", message);
+        Ok(())
+    })?;
+
+    let tokenizer = if chunking.mode == ChunkingMode::Tokens {
+        Some(load_tokenizer(&model_config)?)
+    } else {
+        None
+    };
+    let chunker = chunking.chunker(tokenizer.as_ref(), &model_config, &[(&prompt, generation_config.max_new_tokens)])?;
+    let mut chunks: Vec<Chunk> = Vec::new();
+    for file in &sources {
+        let detection = detect_language(Some(&file.path), &file.content);
+        chunks.extend(chunker.chunk_detected(&file.content, &detection, Some(&file.path))?);
+    }
+    chunks.truncate(args.max_chunks.unwrap_or(chunks.len()));
+    if chunks.is_empty() {
+        return Err(anyhow::anyhow!("No chunks to benchmark in {}", args.input.file));
+    }
+    let lines: usize = chunks.iter().map(|chunk| chunk.line_end - chunk.line_start).sum();
+    let prompted_chunks: Vec<(String, String)> = chunks
+        .iter()
+        .map(|chunk| (chunk.content.clone(), prompt.render(chunk)))
+        .collect();
+
+    let system = ParallelAgentSystem::new(run.parallel_config(&model_config, &generation_config, None))?;
+
+    // The warm-up pass loads sessions and fills caches; it is not timed
+    info!("🔥 Warm-up pass over {} chunks", chunks.len());
+    system.process_prompted_chunks_parallel(prompted_chunks.clone()).await?;
+
+    let mut passes: Vec<Duration> = Vec::with_capacity(args.iterations);
+    for iteration in 1..=args.iterations {
+        let start = Instant::now();
+        system.process_prompted_chunks_parallel(prompted_chunks.clone()).await?;
+        let elapsed = start.elapsed();
+        info!("⏱️ Pass {}/{}: {:?}", iteration, args.iterations, elapsed);
+        passes.push(elapsed);
+    }
+
+    let report = bench_report(&passes, chunks.len(), lines);
+    println!("\n⏱️ BENCHMARK: {} ({} agents, {:?})", model_config.name, run.agent_count, generation_config.strategy);
+    println!("Chunks per pass: {} ({} lines)", chunks.len(), lines);
+    println!("Passes: {}", args.iterations);
+    println!("Mean pass: {:.3}s (best {:.3}s, worst {:.3}s)",
+             report["mean_pass_s"].as_f64().unwrap_or_default(),
+             report["best_pass_s"].as_f64().unwrap_or_default(),
+             report["worst_pass_s"].as_f64().unwrap_or_default());
+    println!("Throughput: {:.2} chunks/second, {:.1} lines/second",
+             report["chunks_per_second"].as_f64().unwrap_or_default(),
+             report["lines_per_second"].as_f64().unwrap_or_default());
+
+    if let Some(output_file) = &args.output_file {
+        let mut report = report;
+        report["model"] = serde_json::json!(model_config.name);
+        report["agent_count"] = serde_json::json!(run.agent_count);
+        report["strategy"] = serde_json::json!(format!("{:?}", generation_config.strategy));
+        fs::write(output_file, serde_json::to_string_pretty(&report)?)?;
+        println!("📄 Benchmark report saved to: {}", output_file);
+    }
+    Ok(())
+}
+
+/// Pass timings and throughput at the mean pass time
+fn bench_report(passes: &[Duration], chunks: usize, lines: usize) -> serde_json::Value {
+    let seconds: Vec<f64> = passes.iter().map(Duration::as_secs_f64).collect();
+    let mean = seconds.iter().sum::<f64>() / seconds.len().max(1) as f64;
+    let per_second = |count: usize| if mean > 0.0 { count as f64 / mean } else { 0.0 };
+    serde_json::json!({
+        "chunks": chunks,
+        "lines": lines,
+        "passes_s": seconds,
+        "mean_pass_s": mean,
+        "best_pass_s": seconds.iter().cloned().fold(f64::INFINITY, f64::min),
+        "worst_pass_s": seconds.iter().cloned().fold(0.0, f64::max),
+        "chunks_per_second": per_second(chunks),
+        "lines_per_second": per_second(lines),
+    })
+}
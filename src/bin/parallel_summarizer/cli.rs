@@ -0,0 +1,754 @@
+//! Command-line arguments shared by the subcommands, and their validation
+
+use clap::{Args, Parser, Subcommand};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use anyhow::Result;
+use futures::TryStreamExt;
+use log::{error, info};
+use chrono::Utc;
+use toml::Value;
+
+use dobby_subagent_code_summarizer::parallel_agents::{ParallelAgentSystem, ParallelConfig};
+use dobby_subagent_code_summarizer::chunking::{detect_language, Chunk, Chunker, ChunkingContract, LineChunker, StreamingChunker, StructuralChunker, TokenBudgetChunker};
+use dobby_subagent_code_summarizer::config::{ChunkingMode, ConfigSource, GenerationConfig, LayeredConfig, ModelConfig, OutputFormat, SamplingStrategy, SummaryStrategy};
+use dobby_subagent_code_summarizer::eval::EmbedderKind;
+use dobby_subagent_code_summarizer::ingest::{is_glob, split_repo_dump, DumpedFile, SourceWalker};
+use dobby_subagent_code_summarizer::prompt::PromptTemplate;
+use dobby_subagent_code_summarizer::structured::OutputSchema;
+
+#[derive(Parser)]
+#[command(name = "parallel_summarizer")]
+#[command(about = "20-Agent Parallel Neural Code Summarizer with Advanced Generation Control")]
+pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Summarize a file, directory or glob and write the summaries to a file
+    Summarize(SummarizeArgs),
+    /// List known models and whether their files are present
+    Models,
+    /// Score summaries of a {code, reference} dataset (ROUGE-L, BLEU)
+    Eval(EvalArgs),
+    /// Measure summarization throughput on an input
+    Bench(BenchArgs),
+    /// Inspect the layered configuration
+    Config(ConfigArgs),
+}
+
+#[derive(Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Print every effective setting and the layer it came from
+    Show,
+}
+
+/// Model and generation settings, accepted before or after the subcommand
+///
+/// Flags override `DOBBY_*` variables, which override the selected profile,
+/// the config file and the built-in defaults.
+#[derive(Args)]
+pub struct GlobalArgs {
+    #[arg(long, global = true, help = "TOML config file (default: $DOBBY_CONFIG, else ./dobby.toml when present)")]
+    pub config: Option<PathBuf>,
+
+    #[arg(long, global = true, help = "Settings profile: fast, quality, or [profiles.<name>] from the config file (default: $DOBBY_PROFILE, else the file's `profile`)")]
+    pub profile: Option<String>,
+
+    #[command(flatten)]
+    pub model: ModelArgs,
+
+    #[command(flatten)]
+    pub generation: GenerationArgs,
+}
+
+#[derive(Args)]
+pub struct ModelArgs {
+    // === MODEL CONFIGURATION ===
+    #[arg(long = "model-name", global = true, help = "Model identifier (qwen2.5-0.5b-int4, smollm2-135m, smollm2-360m, custom); required by commands that run the model")]
+    pub model_name: Option<String>,
+
+    #[arg(long = "model-path", global = true, help = "Custom model path (overrides default for model-name)")]
+    pub model_path: Option<PathBuf>,
+
+    #[arg(long, global = true, help = "Tokenizer directory path")]
+    pub tokenizer_dir: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct GenerationArgs {
+    // === GENERATION STRATEGY ===
+    #[arg(long = "sampling-strategy", global = true, help = "Generation strategy [default: sampling]")]
+    pub sampling_strategy: Option<SamplingStrategy>,
+
+    // === SAMPLING PARAMETERS ===
+    #[arg(long = "temperature", global = true, help = "Sampling temperature (0.0-2.0, lower = more deterministic) [default: 0.35]")]
+    pub temperature: Option<f32>,
+
+    #[arg(long = "top-p", global = true, help = "Nucleus sampling threshold (0.0-1.0) [default: 0.85]")]
+    pub top_p: Option<f32>,
+
+    #[arg(long = "top-k", global = true, help = "Top-k sampling limit (1-1000) [default: 40]")]
+    pub top_k: Option<usize>,
+
+    // === BEAM SEARCH PARAMETERS ===
+    #[arg(long = "num-beams", global = true, help = "Number of beam candidates (1-10) [default: 3]")]
+    pub num_beams: Option<usize>,
+
+    #[arg(long = "length-penalty", global = true, help = "Length penalty for beam search (0.5-2.0) [default: 1.05]")]
+    pub length_penalty: Option<f32>,
+
+    #[arg(long = "early-stopping", global = true, num_args = 0..=1, require_equals = true, default_missing_value = "true",
+          help = "Stop beam search early; --early-stopping=false turns off a profile's or file's setting [default: false]")]
+    pub early_stopping: Option<bool>,
+
+    // === UNIVERSAL GENERATION CONTROLS ===
+    #[arg(long = "max-new-tokens", global = true, help = "Maximum tokens to generate (1-200) [default: 60]")]
+    pub max_new_tokens: Option<usize>,
+
+    #[arg(long = "min-length", global = true, help = "Minimum summary length (1-100) [default: 35]")]
+    pub min_length: Option<usize>,
+
+    #[arg(long = "repetition-penalty", global = true, help = "Repetition penalty (1.0-2.0) [default: 1.15]")]
+    pub repetition_penalty: Option<f32>,
+
+    #[arg(long = "no-repeat-ngram-size", global = true, help = "Prevent n-gram repetition (0-10) [default: 3]")]
+    pub no_repeat_ngram_size: Option<usize>,
+
+    #[arg(long = "stop-sequences", global = true, help = "Stop generation at these strings (comma-separated)", value_delimiter = ',')]
+    pub stop_sequences: Vec<String>,
+}
+
+/// Input files: one file (possibly a repository dump), a directory or a glob
+#[derive(Args)]
+pub struct InputArgs {
+    #[arg(short, long, help = "Input code file, directory, quoted glob pattern (e.g. 'src/**/*.rs') or - for standard input; directories honour .gitignore and .ignore")]
+    pub file: String,
+
+    #[arg(long = "include", help = "With a directory or glob --file, only read files matching these patterns (comma-separated, e.g. '*.rs,src/**/*.py')", value_delimiter = ',')]
+    pub include: Vec<String>,
+
+    #[arg(long = "exclude", help = "With a directory or glob --file, skip files matching these patterns (comma-separated)", value_delimiter = ',')]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct ChunkingArgs {
+    #[arg(long, help = "Lines of code per chunk (or chunking.loc in the config)")]
+    pub loc: Option<usize>,
+
+    #[arg(long = "chunking", help = "Chunking mode (semantic splits Rust, Python, TS/JS, Go and C files at top-level units, up to --loc lines; tokens fits chunks to the model context) [default: lines]")]
+    pub chunking: Option<ChunkingMode>,
+
+    #[arg(long = "overlap", help = "Context repeated from the previous chunk (lines; tokens with --chunking tokens) [default: 0]")]
+    pub overlap: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct OutputArgs {
+    #[arg(long = "format", help = "Output file format: text, markdown (per-file headings, line-range anchors), json, jsonl (full chunk metadata) or html (one self-contained page) [default: text, or json with --output-schema]")]
+    pub format: Option<OutputFormat>,
+}
+
+#[derive(Args)]
+pub struct PromptArgs {
+    #[arg(long, help = "Custom prompt for summarization; may use {{language}}, {{language_confidence}}, {{file_path}}, {{line_start}}, {{line_end}}, {{symbols}} and {{chunk}}", conflicts_with = "prompt_file")]
+    pub prompt: Option<String>,
+
+    #[arg(long, help = "Absolute path to file containing prompt (same template variables as --prompt)", conflicts_with = "prompt")]
+    pub prompt_file: Option<String>,
+}
+
+/// Agent pool settings for commands that run the model
+#[derive(Args)]
+pub struct RunArgs {
+    #[arg(long, help = "Number of parallel agents (or run.agent_count in the config)")]
+    pub agent_count: Option<usize>,
+
+    // === SYSTEM PARAMETERS ===
+    #[arg(long, help = "Maximum concurrent tasks")]
+    pub max_concurrent: Option<usize>,
+
+    #[arg(long = "max-chunk-time-s", help = "Per-chunk generation deadline in seconds; slower chunks return partial output [default: 2]")]
+    pub max_chunk_time_s: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct SummarizeArgs {
+    // === REQUIRED INPUTS (NO DEFAULTS) ===
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    #[arg(long, help = "Absolute path for final summary output")]
+    pub output_file: String,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+
+    #[arg(long, help = "Absolute path for progress/results log")]
+    pub results_file: String,
+
+    #[arg(long, help = "Absolute path of the run journal recording finished chunks as they complete [default: <output-file>.journal]")]
+    pub journal: Option<String>,
+
+    #[arg(long, help = "Reuse chunks the journal already finished under the same configuration instead of starting over")]
+    pub resume: bool,
+
+    #[arg(long = "dry-run", help = "Chunk the input, count real tokens and estimate wall time, then exit without summarizing (prints JSON with --format json; plans --strategy parallel runs only)", conflicts_with = "hierarchical")]
+    pub dry_run: bool,
+
+    #[arg(long = "calibrate", help = "With --dry-run: chunks to time on the model for the wall-time estimate, 0 for none [default: one wave of min(agent count, 10)]", requires = "dry_run", conflicts_with = "bench_report")]
+    pub calibrate: Option<usize>,
+
+    #[arg(long = "bench-report", help = "With --dry-run: estimate wall time from a `bench --output-file` report instead of calibrating", requires = "dry_run")]
+    pub bench_report: Option<PathBuf>,
+
+    // === PROCESSING PARAMETERS (NO DEFAULTS) ===
+    #[command(flatten)]
+    pub chunking: ChunkingArgs,
+
+    #[arg(long = "strategy", help = "parallel summarizes chunks independently; refine summarizes each file's chunks in order, carrying the summary so far (slower, more coherent; budgets use the model's tokenizer.json, or ~4 characters per token without one)", default_value = "parallel")]
+    pub strategy: SummaryStrategy,
+
+    #[arg(long = "hierarchical", help = "Roll chunk summaries up into file, module and crate summaries (map-reduce within the context window)")]
+    pub hierarchical: bool,
+
+    #[arg(long = "output-schema", help = "JSON schema file; every chunk summary is decoded as JSON valid against it", conflicts_with = "hierarchical")]
+    pub output_schema: Option<PathBuf>,
+
+    #[arg(long = "grounding-threshold", help = "Check that identifiers named in each summary occur in its chunk; regenerate summaries scoring below this (0.0-1.0)")]
+    pub grounding_threshold: Option<f64>,
+
+    #[arg(long = "grounding-retries", help = "Regeneration rounds for summaries below --grounding-threshold (0 = report only)", default_value = "1")]
+    pub grounding_retries: usize,
+
+    #[command(flatten)]
+    pub prompt: PromptArgs,
+
+    #[arg(long = "tasks", help = "JSON file of named tasks, e.g. {\"summary\": {\"prompt\": \"...\"}, \"risks\": {\"prompt\": \"...\", \"temperature\": 0.2}}; every task runs on every chunk", conflicts_with_all = ["prompt", "prompt_file", "hierarchical", "output_schema", "grounding_threshold"])]
+    pub tasks: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(Args)]
+pub struct EvalArgs {
+    #[arg(long, help = "JSONL dataset of {code, reference} pairs")]
+    pub dataset: PathBuf,
+
+    #[arg(long, help = "Absolute path for the JSON quality report")]
+    pub output_file: String,
+
+    #[arg(long, help = "Absolute path for progress/results log")]
+    pub results_file: String,
+
+    #[arg(long, value_enum, help = "Also score embedding similarity; bag-of-tokens compares hashed word counts by cosine")]
+    pub embedder: Option<EmbedderKind>,
+
+    #[command(flatten)]
+    pub chunking: ChunkingArgs,
+
+    #[command(flatten)]
+    pub prompt: PromptArgs,
+
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(Args)]
+pub struct BenchArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    #[command(flatten)]
+    pub chunking: ChunkingArgs,
+
+    #[arg(long, help = "Timed passes over all chunks (after one untimed warm-up pass)", default_value = "3")]
+    pub iterations: usize,
+
+    #[arg(long = "max-chunks", help = "Only time the first N chunks")]
+    pub max_chunks: Option<usize>,
+
+    #[arg(long, help = "Absolute path for a JSON report (printed only when omitted)")]
+    pub output_file: Option<String>,
+
+    #[command(flatten)]
+    pub prompt: PromptArgs,
+
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+/// Flags that override settings of the layered configuration
+pub trait FlagOverrides {
+    /// Settings given on the command line, by config key
+    fn overrides(&self) -> Vec<(&'static str, Value)>;
+}
+
+impl GlobalArgs {
+    /// Effective settings: defaults, config file, profile, `DOBBY_*` variables, then global and `command_flags`
+    pub fn settings(&self, command_flags: &[&dyn FlagOverrides]) -> Result<LayeredConfig> {
+        let mut settings = LayeredConfig::load(self.config.as_deref(), self.profile.as_deref(), std::env::vars())?;
+        let flags: [&dyn FlagOverrides; 2] = [&self.model, &self.generation];
+        for (key, value) in flags.iter().chain(command_flags).flat_map(|flags| flags.overrides()) {
+            settings.set(key, value, ConfigSource::Flag)?;
+        }
+        Ok(settings)
+    }
+}
+
+impl FlagOverrides for ModelArgs {
+    fn overrides(&self) -> Vec<(&'static str, Value)> {
+        let path = |path: &PathBuf| Value::String(path.display().to_string());
+        [
+            ("model.name", self.model_name.clone().map(Value::String)),
+            ("model.path", self.model_path.as_ref().map(path)),
+            ("model.tokenizer_dir", self.tokenizer_dir.as_ref().map(path)),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value?)))
+        .collect()
+    }
+}
+
+impl FlagOverrides for GenerationArgs {
+    fn overrides(&self) -> Vec<(&'static str, Value)> {
+        // Widen through the shortest decimal so `0.6` is shown as 0.6, not 0.6000000238418579
+        let float = |value: Option<f32>| value.map(|v| Value::Float(v.to_string().parse().unwrap_or(v as f64)));
+        let count = |value: Option<usize>| value.map(|v| Value::Integer(v as i64));
+        [
+            ("generation.sampling_strategy", self.sampling_strategy.as_ref().map(|strategy| Value::String(value_name(strategy)))),
+            ("generation.temperature", float(self.temperature)),
+            ("generation.top_p", float(self.top_p)),
+            ("generation.top_k", count(self.top_k)),
+            ("generation.num_beams", count(self.num_beams)),
+            ("generation.length_penalty", float(self.length_penalty)),
+            ("generation.early_stopping", self.early_stopping.map(Value::Boolean)),
+            ("generation.max_new_tokens", count(self.max_new_tokens)),
+            ("generation.min_length", count(self.min_length)),
+            ("generation.repetition_penalty", float(self.repetition_penalty)),
+            ("generation.no_repeat_ngram_size", count(self.no_repeat_ngram_size)),
+            ("generation.stop_sequences", (!self.stop_sequences.is_empty())
+                .then(|| Value::Array(self.stop_sequences.iter().cloned().map(Value::String).collect()))),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value?)))
+        .collect()
+    }
+}
+
+impl FlagOverrides for ChunkingArgs {
+    fn overrides(&self) -> Vec<(&'static str, Value)> {
+        [
+            ("chunking.loc", self.loc.map(|loc| Value::Integer(loc as i64))),
+            ("chunking.mode", self.chunking.map(|mode| Value::String(value_name(&mode)))),
+            ("chunking.overlap", self.overlap.map(|overlap| Value::Integer(overlap as i64))),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value?)))
+        .collect()
+    }
+}
+
+impl FlagOverrides for OutputArgs {
+    fn overrides(&self) -> Vec<(&'static str, Value)> {
+        self.format
+            .map(|format| ("output.format", Value::String(value_name(&format))))
+            .into_iter()
+            .collect()
+    }
+}
+
+impl FlagOverrides for RunArgs {
+    fn overrides(&self) -> Vec<(&'static str, Value)> {
+        [
+            ("run.agent_count", self.agent_count.map(|count| Value::Integer(count as i64))),
+            ("run.max_concurrent", self.max_concurrent.map(|count| Value::Integer(count as i64))),
+            ("run.max_chunk_time_s", self.max_chunk_time_s.map(|seconds| Value::Integer(seconds as i64))),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value?)))
+        .collect()
+    }
+}
+
+/// Command-line spelling of a value enum, as stored in the config
+fn value_name(value: &impl clap::ValueEnum) -> String {
+    value.to_possible_value().map(|name| name.get_name().to_string()).unwrap_or_default()
+}
+
+/// Resolve the model, recording missing names and directories in `errors`
+pub fn resolve_model(settings: &LayeredConfig, errors: &mut Vec<String>) -> ModelConfig {
+    let Some(model_config) = settings.model_config() else {
+        errors.push("--model-name (or model.name in the config) is required for this command".to_string());
+        return ModelConfig::from_name("", None, None);
+    };
+
+    // Check model directory exists
+    if !model_config.model_path.exists() {
+        errors.push(format!("Model directory does not exist: {}", model_config.model_path.display()));
+    }
+
+    // Check tokenizer directory exists
+    let tokenizer_path = model_config.tokenizer_path();
+    if !tokenizer_path.exists() {
+        errors.push(format!("Tokenizer directory does not exist: {}", tokenizer_path.display()));
+    }
+    model_config
+}
+
+/// Effective generation settings, recording out-of-range values in `errors`
+pub fn resolve_generation(settings: &LayeredConfig, errors: &mut Vec<String>) -> GenerationConfig {
+    let config = settings.generation_config();
+    if config.temperature < 0.0 || config.temperature > 2.0 {
+        errors.push(format!("--temperature must be between 0.0 and 2.0, got: {}", config.temperature));
+    }
+    if config.top_p < 0.0 || config.top_p > 1.0 {
+        errors.push(format!("--top-p must be between 0.0 and 1.0, got: {}", config.top_p));
+    }
+    if config.top_k == 0 || config.top_k > 1000 {
+        errors.push(format!("--top-k must be between 1 and 1000, got: {}", config.top_k));
+    }
+    if config.num_beams == 0 || config.num_beams > 10 {
+        errors.push(format!("--num-beams must be between 1 and 10, got: {}", config.num_beams));
+    }
+    if config.length_penalty < 0.5 || config.length_penalty > 2.0 {
+        errors.push(format!("--length-penalty must be between 0.5 and 2.0, got: {}", config.length_penalty));
+    }
+    if config.max_new_tokens == 0 || config.max_new_tokens > 200 {
+        errors.push(format!("--max-new-tokens must be between 1 and 200, got: {}", config.max_new_tokens));
+    }
+    if config.min_length > config.max_new_tokens {
+        errors.push(format!("--min-length ({}) cannot be greater than --max-new-tokens ({})",
+                          config.min_length, config.max_new_tokens));
+    }
+    if config.repetition_penalty < 1.0 || config.repetition_penalty > 2.0 {
+        errors.push(format!("--repetition-penalty must be between 1.0 and 2.0, got: {}", config.repetition_penalty));
+    }
+    if config.no_repeat_ngram_size > 10 {
+        errors.push(format!("--no-repeat-ngram-size must be <= 10, got: {}", config.no_repeat_ngram_size));
+    }
+    config
+}
+
+/// Effective chunking settings
+pub struct ChunkSettings {
+    pub loc: usize,
+    pub mode: ChunkingMode,
+    pub overlap: usize,
+}
+
+impl ChunkSettings {
+    pub fn resolve(settings: &LayeredConfig, errors: &mut Vec<String>) -> Self {
+        let loc = settings.usize("chunking.loc").unwrap_or_else(|| {
+            errors.push("--loc (or chunking.loc in the config) is required for this command".to_string());
+            0
+        });
+        let chunking = Self {
+            loc,
+            mode: settings.chunking_mode(),
+            overlap: settings.usize("chunking.overlap").unwrap_or(0),
+        };
+
+        // Validate loc is reasonable
+        if settings.get("chunking.loc").is_some() && chunking.loc == 0 {
+            errors.push("--loc must be greater than 0".to_string());
+        }
+        if chunking.loc > 50000 {
+            errors.push("--loc should be less than 50000 lines for optimal processing".to_string());
+        }
+        if chunking.mode != ChunkingMode::Tokens && chunking.loc > 0 && chunking.overlap >= chunking.loc {
+            errors.push(format!("--overlap ({}) must be smaller than --loc ({})", chunking.overlap, chunking.loc));
+        }
+        chunking
+    }
+
+    /// Chunker for the chosen mode; token mode needs `tokenizer` and budgets
+    /// each chunk so every one of `prompts` (template, max_new_tokens) fits
+    pub fn chunker(
+        &self,
+        tokenizer: Option<&Arc<tokenizers::Tokenizer>>,
+        model_config: &ModelConfig,
+        prompts: &[(&PromptTemplate, usize)],
+    ) -> Result<Box<dyn Chunker>> {
+        Ok(match (self.mode, tokenizer) {
+            (ChunkingMode::Tokens, Some(tokenizer)) => {
+                let chunker = TokenBudgetChunker::for_prompts(Arc::clone(tokenizer), model_config.context_window(), prompts)?
+                    .with_overlap_tokens(self.overlap);
+                info!("🔢 Token budget per chunk: up to {} (context window {}, less each file's path and symbols)",
+                      chunker.max_chunk_tokens(), model_config.context_window());
+                Box::new(chunker)
+            }
+            (ChunkingMode::Semantic, _) => Box::new(StructuralChunker::new(self.loc).with_overlap(self.overlap)),
+            (ChunkingMode::Lines | ChunkingMode::Tokens, _) => Box::new(LineChunker::new(self.loc).with_overlap(self.overlap)),
+        })
+    }
+}
+
+/// Effective agent pool settings
+pub struct RunSettings {
+    pub agent_count: usize,
+    pub max_concurrent: Option<usize>,
+    pub max_chunk_time_s: u64,
+}
+
+impl RunSettings {
+    pub fn resolve(settings: &LayeredConfig, errors: &mut Vec<String>) -> Self {
+        let agent_count = settings.usize("run.agent_count").unwrap_or_else(|| {
+            errors.push("--agent-count (or run.agent_count in the config) is required for this command".to_string());
+            1
+        });
+        let run = Self {
+            agent_count,
+            max_concurrent: settings.usize("run.max_concurrent"),
+            max_chunk_time_s: settings.usize("run.max_chunk_time_s").unwrap_or(0) as u64,
+        };
+
+        // Validate agent count
+        if run.agent_count == 0 {
+            errors.push("--agent-count must be greater than 0".to_string());
+        }
+        if run.agent_count > 100 {
+            errors.push("--agent-count should be less than 100 for system stability".to_string());
+        }
+        if run.max_chunk_time_s == 0 {
+            errors.push("--max-chunk-time-s must be greater than 0".to_string());
+        }
+        run
+    }
+
+    /// Parallel system settings shared by every command that runs the model
+    pub fn parallel_config(
+        &self,
+        model_config: &ModelConfig,
+        generation_config: &GenerationConfig,
+        output_schema: Option<OutputSchema>,
+    ) -> ParallelConfig {
+        ParallelConfig {
+            agent_count: self.agent_count,
+            model_dir: model_config.model_path.clone(),
+            tokenizer_dir: model_config.tokenizer_path(),
+            max_concurrent: self.max_concurrent.unwrap_or_else(num_cpus::get),
+            generation_config: generation_config.clone(),
+            max_chunk_processing_time: std::time::Duration::from_secs(self.max_chunk_time_s),
+            output_schema: output_schema.map(Arc::new),
+        }
+    }
+}
+
+/// `--file` value that reads standard input
+pub const STDIN_INPUT: &str = "-";
+
+impl InputArgs {
+    pub fn validate(&self, errors: &mut Vec<String>) {
+        // Glob patterns are matched when walked
+        if !self.is_stdin() && !Path::new(&self.file).exists() && !is_glob(&self.file) {
+            errors.push(format!("Input file or directory does not exist: {}", self.file));
+        }
+    }
+
+    pub fn is_stdin(&self) -> bool {
+        self.file == STDIN_INPUT
+    }
+
+    /// Standard input, or a single file over the in-memory size contract
+    /// (`ChunkingContract::max_file_size_mb`)
+    pub fn is_large(&self) -> bool {
+        let limit = ChunkingContract::default().max_file_size_mb as u64 * 1024 * 1024;
+        self.is_stdin() || fs::metadata(&self.file).is_ok_and(|metadata| metadata.is_file() && metadata.len() > limit)
+    }
+
+    /// Chunk the input while reading it, `max_lines` lines per chunk
+    ///
+    /// The input is never held whole, so repository dumps are not split and
+    /// binary or generated content is not skipped. The first chunk settles
+    /// the language for the rest.
+    pub async fn stream_chunks(&self, max_lines: usize, mut log: impl FnMut(&str) -> Result<()>) -> Result<Vec<Chunk>> {
+        let chunker = StreamingChunker::new(max_lines);
+        let (path, mut stream) = if self.is_stdin() {
+            (None, chunker.chunk_reader(tokio::io::BufReader::new(tokio::io::stdin())))
+        } else {
+            (Some(self.file.as_str()), chunker.chunk_file(&self.file).await?)
+        };
+
+        let mut chunks = Vec::new();
+        let mut detection = None;
+        let mut total_bytes = 0;
+        while let Some(chunk) = stream.try_next().await? {
+            let detection = detection.get_or_insert_with(|| detect_language(path, &chunk.content));
+            total_bytes += chunk.content.len();
+            let chunk = chunk.with_detection(detection);
+            chunks.push(match path {
+                Some(path) => chunk.with_file_path(path),
+                None => chunk,
+            });
+        }
+        log(&format!("🌊 Streamed {}: {} chunks ({} bytes)", path.unwrap_or("standard input"), chunks.len(), total_bytes))?;
+        if let Some(detection) = detection {
+            log(&format!("🔤 Language: {}", detection))?;
+        }
+        Ok(chunks)
+    }
+
+    /// Read the input, logging each step through `log`
+    ///
+    /// Returns the files and whether they came from more than one file
+    /// (a repository dump, directory or glob).
+    pub fn read_sources(&self, threads: usize, mut log: impl FnMut(&str) -> Result<()>) -> Result<(Vec<DumpedFile>, bool)> {
+        if self.is_stdin() || Path::new(&self.file).is_file() {
+            let code = if self.is_stdin() {
+                std::io::read_to_string(std::io::stdin())?
+            } else {
+                fs::read_to_string(&self.file)?
+            };
+            log(&format!("📖 Read file: {} ({} bytes, {} lines)", self.file, code.len(), code.lines().count()))?;
+
+            // Repository dumps are split per file
+            return Ok(match split_repo_dump(&code) {
+                Some(files) => {
+                    log(&format!("📦 Repository dump detected: {} files", files.len()))?;
+                    (files, true)
+                }
+                None => (vec![DumpedFile { path: self.file.clone(), content: code }], false),
+            });
+        }
+
+        let walked = SourceWalker::new()
+            .with_includes(self.include.clone())
+            .with_excludes(self.exclude.clone())
+            .with_threads(threads)
+            .walk(&self.file)?;
+        for (path, reason) in &walked.skipped {
+            log(&format!("⏭️ Skipped {}: {}", path, reason))?;
+        }
+        if walked.files.is_empty() {
+            return Err(anyhow::anyhow!("No input files found for {}", self.file));
+        }
+        let total_bytes: usize = walked.files.iter().map(|file| file.content.len()).sum();
+        log(&format!("📂 Found {} files under {} ({} bytes)", walked.files.len(), self.file, total_bytes))?;
+        Ok((walked.files, true))
+    }
+}
+
+impl PromptArgs {
+    pub fn is_set(&self) -> bool {
+        self.prompt.is_some() || self.prompt_file.is_some()
+    }
+
+    /// Check the prompt file; `required` reports a missing prompt
+    pub fn validate(&self, errors: &mut Vec<String>, required: bool) {
+        match (&self.prompt, &self.prompt_file) {
+            (Some(_), Some(_)) => errors.push("Cannot use both --prompt and --prompt-file simultaneously".to_string()),
+            (None, None) if required => errors.push("Either --prompt or --prompt-file is required".to_string()),
+            (None, Some(file)) => {
+                if !Path::new(file).exists() {
+                    errors.push(format!("Prompt file does not exist: {}", file));
+                }
+                if !Path::new(file).is_absolute() {
+                    errors.push(format!("--prompt-file must be absolute path, got: {}", file));
+                }
+            }
+            _ => {} // Valid case: prompt provided
+        }
+    }
+
+    /// Inline or file prompt text, if either was given
+    pub fn text(&self) -> Result<Option<String>> {
+        match (self.prompt.as_ref(), self.prompt_file.as_ref()) {
+            (Some(p), _) => Ok(Some(p.clone())),
+            (None, Some(file_path)) => Ok(Some(
+                fs::read_to_string(file_path)
+                    .map_err(|e| anyhow::anyhow!("Failed to read prompt file {}: {}", file_path, e))?
+                    .trim()
+                    .to_string(),
+            )),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Parsed prompt; unknown template variables fail here, before any model is loaded
+    pub fn template(&self) -> Result<PromptTemplate> {
+        Ok(PromptTemplate::parse(&self.text()?.unwrap_or_default())?)
+    }
+
+    pub fn source_name(&self) -> &'static str {
+        if self.prompt_file.is_some() { "file" } else { "inline" }
+    }
+}
+
+/// Check that `path` is absolute, recording `flag` in `errors` otherwise
+pub fn require_absolute(flag: &str, path: &str, errors: &mut Vec<String>) {
+    if !path.starts_with('/') {
+        errors.push(format!("{} must be absolute path (start with '/'), got: {}", flag, path));
+    }
+}
+
+/// Report validation errors and fail when there are any
+pub fn fail_on(errors: Vec<String>) -> Result<()> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    error!("❌ VALIDATION ERRORS:");
+    for err in &errors {
+        error!("   {}", err);
+    }
+    error!("\n📖 Use --help for complete usage information");
+    error!("   cargo run --bin parallel_summarizer -- <command> --help");
+    Err(anyhow::anyhow!("Validation failed: {}", errors.join(", ")))
+}
+
+/// Create the parent directory of each output path
+pub fn create_parent_dirs<'a>(paths: impl IntoIterator<Item = &'a str>) -> Result<()> {
+    for path in paths {
+        if let Some(parent) = Path::new(path).parent() {
+            fs::create_dir_all(parent)?;
+            info!("✅ Created directory: {}", parent.display());
+        }
+    }
+    Ok(())
+}
+
+/// The tokenizer of `model_config`, shared by chunkers and budget measures
+pub fn load_tokenizer(model_config: &ModelConfig) -> Result<Arc<tokenizers::Tokenizer>> {
+    let tokenizer_file = model_config.tokenizer_path().join("tokenizer.json");
+    let tokenizer = tokenizers::Tokenizer::from_file(&tokenizer_file)
+        .map_err(|e| anyhow::anyhow!("Failed to load tokenizer {}: {}", tokenizer_file.display(), e))?;
+    Ok(Arc::new(tokenizer))
+}
+
+/// Ctrl-C cancels in-flight generations; partial summaries are still written
+pub fn cancel_on_interrupt(system: &ParallelAgentSystem) {
+    let cancellation = system.cancellation_token();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            error!("🛑 Interrupt received - cancelling in-flight chunks");
+            cancellation.cancel();
+        }
+    });
+}
+
+/// Write progress to results file
+pub fn write_progress(results_file: &str, message: &str) -> Result<()> {
+    let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S UTC");
+    let log_entry = format!("[{}] {}\n", timestamp, message);
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(results_file)?
+        .write_all(log_entry.as_bytes())?;
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! `eval`: summarize a {code, reference} dataset and score it
+
+use std::fs;
+use std::sync::Arc;
+use anyhow::Result;
+use log::info;
+
+use dobby_subagent_code_summarizer::parallel_agents::ParallelAgentSystem;
+use dobby_subagent_code_summarizer::config::ChunkingMode;
+use dobby_subagent_code_summarizer::eval::{load_dataset, EvalHarness};
+
+use crate::cli::{create_parent_dirs, fail_on, load_tokenizer, require_absolute, resolve_generation, resolve_model, write_progress, ChunkSettings, EvalArgs, GlobalArgs, RunSettings};
+
+/// Summarize every dataset item and write the scored report as JSON
+pub async fn run(args: &EvalArgs, global: &GlobalArgs) -> Result<()> {
+    let mut errors = Vec::new();
+    let settings = global.settings(&[&args.chunking, &args.run])?;
+    if !args.dataset.is_file() {
+        errors.push(format!("Dataset file does not exist: {}", args.dataset.display()));
+    }
+    require_absolute("--output-file", &args.output_file, &mut errors);
+    require_absolute("--results-file", &args.results_file, &mut errors);
+    args.prompt.validate(&mut errors, true);
+    // Samples are chunked like `summarize` input once chunking is configured, and sent whole otherwise
+    let chunking = (settings.get("chunking.loc").is_some() || settings.get("chunking.mode").is_some())
+        .then(|| ChunkSettings::resolve(&settings, &mut errors));
+    let run = RunSettings::resolve(&settings, &mut errors);
+    let generation_config = resolve_generation(&settings, &mut errors);
+    let model_config = resolve_model(&settings, &mut errors);
+    fail_on(errors)?;
+    create_parent_dirs([args.output_file.as_str(), args.results_file.as_str()])?;
+
+    let prompt = args.prompt.template()?;
+
+    let items = load_dataset(&args.dataset)?;
+    info!("🧪 Evaluating {} dataset items from {}", items.len(), args.dataset.display());
+    write_progress(&args.results_file, &format!("🧪 Evaluating {} dataset items", items.len()))?;
+
+    let mut harness = EvalHarness::new(prompt.clone()).with_label(format!("{} {:?}", model_config.name, generation_config.strategy));
+    if let Some(chunking) = &chunking {
+        let tokenizer = if chunking.mode == ChunkingMode::Tokens {
+            Some(load_tokenizer(&model_config)?)
+        } else {
+            None
+        };
+        let chunker = chunking.chunker(tokenizer.as_ref(), &model_config, &[(&prompt, generation_config.max_new_tokens)])?;
+        write_progress(&args.results_file, &format!("🧩 Chunking samples with the {} chunker", chunker.name()))?;
+        harness = harness.with_chunker(Arc::from(chunker));
+    }
+    if let Some(embedder) = args.embedder {
+        harness = harness.with_embedder(embedder.build());
+    }
+
+    let system = ParallelAgentSystem::new(run.parallel_config(&model_config, &generation_config, None))?;
+    let report = harness.run(&items, &system).await?;
+
+    fs::write(&args.output_file, serde_json::to_string_pretty(&report.to_json())?)?;
+    let embedding = report
+        .mean_embedding_similarity()
+        .map(|similarity| format!(", embedding similarity {:.4}", similarity))
+        .unwrap_or_default();
+    info!("📈 ROUGE-L {:.4}, BLEU {:.4}{}", report.mean_rouge_l(), report.mean_bleu(), embedding);
+    write_progress(&args.results_file, &format!("📈 ROUGE-L {:.4}, BLEU {:.4}{} over {} items",
+                                                report.mean_rouge_l(), report.mean_bleu(), embedding, report.items.len()))?;
+    write_progress(&args.results_file, &format!("💾 Evaluation report saved to: {}", args.output_file))?;
+    println!("📄 Evaluation report saved to: {}", args.output_file);
+    Ok(())
+}
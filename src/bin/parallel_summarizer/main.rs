@@ -0,0 +1,40 @@
+//! 20-Agent Parallel Code Summarizer CLI
+//!
+//! Real neural inference using 20 independent agents for maximum parallelism.
+//! Subcommands: `summarize` files, list `models`, `eval` a dataset and `bench`
+//! throughput. Model and generation options are global; `config show` prints
+//! the settings layered from defaults, `dobby.toml`, a profile, `DOBBY_*`
+//! variables and flags.
+
+mod bench;
+mod cli;
+mod eval;
+mod models;
+mod plan;
+mod summarize;
+
+use clap::Parser;
+use anyhow::Result;
+use env_logger::Env;
+
+use cli::{Cli, Command, ConfigCommand};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize logger
+    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+
+    let cli = Cli::parse();
+    match &cli.command {
+        Command::Summarize(args) => summarize::run(args, &cli.global).await,
+        Command::Models => models::run(&cli.global),
+        Command::Eval(args) => eval::run(args, &cli.global).await,
+        Command::Bench(args) => bench::run(args, &cli.global).await,
+        Command::Config(config) => match &config.command {
+            ConfigCommand::Show => {
+                println!("{}", cli.global.settings(&[])?.render());
+                Ok(())
+            }
+        },
+    }
+}
@@ -0,0 +1,61 @@
+//! `models`: known models, their resolved paths and whether their files are present
+
+use std::path::Path;
+use anyhow::Result;
+
+use dobby_subagent_code_summarizer::config::{ModelConfig, KNOWN_MODELS};
+
+use crate::cli::GlobalArgs;
+
+pub fn run(global: &GlobalArgs) -> Result<()> {
+    let selected_model = global.settings(&[])?.model_config();
+
+    // Known names first, then custom directories under ./models, then the one asked for
+    let mut names: Vec<String> = KNOWN_MODELS.iter().map(|name| name.to_string()).collect();
+    if let Ok(entries) = std::fs::read_dir("./models") {
+        let mut custom: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| !names.contains(name))
+            .collect();
+        custom.sort();
+        names.extend(custom);
+    }
+    if let Some(model) = &selected_model {
+        if !names.contains(&model.name) {
+            names.push(model.name.clone());
+        }
+    }
+
+    println!("{:<2} {:<20} {:<8} {:<10} {:>7}  PATH", "", "NAME", "MODEL", "TOKENIZER", "CONTEXT");
+    for name in &names {
+        // Path overrides only apply to the selected model
+        let (selected, config) = match &selected_model {
+            Some(model) if &model.name == name => (true, model.clone()),
+            _ => (false, ModelConfig::from_name(name, None, None)),
+        };
+        let weights = has_weights(&config.model_path);
+        let tokenizer = config.tokenizer_path().join("tokenizer.json").is_file();
+        println!("{:<2} {:<20} {:<8} {:<10} {:>7}  {}",
+                 if selected { "*" } else { "" },
+                 name,
+                 if weights { "ok" } else { "missing" },
+                 if tokenizer { "ok" } else { "missing" },
+                 config.context_window(),
+                 config.model_path.display());
+    }
+    Ok(())
+}
+
+/// Whether `dir` holds model weights (`*.safetensors` or `*.onnx`)
+fn has_weights(dir: &Path) -> bool {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries.filter_map(|entry| entry.ok()).any(|entry| {
+                let path = entry.path();
+                path.extension().is_some_and(|ext| ext == "safetensors" || ext == "onnx")
+            })
+        })
+        .unwrap_or(false)
+}
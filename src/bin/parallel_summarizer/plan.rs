@@ -0,0 +1,104 @@
+//! `summarize --dry-run`: token counts and a wall-time estimate without a full model pass
+
+use std::path::Path;
+use std::time::Instant;
+use anyhow::Result;
+use log::info;
+use tokenizers::Tokenizer;
+
+use dobby_subagent_code_summarizer::chunking::{prompt_input_tokens, Chunk};
+use dobby_subagent_code_summarizer::config::OutputFormat;
+use dobby_subagent_code_summarizer::journal;
+use dobby_subagent_code_summarizer::parallel_agents::ParallelAgentSystem;
+use dobby_subagent_code_summarizer::plan::{RunPlan, Throughput};
+use dobby_subagent_code_summarizer::prompt::PromptTemplate;
+
+use crate::cli::{write_progress, SummarizeArgs};
+use crate::summarize::{journal_path, run_config_hash, Resolved};
+
+/// Count every chunk's tokens, time a calibration pass or read a benchmark,
+/// and print the plan (as JSON with `--format json` or `jsonl`)
+pub async fn run(
+    args: &SummarizeArgs,
+    resolved: &Resolved,
+    chunks: &[Chunk],
+    skipped: Vec<(String, String)>,
+    tokenizer: &Tokenizer,
+) -> Result<()> {
+    let Resolved { prompt, tasks, model_config, generation_config, run, output_schema, format, .. } = resolved;
+    // Counted as the token chunker budgets them, so a chunk it sized never shows as over budget
+    let input_tokens = |template: &PromptTemplate, chunk: &Chunk| prompt_input_tokens(tokenizer, template, chunk);
+
+    // With --resume, chunks the journal already finished would not run
+    let finished = if args.resume {
+        journal::recorded(Path::new(&journal_path(args)), &run_config_hash(resolved))?
+    } else {
+        Default::default()
+    };
+
+    let mut plan = RunPlan::new(model_config.context_window());
+    for chunk in chunks {
+        let prompts: Vec<(usize, usize)> = if tasks.is_empty() {
+            vec![(input_tokens(prompt, chunk), generation_config.max_new_tokens)]
+        } else {
+            tasks.iter().map(|task| (input_tokens(&task.template, chunk), task.generation.max_new_tokens)).collect()
+        };
+        plan.add_chunk(chunk, &prompts, finished.contains_key(chunk.id().as_str()));
+    }
+    for (path, reason) in skipped {
+        plan.skip_file(path, reason);
+    }
+
+    let throughput = match &args.bench_report {
+        Some(report) => Some(Throughput::from_bench_report(report, &model_config.name, run.agent_count)?),
+        None => {
+            // One wave of concurrent chunks measures throughput at full parallelism
+            let wave = args.calibrate.unwrap_or(run.agent_count.min(10));
+            let sample: Vec<&Chunk> = chunks
+                .iter()
+                .filter(|chunk| !finished.contains_key(chunk.id().as_str()))
+                .take(wave)
+                .collect();
+            if sample.is_empty() {
+                None
+            } else {
+                info!("⏱️ Calibrating on {} chunks...", sample.len());
+                write_progress(&args.results_file, &format!("⏱️ Calibrating on {} chunks...", sample.len()))?;
+                let system = ParallelAgentSystem::new(run.parallel_config(model_config, generation_config, output_schema.clone()))?;
+                let start = Instant::now();
+                if tasks.is_empty() {
+                    let prompted = sample.iter().map(|chunk| (chunk.content.clone(), prompt.render(chunk))).collect();
+                    system.process_prompted_chunks_parallel(prompted).await?;
+                } else {
+                    let tasked = sample
+                        .iter()
+                        .map(|chunk| {
+                            let prompts = tasks.iter().map(|task| (task.template.render(chunk), task.generation.clone())).collect();
+                            (chunk.content.clone(), prompts)
+                        })
+                        .collect();
+                    system.process_chunk_tasks_parallel(tasked).await?;
+                }
+                let elapsed = start.elapsed().as_secs_f64();
+                Some(Throughput {
+                    chunks_per_second: if elapsed > 0.0 { sample.len() as f64 / elapsed } else { 0.0 },
+                    source: format!("calibrated on {} chunks", sample.len()),
+                })
+            }
+        }
+    };
+    let plan = plan.with_throughput(throughput);
+
+    write_progress(&args.results_file, &format!("🧾 Dry run: {} chunks, {} input tokens, up to {} output tokens",
+                                                plan.to_run().count(), plan.input_tokens(), plan.output_tokens()))?;
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&plan.to_json())?),
+        OutputFormat::Jsonl => println!("{}", plan.to_json()),
+        _ => {
+            println!("\n🧾 DRY RUN: {} ({:?})", model_config.name, generation_config.strategy);
+            println!("=====================");
+            println!("{}", plan.render());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,518 @@
+//! `summarize`: chunk the input, summarize every chunk and write the summaries
+
+use std::fs;
+use std::time::Duration;
+use anyhow::Result;
+use log::{debug, info, warn};
+
+use dobby_subagent_code_summarizer::parallel_agents::ParallelAgentSystem;
+use dobby_subagent_code_summarizer::chunking::{detect_language, overlap, Chunk, ChunkId};
+use dobby_subagent_code_summarizer::config::{ChunkingMode, GenerationConfig, ModelConfig, OutputFormat, SummaryStrategy};
+use dobby_subagent_code_summarizer::ingest::classify_text;
+use dobby_subagent_code_summarizer::summarization::{file_summaries, GroundingCheck, HierarchicalSummarizer, LevelBudgets, RefineSummarizer};
+use dobby_subagent_code_summarizer::structured::OutputSchema;
+use dobby_subagent_code_summarizer::output::{ChunkReport, SummaryReport};
+use dobby_subagent_code_summarizer::journal::{config_hash, JournalEntry, RunJournal};
+use dobby_subagent_code_summarizer::prompt::{load_tasks, render_task_outputs, PromptTask, PromptTemplate};
+
+use crate::cli::{cancel_on_interrupt, create_parent_dirs, fail_on, load_tokenizer, require_absolute, resolve_generation, resolve_model, write_progress, ChunkSettings, GlobalArgs, RunSettings, SummarizeArgs};
+
+/// Everything `summarize` needs once its arguments are validated
+pub struct Resolved {
+    pub prompt: PromptTemplate,
+    pub tasks: Vec<PromptTask>,
+    pub model_config: ModelConfig,
+    pub generation_config: GenerationConfig,
+    pub chunking: ChunkSettings,
+    pub run: RunSettings,
+    pub output_schema: Option<OutputSchema>,
+    pub format: OutputFormat,
+    /// Config file and profile the settings came from, when any
+    pub origin: Option<String>,
+}
+
+/// Validate all compulsory arguments of `summarize`
+fn validate_args(args: &SummarizeArgs, global: &GlobalArgs) -> Result<Resolved> {
+    let mut errors = Vec::new();
+    let settings = global.settings(&[&args.chunking, &args.run, &args.output])?;
+
+    args.input.validate(&mut errors);
+    require_absolute("--output-file", &args.output_file, &mut errors);
+    require_absolute("--results-file", &args.results_file, &mut errors);
+    if let Some(journal) = &args.journal {
+        require_absolute("--journal", journal, &mut errors);
+    }
+
+    // Validate prompt input (exactly one required, unless tasks bring their own)
+    args.prompt.validate(&mut errors, false);
+    match &args.tasks {
+        Some(tasks) if !tasks.exists() => errors.push(format!("Tasks file does not exist: {}", tasks.display())),
+        None if !args.prompt.is_set() => errors.push("Either --prompt, --prompt-file or --tasks is required".to_string()),
+        _ => {}
+    }
+
+    let chunking = ChunkSettings::resolve(&settings, &mut errors);
+    let run = RunSettings::resolve(&settings, &mut errors);
+    let generation_config = resolve_generation(&settings, &mut errors);
+
+    if let Some(threshold) = args.grounding_threshold {
+        if !(0.0..=1.0).contains(&threshold) {
+            errors.push(format!("--grounding-threshold must be between 0.0 and 1.0, got: {}", threshold));
+        }
+        // A refined summary covers earlier chunks too, so one chunk cannot ground it
+        if args.strategy == SummaryStrategy::Refine {
+            errors.push("--grounding-threshold requires --strategy parallel".to_string());
+        }
+    }
+    if args.tasks.is_some() && args.strategy == SummaryStrategy::Refine {
+        errors.push("--tasks requires --strategy parallel".to_string());
+    }
+    // Refine steps depend on every step before them, so there is nothing per chunk to reuse
+    if args.resume && args.strategy == SummaryStrategy::Refine {
+        errors.push("--resume requires --strategy parallel".to_string());
+    }
+    // A dry run plans one pass over the chunks; refine steps depend on summaries it never produces
+    if args.dry_run && args.strategy == SummaryStrategy::Refine {
+        errors.push("--dry-run requires --strategy parallel".to_string());
+    }
+    if let Some(report) = &args.bench_report {
+        if !report.is_file() {
+            errors.push(format!("Benchmark report does not exist: {}", report.display()));
+        }
+    }
+
+    let model_config = resolve_model(&settings, &mut errors);
+    fail_on(errors)?;
+    create_parent_dirs([args.output_file.as_str(), args.results_file.as_str()])?;
+    create_parent_dirs(args.journal.as_deref())?;
+
+    // Tasks inherit the generation settings above unless they override them
+    let tasks = match &args.tasks {
+        Some(path) => load_tasks(path, &generation_config)?,
+        None => Vec::new(),
+    };
+
+    let prompt = match args.prompt.text()? {
+        Some(prompt) => prompt,
+        // With tasks, the longest task prompt sizes the chunks
+        None => tasks
+            .iter()
+            .map(|task| task.template.source())
+            .max_by_key(|source| source.len())
+            .unwrap_or_default()
+            .to_string(),
+    };
+    // Unknown template variables fail here, before any model is loaded
+    let mut prompt = PromptTemplate::parse(&prompt)?;
+
+    // Structured mode: the schema is compiled up front and described in the prompt
+    let output_schema = args.output_schema.as_ref().map(OutputSchema::from_file).transpose()?;
+    if let Some(schema) = &output_schema {
+        prompt.append_text(&format!("\n{}", schema.prompt_hint()));
+    }
+
+    let origin = match (settings.file(), settings.profile()) {
+        (None, None) => None,
+        (file, profile) => Some(format!("{} (profile {})",
+            file.map(|path| path.display().to_string()).unwrap_or_else(|| "no file".to_string()),
+            profile.unwrap_or("none"))),
+    };
+
+    // Structured summaries are JSON documents, so JSON is their natural container
+    let format = settings.output_format().unwrap_or(match args.output_schema {
+        Some(_) => OutputFormat::Json,
+        None => OutputFormat::Text,
+    });
+
+    Ok(Resolved { prompt, tasks, model_config, generation_config, chunking, run, output_schema, format, origin })
+}
+
+pub async fn run(args: &SummarizeArgs, global: &GlobalArgs) -> Result<()> {
+    // Phase 0: Validate all compulsory arguments and get configurations
+    let resolved = validate_args(args, global)?;
+    let Resolved { prompt, tasks, model_config, generation_config, chunking, run, output_schema, format, origin } = &resolved;
+
+    // Initialize progress file
+    write_progress(&args.results_file, "🚀 Starting 20-Agent Parallel Code Summarizer")?;
+    write_progress(&args.results_file, &format!("📁 Input file: {}", args.input.file))?;
+    write_progress(&args.results_file, &format!("📄 Output file: {}", args.output_file))?;
+    write_progress(&args.results_file, &format!("📝 Output format: {:?}", format))?;
+    write_progress(&args.results_file, &format!("📊 Results file: {}", args.results_file))?;
+    if let Some(origin) = &origin {
+        write_progress(&args.results_file, &format!("🎛️  Config: {}", origin))?;
+    }
+    write_progress(&args.results_file, &format!("🔢 Lines per chunk: {}", chunking.loc))?;
+    write_progress(&args.results_file, &format!("✂️  Chunking: {:?}", chunking.mode))?;
+    write_progress(&args.results_file, &format!("🤖 Agent count: {}", run.agent_count))?;
+    write_progress(&args.results_file, &format!("🧠 Model: {}", model_config.name))?;
+    write_progress(&args.results_file, &format!("🧵 Strategy: {:?} ({:?} generation)", args.strategy, generation_config.strategy))?;
+    write_progress(&args.results_file, &format!("🌡️  Temperature: {:.2}", generation_config.temperature))?;
+    write_progress(&args.results_file, &format!("💭 Prompt source: {}",
+        if args.tasks.is_some() { "tasks" } else { args.prompt.source_name() }))?;
+    if !prompt.is_static() {
+        write_progress(&args.results_file, "💭 Prompt template: rendered per chunk")?;
+    }
+    if !tasks.is_empty() {
+        let names: Vec<&str> = tasks.iter().map(|task| task.name.as_str()).collect();
+        write_progress(&args.results_file, &format!("🗂️  Tasks: {}", names.join(", ")))?;
+    }
+
+    info!("🚀 Starting 20-Agent Parallel Code Summarizer");
+    info!("File: {}", args.input.file);
+    info!("Output: {}", args.output_file);
+    info!("Results: {}", args.results_file);
+    info!("Lines per chunk: {}", chunking.loc);
+    info!("Agents: {}", run.agent_count);
+    info!("Model: {}", model_config.name);
+    info!("Strategy: {:?}", generation_config.strategy);
+    info!("Temperature: {:.2}", generation_config.temperature);
+
+    // Phase 1: Read the input: one file (possibly a repository dump), or every file under a directory or glob.
+    // Standard input and files over the size contract are chunked while they are read, when lines allow it.
+    let streamed = args.input.is_large() && chunking.mode == ChunkingMode::Lines && chunking.overlap == 0;
+    if args.input.is_large() && !streamed {
+        warn!("⚠️  {} is read whole: only --chunking lines without --overlap streams it", args.input.file);
+    }
+    let (sources, multi_file) = if streamed {
+        (Vec::new(), false)
+    } else {
+        args.input.read_sources(run.max_concurrent.unwrap_or(0), |message| {
+            info!("{}", message);
+            write_progress(&args.results_file, message)
+        })?
+    };
+
+    // Phase 2: Create chunks for parallel processing, each file on its own
+
+    // The tokenizer measures token-mode chunks, refine prompts, hierarchical reduce inputs and dry-run costs;
+    // refine alone can fall back to a character estimate
+    let tokenizer = if chunking.mode == ChunkingMode::Tokens || args.hierarchical || args.dry_run {
+        Some(load_tokenizer(model_config)?)
+    } else if args.strategy == SummaryStrategy::Refine {
+        load_tokenizer(model_config)
+            .map_err(|e| warn!("⚠️  {} - refine budgets fall back to a character estimate", e))
+            .ok()
+    } else {
+        None
+    };
+
+    // Every task's prompt and output must fit next to the chunk
+    let prompts: Vec<(&PromptTemplate, usize)> = if tasks.is_empty() {
+        vec![(prompt, generation_config.max_new_tokens)]
+    } else {
+        tasks.iter().map(|task| (&task.template, task.generation.max_new_tokens)).collect()
+    };
+    let chunker = chunking.chunker(tokenizer.as_ref(), model_config, &prompts)?;
+
+    let mut chunks: Vec<Chunk> = if streamed {
+        args.input.stream_chunks(chunking.loc, |message| {
+            info!("{}", message);
+            write_progress(&args.results_file, message)
+        }).await?
+    } else {
+        Vec::new()
+    };
+    let mut skipped: Vec<(String, String)> = Vec::new();
+    for file in &sources {
+        let (path, source) = (&file.path, &file.content);
+        // Binary, generated and minified sources are not worth a model call
+        if let Some(reason) = classify_text(Some(path), source) {
+            info!("⏭️ Skipped {}: {}", path, reason);
+            write_progress(&args.results_file, &format!("⏭️ Skipped {}: {}", path, reason))?;
+            skipped.push((path.clone(), reason.to_string()));
+            continue;
+        }
+
+        // Extension, shebang, modeline, then content: repository dumps hold extensionless scripts too
+        let detection = detect_language(Some(path), source);
+        debug!("🧩 Chunking {} as {} ({} chunker)", path, detection, chunker.name());
+        if !multi_file {
+            write_progress(&args.results_file, &format!("🔤 Language: {}", detection))?;
+        }
+        chunks.extend(chunker.chunk_detected(source, &detection, Some(path))?);
+    }
+
+    // Overlapped lines are counted once so coverage matches the source file
+    let covered_lines = overlap::owned_line_count(&chunks);
+    info!("✅ Created {} chunks for parallel processing ({} lines each, {} lines covered)",
+          chunks.len(), chunking.loc, covered_lines);
+    write_progress(&args.results_file, &format!("✅ Created {} chunks for parallel processing ({} lines each, {} lines covered, overlap {})",
+                                                chunks.len(), chunking.loc, covered_lines, chunking.overlap))?;
+
+    // Dry run: report what the run would cost and stop before the full model pass
+    if let (true, Some(tokenizer)) = (args.dry_run, &tokenizer) {
+        return crate::plan::run(args, &resolved, &chunks, skipped, tokenizer).await;
+    }
+
+    // Every input was skipped: report that and stop before loading the model
+    if chunks.is_empty() {
+        let report = SummaryReport::new(&model_config.name, Duration::ZERO, Vec::new()).with_skipped(skipped);
+        fs::write(&args.output_file, report.render(*format))?;
+        info!("📭 Nothing to summarize: {} file(s) skipped", report.skipped.len());
+        write_progress(&args.results_file, &format!("📭 Nothing to summarize: {} file(s) skipped", report.skipped.len()))?;
+        write_progress(&args.results_file, &format!("💾 Final summary saved to: {}", args.output_file))?;
+
+        println!("\n📭 NOTHING TO SUMMARIZE: {} file(s) skipped", report.skipped.len());
+        for (path, reason) in &report.skipped {
+            println!("  {}: {}", path, reason);
+        }
+        println!("📄 Summary saved to: {}", args.output_file);
+        return Ok(());
+    }
+
+    // Phase 3: Configure parallel system with user-specified parameters
+    let config = run.parallel_config(model_config, generation_config, output_schema.clone());
+
+    // Phase 4: Initialize parallel system
+    info!("🔧 Initializing {}-agent parallel system...", run.agent_count);
+    write_progress(&args.results_file, "🔧 Initializing parallel system...")?;
+    let system = ParallelAgentSystem::new(config)?;
+
+    let metrics = system.get_metrics();
+    info!("✅ Parallel system ready: {}", metrics);
+    write_progress(&args.results_file, &format!("✅ Parallel system ready: {}", metrics))?;
+
+    cancel_on_interrupt(&system);
+
+    // Phase 5: Process chunks in parallel using custom prompts
+    info!("🔄 Starting parallel processing with {} agents...", run.agent_count);
+    write_progress(&args.results_file, "🔄 Starting parallel processing...")?;
+    let start_time = std::time::Instant::now();
+
+    // Per-chunk generation times and task outputs line up with `results`, when known
+    let (results, timings, task_outputs) = match (args.strategy, &tokenizer) {
+        (SummaryStrategy::Refine, tokenizer) => {
+            let budget = LevelBudgets::for_context(model_config.context_window(), generation_config.max_new_tokens).file;
+            let mut refine = RefineSummarizer::new(prompt.clone(), budget);
+            if let Some(tokenizer) = tokenizer {
+                let tokenizer = std::sync::Arc::clone(tokenizer);
+                refine = refine.with_measure(move |text: &str| tokenizer.encode(text, false).map(|e| e.len()).unwrap_or(text.len()));
+            }
+            let steps = refine.summarize(&chunks, &system).await?;
+            (chunks.iter().cloned().zip(steps).collect::<Vec<_>>(), Vec::new(), Vec::new())
+        }
+        _ => {
+            // Finished chunks are journaled as they complete; a resumed journal's chunks are not run again
+            let mut journal = RunJournal::open(journal_path(args), run_config_hash(&resolved), args.resume)?;
+            let ids: Vec<ChunkId> = chunks.iter().map(Chunk::id).collect();
+            let mut finished: Vec<Option<JournalEntry>> = ids.iter().map(|id| journal.get(id).cloned()).collect();
+            let pending: Vec<usize> = (0..chunks.len()).filter(|&index| finished[index].is_none()).collect();
+            write_progress(&args.results_file, &format!("📓 Journal: {}", journal.path().display()))?;
+            if pending.len() < chunks.len() {
+                info!("⏩ Resuming: {} of {} chunks already finished", chunks.len() - pending.len(), chunks.len());
+                write_progress(&args.results_file, &format!("⏩ Resuming: {} of {} chunks already finished",
+                                                            chunks.len() - pending.len(), chunks.len()))?;
+            }
+
+            // Truncated, cancelled and failed chunks still reach this run's output, but not the journal
+            let mut finish = |index: usize, entry: JournalEntry| -> Result<()> {
+                let chunk_index = pending[index];
+                journal.record(&ids[chunk_index], entry.clone())?;
+                finished[chunk_index] = Some(entry);
+                Ok(())
+            };
+            let died: Vec<(usize, anyhow::Error)> = if tasks.is_empty() {
+                let prompted_chunks: Vec<(String, String)> = pending
+                    .iter()
+                    .map(|&index| (chunks[index].content.clone(), prompt.render(&chunks[index])))
+                    .collect();
+                system
+                    .process_prompted_chunks_with(prompted_chunks, |index, timed| {
+                        finish(index, JournalEntry {
+                            summary: timed.summary.clone(),
+                            tasks: Vec::new(),
+                            elapsed: Some(timed.elapsed),
+                            failure: timed.failure.clone(),
+                        })
+                    })
+                    .await?
+                    .into_iter()
+                    .filter_map(|(index, result)| result.err().map(|e| (index, e)))
+                    .collect()
+            } else {
+                let tasked_chunks: Vec<(String, Vec<(String, GenerationConfig)>)> = pending
+                    .iter()
+                    .map(|&index| {
+                        let chunk = &chunks[index];
+                        let prompts = tasks.iter().map(|task| (task.template.render(chunk), task.generation.clone())).collect();
+                        (chunk.content.clone(), prompts)
+                    })
+                    .collect();
+                // Outputs stay keyed by task name: `[name]` sections of the summary, and named for the report
+                system
+                    .process_chunk_tasks_with(tasked_chunks, |index, tasked| {
+                        let named = tasks.iter().map(|task| task.name.clone()).zip(tasked.outputs.iter().cloned()).collect();
+                        finish(index, JournalEntry {
+                            summary: render_task_outputs(tasks, &tasked.outputs),
+                            tasks: named,
+                            elapsed: None,
+                            failure: tasked.failure.clone(),
+                        })
+                    })
+                    .await?
+                    .into_iter()
+                    .filter_map(|(index, result)| result.err().map(|e| (index, e)))
+                    .collect()
+            };
+
+            // A chunk whose task died keeps its place in the output with the error, but stays out of the journal
+            for (index, e) in died {
+                let chunk_index = pending[index];
+                let chunk = &chunks[chunk_index];
+                write_progress(&args.results_file, &format!("❌ Chunk {} ({} lines {}-{}) failed: {}", chunk_index,
+                    chunk.file_path.as_deref().unwrap_or("input"), chunk.line_start + 1, chunk.line_end, e))?;
+                finished[chunk_index] = Some(JournalEntry {
+                    summary: format!("ERROR: {}", e),
+                    tasks: Vec::new(),
+                    elapsed: None,
+                    failure: Some(e.to_string()),
+                });
+            }
+
+            let (mut results, mut timings, mut task_outputs) = (Vec::new(), Vec::new(), Vec::new());
+            for (chunk, entry) in chunks.iter().zip(finished) {
+                let entry = entry.expect("every chunk is journaled, finished or failed");
+                results.push((chunk.clone(), entry.summary));
+                timings.push(entry.elapsed);
+                task_outputs.push(entry.tasks);
+            }
+            (results, timings, task_outputs)
+        }
+    };
+
+    let processing_time = start_time.elapsed();
+    info!("✅ Parallel processing completed in {:?}", processing_time);
+    write_progress(&args.results_file, &format!("✅ Parallel processing completed in {:?}", processing_time))?;
+
+    // Phase 5b: Flag, and optionally regenerate, summaries naming identifiers their chunk lacks
+    let (results, grounding) = match args.grounding_threshold {
+        Some(threshold) => {
+            let items = results
+                .iter()
+                .map(|(chunk, summary)| (chunk.clone(), prompt.render(chunk), summary.clone()))
+                .collect();
+            let checked = GroundingCheck::new(threshold)
+                .with_max_attempts(args.grounding_retries)
+                .enforce(items, &system)
+                .await?;
+
+            for (index, (_, report)) in checked.iter().enumerate() {
+                let flag = if report.score < threshold { "⚠️" } else { "🔎" };
+                write_progress(&args.results_file, &format!("{} Chunk {} grounding {:.2}{}", flag, index, report.score,
+                    if report.ungrounded.is_empty() { String::new() } else { format!(" (ungrounded: {})", report.ungrounded.join(", ")) }))?;
+            }
+            let results: Vec<(Chunk, String)> = results
+                .into_iter()
+                .zip(&checked)
+                .map(|((chunk, _), (summary, _))| (chunk, summary.clone()))
+                .collect();
+            (results, checked.into_iter().map(|(_, report)| report).collect())
+        }
+        None => (results, Vec::new()),
+    };
+
+    // Phase 6: Display summary to console
+    println!("\n🎯 PROCESSING SUMMARY:");
+    println!("=====================");
+    println!("Total chunks processed: {}", results.len());
+    println!("Total processing time: {:?}", processing_time);
+    println!("Average time per chunk: {}ms", processing_time.as_millis() / results.len().max(1) as u128);
+    let seconds = processing_time.as_secs_f64();
+    let throughput = if seconds > 0.0 { results.len() as f64 / seconds } else { 0.0 };
+    println!("Throughput: {:.2} chunks/second", throughput);
+    println!("Generation strategy: {:?}", generation_config.strategy);
+    println!("Temperature: {:.2}", generation_config.temperature);
+
+    // Refine: the last running summary of each file stands for the whole file
+    let results = match args.strategy {
+        SummaryStrategy::Refine => {
+            let steps: Vec<String> = results.into_iter().map(|(_, summary)| summary).collect();
+            file_summaries(&chunks, &steps)
+        }
+        SummaryStrategy::Parallel => results,
+    };
+
+    // Phase 6b: Roll chunk summaries up to files, modules and the crate
+    let hierarchy = match &tokenizer {
+        Some(tokenizer) if args.hierarchical => {
+            info!("🧮 Building hierarchical summary...");
+            write_progress(&args.results_file, "🧮 Building hierarchical summary...")?;
+            let tokenizer = std::sync::Arc::clone(tokenizer);
+            let summarizer = HierarchicalSummarizer::new(LevelBudgets::for_context(
+                model_config.context_window(),
+                generation_config.max_new_tokens,
+            ))
+            .with_measure(move |text: &str| tokenizer.encode(text, false).map(|e| e.len()).unwrap_or(text.len()));
+            Some(summarizer.summarize(&results, &system).await?)
+        }
+        _ => None,
+    };
+
+    // Phase 7: Save final summary to specified output file
+    let reports: Vec<ChunkReport> = results
+        .iter()
+        .cloned()
+        .enumerate()
+        .map(|(index, (chunk, summary))| {
+            let mut report = ChunkReport::new(chunk, summary.clone());
+            if let Some(schema) = &output_schema {
+                report = report.with_structured(serde_json::from_str(&summary).unwrap_or_else(|_| schema.constrain_text(&summary)));
+            }
+            if let Some(outputs) = task_outputs.get(index) {
+                report = report.with_tasks(outputs.clone());
+            }
+            if let Some(grounding) = grounding.get(index) {
+                report = report.with_grounding(grounding.clone());
+            }
+            if let Some(elapsed) = timings.get(index).copied().flatten() {
+                report = report.with_elapsed(elapsed);
+            }
+            report
+        })
+        .collect();
+    let skipped_count = skipped.len();
+    let full_summary = SummaryReport::new(&model_config.name, processing_time, reports)
+        .with_rollup(hierarchy.as_ref().map(|hierarchy| hierarchy.render()))
+        .with_skipped(skipped)
+        .render(*format);
+
+    fs::write(&args.output_file, full_summary)?;
+    info!("💾 Final summary saved to: {}", args.output_file);
+    write_progress(&args.results_file, &format!("💾 Final summary saved to: {}", args.output_file))?;
+
+    // Phase 8: Final progress update
+    write_progress(&args.results_file, "🎉 PARALLEL PROCESSING COMPLETE!")?;
+    write_progress(&args.results_file, &format!("📊 Final metrics: {} chunks, {} skipped files, {:?} total time",
+                                                results.len(), skipped_count, processing_time))?;
+
+    println!("\n🎉 PARALLEL PROCESSING COMPLETE!");
+    println!("📄 Summary saved to: {}", args.output_file);
+    println!("📊 Progress logged to: {}", args.results_file);
+    Ok(())
+}
+
+/// `--journal`, or the output file with a `.journal` suffix
+pub fn journal_path(args: &SummarizeArgs) -> String {
+    args.journal.clone().unwrap_or_else(|| format!("{}.journal", args.output_file))
+}
+
+/// Hash of everything that shapes a chunk's summary, so a resumed journal
+/// only reuses summaries this exact configuration would produce
+pub fn run_config_hash(resolved: &Resolved) -> String {
+    let Resolved { prompt, tasks, model_config, generation_config, chunking, run, output_schema, .. } = resolved;
+    let mut parts = vec![
+        model_config.name.clone(),
+        model_config.model_path.display().to_string(),
+        format!("{:?}", generation_config),
+        format!("{:?} {} {}", chunking.mode, chunking.loc, chunking.overlap),
+        prompt.source().to_string(),
+        // JSON and prose summaries must not mix in one output
+        output_schema.as_ref().map(|schema| schema.as_json().to_string()).unwrap_or_default(),
+        // The deadline decides where generation stops
+        run.max_chunk_time_s.to_string(),
+    ];
+    for task in tasks {
+        parts.extend([task.name.clone(), task.template.source().to_string(), format!("{:?}", task.generation)]);
+    }
+    config_hash(&parts)
+}
@@ -0,0 +1,269 @@
+//! The `Chunker` trait: one chunking interface for the CLI, library and orchestrator
+//!
+//! Contracts:
+//! - `split` returns chunks in source order with 0-based, end-exclusive line ranges
+//! - `chunk` fills in the remaining metadata (file path, language, defined
+//!   symbols), so every chunk's `Chunk::id` is path-aware whichever
+//!   implementation produced it
+//! - `chunk_detected` also records the detection confidence on every chunk
+//! - Implementations are `Send + Sync` and can be shared as `Arc<dyn Chunker>`
+
+use super::detect::{detect_language, LanguageDetection};
+use super::language::SourceLanguage;
+use super::overlap::apply_line_overlap;
+use super::symbols::{attach_symbols, extract_symbols};
+use super::Chunk;
+use crate::errors::Result;
+
+/// Pluggable chunking strategy
+pub trait Chunker: Send + Sync {
+    /// Short strategy name for logs
+    fn name(&self) -> &str;
+
+    /// Split `source` (written in `language`) into chunks with line ranges
+    fn split(&self, source: &str, language: SourceLanguage) -> Result<Vec<Chunk>>;
+
+    /// Chunks of one source with full metadata
+    fn chunk(&self, source: &str, language: SourceLanguage, file_path: Option<&str>) -> Result<Vec<Chunk>> {
+        Ok(with_metadata(self.split(source, language)?, source, language, file_path))
+    }
+
+    /// Chunks of one source in a detected language, carrying its confidence
+    fn chunk_detected(&self, source: &str, detection: &LanguageDetection, file_path: Option<&str>) -> Result<Vec<Chunk>> {
+        Ok(self
+            .chunk(source, detection.language, file_path)?
+            .into_iter()
+            .map(|chunk| chunk.with_detection(detection))
+            .collect())
+    }
+
+    /// Chunks of a file's contents, with the language detected from its path and content
+    fn chunk_file(&self, file_path: &str, source: &str) -> Result<Vec<Chunk>> {
+        self.chunk_detected(source, &detect_language(Some(file_path), source), Some(file_path))
+    }
+}
+
+/// Fill in language, file path and defined symbols on chunks split from `source`
+pub(super) fn with_metadata(chunks: Vec<Chunk>, source: &str, language: SourceLanguage, file_path: Option<&str>) -> Vec<Chunk> {
+    let mut chunks: Vec<Chunk> = chunks
+        .into_iter()
+        .map(|chunk| {
+            let chunk = chunk.with_language(language);
+            match file_path {
+                Some(path) => chunk.with_file_path(path),
+                None => chunk,
+            }
+        })
+        .collect();
+    attach_symbols(&mut chunks, &extract_symbols(source, language));
+    chunks
+}
+
+/// Fixed windows of `max_lines` lines, ignoring language structure
+pub struct LineChunker {
+    max_lines: usize,
+    overlap_lines: usize,
+    merge_below_lines: usize,
+}
+
+impl LineChunker {
+    pub fn new(max_lines: usize) -> Self {
+        Self {
+            max_lines: max_lines.max(1),
+            overlap_lines: 0,
+            merge_below_lines: 0,
+        }
+    }
+
+    /// Repeat up to `overlap_lines` preceding lines at the start of each chunk
+    pub fn with_overlap(mut self, overlap_lines: usize) -> Self {
+        self.overlap_lines = overlap_lines;
+        self
+    }
+
+    /// Merge neighbouring chunks that are both shorter than `lines` (0 = never)
+    pub fn with_small_chunk_merging(mut self, lines: usize) -> Self {
+        self.merge_below_lines = lines;
+        self
+    }
+}
+
+impl Chunker for LineChunker {
+    fn name(&self) -> &str {
+        "lines"
+    }
+
+    fn split(&self, source: &str, _language: SourceLanguage) -> Result<Vec<Chunk>> {
+        let lines: Vec<&str> = source.lines().collect();
+        let mut chunks: Vec<Chunk> = (0..lines.len())
+            .step_by(self.max_lines)
+            .map(|start| {
+                let end = (start + self.max_lines).min(lines.len());
+                Chunk::new(start, end, lines[start..end].join("\n"))
+            })
+            .filter(|chunk| !chunk.content.trim().is_empty())
+            .collect();
+
+        if self.merge_below_lines > 0 {
+            chunks = merge_small_chunks(chunks, self.merge_below_lines);
+        }
+        Ok(apply_line_overlap(&lines, chunks, self.overlap_lines))
+    }
+}
+
+/// Pieces of at most `max_chars` bytes, cut on character boundaries
+///
+/// A piece owns the lines that start inside it, so a line cut mid-way belongs
+/// to its first piece, later pieces of it own no lines, and the ranges tile
+/// the input.
+pub struct CharacterChunker {
+    max_chars: usize,
+}
+
+impl CharacterChunker {
+    pub fn new(max_chars: usize) -> Self {
+        // At least one character of any width
+        Self { max_chars: max_chars.max(4) }
+    }
+}
+
+impl Chunker for CharacterChunker {
+    fn name(&self) -> &str {
+        "characters"
+    }
+
+    fn split(&self, source: &str, _language: SourceLanguage) -> Result<Vec<Chunk>> {
+        let mut chunks = Vec::new();
+        let mut rest = source;
+        let mut line_start = 0;
+        let mut at_line_start = true;
+
+        while !rest.is_empty() {
+            let mut end = rest.len().min(self.max_chars);
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            let (piece, tail) = rest.split_at(end);
+            // A trailing newline opens a line that starts in the next piece
+            let ends_line = piece.ends_with('\n');
+            let line_end = line_start + usize::from(at_line_start) + piece.matches('\n').count() - usize::from(ends_line);
+            chunks.push(Chunk::new(line_start, line_end, piece.to_string()));
+            line_start = line_end;
+            at_line_start = ends_line;
+            rest = tail;
+        }
+
+        Ok(chunks)
+    }
+}
+
+/// Merge each run of neighbouring chunks shorter than `min_lines`
+pub fn merge_small_chunks(mut chunks: Vec<Chunk>, min_lines: usize) -> Vec<Chunk> {
+    let mut i = 0;
+    while i + 1 < chunks.len() {
+        if chunks[i].line_count < min_lines && chunks[i + 1].line_count < min_lines {
+            chunks[i] = chunks[i].merge(&chunks[i + 1]);
+            chunks.remove(i + 1);
+            continue;
+        }
+        i += 1;
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// A custom strategy: one chunk per blank-line separated paragraph
+    struct ParagraphChunker;
+
+    impl Chunker for ParagraphChunker {
+        fn name(&self) -> &str {
+            "paragraphs"
+        }
+
+        fn split(&self, source: &str, _language: SourceLanguage) -> Result<Vec<Chunk>> {
+            let lines: Vec<&str> = source.lines().collect();
+            let mut chunks = Vec::new();
+            let mut start = 0;
+            for end in 0..=lines.len() {
+                if end == lines.len() || lines[end].trim().is_empty() {
+                    if end > start {
+                        chunks.push(Chunk::new(start, end, lines[start..end].join("\n")));
+                    }
+                    start = end + 1;
+                }
+            }
+            Ok(chunks)
+        }
+    }
+
+    #[test]
+    fn test_every_chunker_fills_in_metadata() {
+        let source = "fn a() {}\n\nfn b() {}\n";
+        let chunkers: Vec<Arc<dyn Chunker>> = vec![
+            Arc::new(LineChunker::new(2)),
+            Arc::new(CharacterChunker::new(8)),
+            Arc::new(ParagraphChunker),
+        ];
+
+        for chunker in chunkers {
+            let chunks = chunker.chunk_file("src/lib.rs", source).unwrap();
+            assert!(!chunks.is_empty(), "{} produced no chunks", chunker.name());
+            for chunk in &chunks {
+                assert_eq!(chunk.file_path.as_deref(), Some("src/lib.rs"));
+                assert_eq!(chunk.language, SourceLanguage::Rust);
+                assert!(chunk.line_start <= chunk.line_end);
+                assert_ne!(chunk.id(), Chunk::new(0, 1, chunk.content.clone()).id());
+            }
+        }
+    }
+
+    #[test]
+    fn test_line_chunker_merging_and_overlap() {
+        let source = (0..12).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n");
+
+        let plain = LineChunker::new(3).split(&source, SourceLanguage::Unknown).unwrap();
+        assert_eq!(plain.len(), 4);
+
+        let merged = LineChunker::new(3).with_small_chunk_merging(5).split(&source, SourceLanguage::Unknown).unwrap();
+        let ranges: Vec<(usize, usize)> = merged.iter().map(|chunk| (chunk.line_start, chunk.line_end)).collect();
+        assert_eq!(ranges, vec![(0, 6), (6, 12)]);
+
+        let overlapped = LineChunker::new(3).with_overlap(1).split(&source, SourceLanguage::Unknown).unwrap();
+        assert_eq!(overlapped[1].overlap_lines, 1);
+        assert!(overlapped[1].content.starts_with("line 2\nline 3"));
+    }
+
+    #[test]
+    fn test_character_chunker_respects_char_boundaries() {
+        let source = "é".repeat(10);
+        let chunks = CharacterChunker::new(5).split(&source, SourceLanguage::Unknown).unwrap();
+        assert!(chunks.iter().all(|chunk| chunk.content.len() <= 5));
+        assert_eq!(chunks.iter().map(|chunk| chunk.content.as_str()).collect::<String>(), source);
+    }
+
+    #[test]
+    fn test_character_chunker_ranges_tile_the_input() {
+        let source = "fn a() {}\nlet long_line = 1234567890;\n\nfn b() {}\ntail";
+        for max_chars in [4, 7, 10, 16, 64] {
+            let chunks = CharacterChunker::new(max_chars).split(source, SourceLanguage::Unknown).unwrap();
+            assert_eq!(chunks[0].line_start, 0);
+            assert!(chunks.windows(2).all(|pair| pair[0].line_end == pair[1].line_start));
+            assert_eq!(chunks.last().unwrap().line_end, source.lines().count());
+            assert_eq!(crate::chunking::overlap::owned_line_count(&chunks), source.lines().count());
+        }
+
+        let chunks = CharacterChunker::new(8).split("abcdefg\nhijklmn\n", SourceLanguage::Unknown).unwrap();
+        let ranges: Vec<(usize, usize)> = chunks.iter().map(|chunk| (chunk.line_start, chunk.line_end)).collect();
+        assert_eq!(ranges, vec![(0, 1), (1, 2)]);
+
+        // Only the first piece of an over-long line owns it
+        let chunks = CharacterChunker::new(4).split("abcdefghij\nk", SourceLanguage::Unknown).unwrap();
+        let ranges: Vec<(usize, usize)> = chunks.iter().map(|chunk| (chunk.line_start, chunk.line_end)).collect();
+        assert_eq!(ranges, vec![(0, 1), (1, 1), (1, 2)]);
+    }
+}
@@ -0,0 +1,289 @@
+//! Language detection for sources that do not declare their language
+//!
+//! Contracts:
+//! - Signals are tried in order: file extension, shebang, modeline, then a
+//!   keyword classifier over the content; the first that names a language wins
+//! - Confidence is in `[0, 1]`: 1.0 for an explicit name, 0.95 for an
+//!   extension, 0.9 for a shebang or modeline, at most 0.8 for the classifier
+//! - Nothing recognised is `Unknown` with confidence 0
+
+use super::language::SourceLanguage;
+
+/// Which signal decided the language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectionMethod {
+    /// Named by the caller, e.g. `CodeRecord::language`
+    Explicit,
+    Extension,
+    /// `#!/usr/bin/env python3`
+    Shebang,
+    /// Emacs `-*- mode: python -*-` or Vim `vim: set ft=python:`
+    Modeline,
+    /// Keyword classifier over the content
+    Content,
+    /// No signal matched
+    Undetected,
+}
+
+impl DetectionMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DetectionMethod::Explicit => "explicit",
+            DetectionMethod::Extension => "extension",
+            DetectionMethod::Shebang => "shebang",
+            DetectionMethod::Modeline => "modeline",
+            DetectionMethod::Content => "content",
+            DetectionMethod::Undetected => "undetected",
+        }
+    }
+}
+
+/// A detected language and how sure the detector is about it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LanguageDetection {
+    pub language: SourceLanguage,
+    /// In `[0, 1]`; 0 for `Unknown`
+    pub confidence: f32,
+    pub method: DetectionMethod,
+}
+
+impl LanguageDetection {
+    pub fn new(language: SourceLanguage, confidence: f32, method: DetectionMethod) -> Self {
+        Self {
+            language,
+            confidence,
+            method,
+        }
+    }
+
+    /// A language the caller named; `Unknown` names stay undetected
+    pub fn explicit(language: SourceLanguage) -> Self {
+        match language {
+            SourceLanguage::Unknown => Self::undetected(),
+            language => Self::new(language, 1.0, DetectionMethod::Explicit),
+        }
+    }
+
+    pub fn undetected() -> Self {
+        Self::new(SourceLanguage::Unknown, 0.0, DetectionMethod::Undetected)
+    }
+
+    pub fn is_known(&self) -> bool {
+        self.language != SourceLanguage::Unknown
+    }
+}
+
+impl std::fmt::Display for LanguageDetection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (by {}, confidence {:.2})", self.language, self.method.as_str(), self.confidence)
+    }
+}
+
+/// Detect the language of `content`, optionally stored at `path`
+pub fn detect_language(path: Option<&str>, content: &str) -> LanguageDetection {
+    if let Some(language) = path.map(SourceLanguage::from_path).filter(|l| *l != SourceLanguage::Unknown) {
+        return LanguageDetection::new(language, 0.95, DetectionMethod::Extension);
+    }
+    if let Some(language) = shebang_language(content) {
+        return LanguageDetection::new(language, 0.9, DetectionMethod::Shebang);
+    }
+    if let Some(language) = modeline_language(content) {
+        return LanguageDetection::new(language, 0.9, DetectionMethod::Modeline);
+    }
+    match classify_content(content) {
+        Some((language, confidence)) => LanguageDetection::new(language, confidence, DetectionMethod::Content),
+        None => LanguageDetection::undetected(),
+    }
+}
+
+/// Interpreter named by a `#!` first line; `env` and its flags are skipped
+fn shebang_language(content: &str) -> Option<SourceLanguage> {
+    let line = content.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let mut interpreter = words.next()?.rsplit('/').next()?;
+    if interpreter == "env" {
+        interpreter = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+    // `python3.11` -> `python`
+    let name = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    Some(SourceLanguage::from_name(name)).filter(|language| *language != SourceLanguage::Unknown)
+}
+
+/// Emacs or Vim modeline in the first or last five lines
+fn modeline_language(content: &str) -> Option<SourceLanguage> {
+    let lines: Vec<&str> = content.lines().collect();
+    let edges = lines.iter().take(5).chain(lines.iter().skip(lines.len().saturating_sub(5).max(5)));
+    edges
+        .filter_map(|line| emacs_mode(line).or_else(|| vim_filetype(line)))
+        .map(SourceLanguage::from_name)
+        .find(|language| *language != SourceLanguage::Unknown)
+}
+
+/// `-*- mode: rust -*-` or the short form `-*- rust -*-`
+fn emacs_mode(line: &str) -> Option<&str> {
+    let start = line.find("-*-")? + 3;
+    let inner = &line[start..start + line[start..].find("-*-")?];
+    if !inner.contains(':') {
+        return Some(inner.trim());
+    }
+    inner
+        .split(';')
+        .filter_map(|setting| setting.split_once(':'))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("mode"))
+        .map(|(_, value)| value.trim())
+}
+
+/// `vim: set ft=python:` / `vi: filetype=go`
+fn vim_filetype(line: &str) -> Option<&str> {
+    let start = ["vim:", "vi:", "ex:"]
+        .iter()
+        .filter_map(|marker| {
+            let index = line.find(marker)?;
+            // The marker must start a word: `// vim:` but not `envi:`
+            let starts_word = line[..index].chars().last().is_none_or(char::is_whitespace);
+            starts_word.then_some(index + marker.len())
+        })
+        .min()?;
+    line[start..]
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .filter_map(|setting| setting.split_once('='))
+        .find(|(key, _)| matches!(*key, "ft" | "filetype" | "syntax"))
+        .map(|(_, value)| value)
+}
+
+/// Substrings typical of each language, with their weight
+const SIGNALS: &[(SourceLanguage, &str, f32)] = &[
+    (SourceLanguage::Rust, "fn ", 1.0),
+    (SourceLanguage::Rust, "let mut ", 2.0),
+    (SourceLanguage::Rust, "impl ", 2.0),
+    (SourceLanguage::Rust, "pub fn ", 2.0),
+    (SourceLanguage::Rust, "use std::", 3.0),
+    (SourceLanguage::Rust, "&self", 2.0),
+    (SourceLanguage::Rust, "#[derive(", 3.0),
+    (SourceLanguage::Rust, "Some(", 1.0),
+    (SourceLanguage::Rust, "Ok(", 1.0),
+    (SourceLanguage::Rust, "match ", 1.0),
+    (SourceLanguage::Python, "def ", 2.0),
+    (SourceLanguage::Python, "import ", 1.0),
+    (SourceLanguage::Python, "self.", 1.0),
+    (SourceLanguage::Python, "elif ", 3.0),
+    (SourceLanguage::Python, "None", 1.0),
+    (SourceLanguage::Python, "__init__", 3.0),
+    (SourceLanguage::Python, "__name__", 3.0),
+    (SourceLanguage::Python, "):\n", 1.0),
+    (SourceLanguage::TypeScript, "interface ", 2.0),
+    (SourceLanguage::TypeScript, ": string", 3.0),
+    (SourceLanguage::TypeScript, ": number", 3.0),
+    (SourceLanguage::TypeScript, ": boolean", 3.0),
+    (SourceLanguage::TypeScript, "readonly ", 2.0),
+    (SourceLanguage::TypeScript, "implements ", 2.0),
+    (SourceLanguage::JavaScript, "function ", 1.0),
+    (SourceLanguage::JavaScript, "const ", 1.0),
+    (SourceLanguage::JavaScript, "=> ", 1.0),
+    (SourceLanguage::JavaScript, "require(", 3.0),
+    (SourceLanguage::JavaScript, "module.exports", 3.0),
+    (SourceLanguage::JavaScript, "console.log", 2.0),
+    (SourceLanguage::JavaScript, "===", 2.0),
+    (SourceLanguage::Go, "package ", 2.0),
+    (SourceLanguage::Go, "func ", 2.0),
+    (SourceLanguage::Go, ":= ", 2.0),
+    (SourceLanguage::Go, "fmt.", 2.0),
+    (SourceLanguage::Go, "err != nil", 3.0),
+    (SourceLanguage::Go, "import (", 3.0),
+    (SourceLanguage::C, "#include", 3.0),
+    (SourceLanguage::C, "#define ", 2.0),
+    (SourceLanguage::C, "printf(", 2.0),
+    (SourceLanguage::C, "malloc(", 3.0),
+    (SourceLanguage::C, "NULL", 2.0),
+    (SourceLanguage::C, "typedef ", 2.0),
+    (SourceLanguage::C, "sizeof(", 2.0),
+];
+
+/// Scores below this are too little evidence to name a language
+const MIN_CONTENT_SCORE: f32 = 4.0;
+
+/// Highest confidence the classifier reports
+const MAX_CONTENT_CONFIDENCE: f32 = 0.8;
+
+/// Keyword classifier: the best-scoring language, with confidence from its lead
+///
+/// TypeScript also scores JavaScript's signals, since it is a superset.
+fn classify_content(content: &str) -> Option<(SourceLanguage, f32)> {
+    let mut scores: Vec<(SourceLanguage, f32)> = Vec::new();
+    for (language, pattern, weight) in SIGNALS {
+        // Capped so one repeated keyword cannot outvote everything else
+        let hits = content.matches(pattern).count().min(3) as f32;
+        if hits == 0.0 {
+            continue;
+        }
+        let targets: &[SourceLanguage] = match language {
+            SourceLanguage::JavaScript => &[SourceLanguage::JavaScript, SourceLanguage::TypeScript],
+            _ => std::slice::from_ref(language),
+        };
+        for target in targets {
+            match scores.iter_mut().find(|(scored, _)| scored == target) {
+                Some((_, score)) => *score += hits * weight,
+                None => scores.push((*target, hits * weight)),
+            }
+        }
+    }
+
+    // TypeScript without any of its own signals is plain JavaScript
+    let score_of = |language: SourceLanguage| scores.iter().find(|(scored, _)| *scored == language).map(|(_, score)| *score);
+    if score_of(SourceLanguage::TypeScript) == score_of(SourceLanguage::JavaScript) {
+        scores.retain(|(language, _)| *language != SourceLanguage::TypeScript);
+    }
+
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let (language, best) = *scores.first()?;
+    if best < MIN_CONTENT_SCORE {
+        return None;
+    }
+    let second = scores.get(1).map_or(0.0, |(_, score)| *score);
+    Some((language, MAX_CONTENT_CONFIDENCE * (best / (best + second))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_structural_signals_in_order() {
+        let python_script = "#!/usr/bin/env -S python3.11 -u\nprint('hi')\n";
+        assert_eq!(detect_language(Some("tool.py"), "").method, DetectionMethod::Extension);
+        assert_eq!(
+            detect_language(Some("bin/tool"), python_script),
+            LanguageDetection::new(SourceLanguage::Python, 0.9, DetectionMethod::Shebang)
+        );
+        // The extension wins over a contradicting shebang
+        assert_eq!(detect_language(Some("tool.rs"), python_script).language, SourceLanguage::Rust);
+        assert_eq!(detect_language(None, "#!/usr/bin/node\n").language, SourceLanguage::JavaScript);
+
+        let emacs = detect_language(None, "# -*- mode: python; coding: utf-8 -*-\nx = 1\n");
+        assert_eq!((emacs.language, emacs.method), (SourceLanguage::Python, DetectionMethod::Modeline));
+        let vim = format!("{}// vim: set ts=4 ft=go:\n", "x\n".repeat(20));
+        assert_eq!(detect_language(None, &vim).language, SourceLanguage::Go);
+        assert_eq!(detect_language(None, "/* -*- c -*- */\n").language, SourceLanguage::C);
+
+        assert_eq!(detect_language(Some("notes.txt"), "plain words here"), LanguageDetection::undetected());
+    }
+
+    #[test]
+    fn test_classifier_recognises_unlabelled_snippets() {
+        let samples = [
+            (SourceLanguage::Rust, "use std::fmt;\n\n#[derive(Debug)]\npub struct A;\n\nimpl A {\n    pub fn new() -> Self { let mut a = A; a }\n}\n"),
+            (SourceLanguage::Python, "import os\n\nclass A:\n    def __init__(self):\n        self.x = None\n"),
+            (SourceLanguage::Go, "package main\n\nimport (\n    \"fmt\"\n)\n\nfunc main() {\n    x := 1\n    fmt.Println(x)\n}\n"),
+            (SourceLanguage::C, "#include <stdio.h>\n\nint main(void) {\n    char *p = malloc(sizeof(char));\n    if (p == NULL) return 1;\n}\n"),
+            (SourceLanguage::JavaScript, "const fs = require('fs');\nmodule.exports = function load() {\n    console.log('x');\n};\n"),
+            (SourceLanguage::TypeScript, "interface User {\n    readonly id: number;\n    name: string;\n}\nconst greet = (u: User): string => u.name;\n"),
+        ];
+        for (language, code) in samples {
+            let detection = detect_language(None, code);
+            assert_eq!(detection.language, language, "{:?}", code);
+            assert_eq!(detection.method, DetectionMethod::Content);
+            assert!(detection.confidence > 0.4 && detection.confidence <= 0.8, "{}", detection);
+        }
+        assert!(!detect_language(None, "Hello, world. Nothing to see.").is_known());
+    }
+}
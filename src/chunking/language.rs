@@ -0,0 +1,92 @@
+//! Source language resolution for structural chunking
+//!
+//! Contracts:
+//! - An explicit language name (e.g. `CodeRecord::language`) wins over the path
+//! - File extensions are the fallback; anything unrecognised is `Unknown`
+//!   (`detect::detect_language` also reads shebangs, modelines and content)
+//! - `Unknown` sources are chunked by lines
+
+use std::path::Path;
+
+/// Languages with a structural chunker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SourceLanguage {
+    Rust,
+    Python,
+    TypeScript,
+    JavaScript,
+    Go,
+    C,
+    Unknown,
+}
+
+impl SourceLanguage {
+    /// Language from a name such as `"rust"`, `"py"` or `"TypeScript"`
+    pub fn from_name(name: &str) -> Self {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "rust" | "rs" => SourceLanguage::Rust,
+            "python" | "py" | "python3" => SourceLanguage::Python,
+            "typescript" | "ts" | "tsx" | "ts-node" => SourceLanguage::TypeScript,
+            "javascript" | "js" | "jsx" | "mjs" | "cjs" | "node" => SourceLanguage::JavaScript,
+            "go" | "golang" => SourceLanguage::Go,
+            "c" | "h" => SourceLanguage::C,
+            _ => SourceLanguage::Unknown,
+        }
+    }
+
+    /// Language from a file extension, e.g. `src/main.go`
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some(ext) => match ext.to_ascii_lowercase().as_str() {
+                "rs" => SourceLanguage::Rust,
+                "py" | "pyi" => SourceLanguage::Python,
+                "ts" | "tsx" | "mts" | "cts" => SourceLanguage::TypeScript,
+                "js" | "jsx" | "mjs" | "cjs" => SourceLanguage::JavaScript,
+                "go" => SourceLanguage::Go,
+                "c" | "h" => SourceLanguage::C,
+                _ => SourceLanguage::Unknown,
+            },
+            None => SourceLanguage::Unknown,
+        }
+    }
+
+    /// Explicit language name first, then the path's extension
+    pub fn resolve(name: Option<&str>, path: Option<&str>) -> Self {
+        name.map(Self::from_name)
+            .filter(|language| *language != SourceLanguage::Unknown)
+            .or_else(|| path.map(Self::from_path))
+            .unwrap_or(SourceLanguage::Unknown)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceLanguage::Rust => "rust",
+            SourceLanguage::Python => "python",
+            SourceLanguage::TypeScript => "typescript",
+            SourceLanguage::JavaScript => "javascript",
+            SourceLanguage::Go => "go",
+            SourceLanguage::C => "c",
+            SourceLanguage::Unknown => "unknown",
+        }
+    }
+}
+
+impl std::fmt::Display for SourceLanguage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_takes_precedence_over_extension() {
+        assert_eq!(SourceLanguage::resolve(Some("Python"), Some("script.js")), SourceLanguage::Python);
+        assert_eq!(SourceLanguage::resolve(Some("text"), Some("main.go")), SourceLanguage::Go);
+        assert_eq!(SourceLanguage::resolve(None, Some("lib/util.h")), SourceLanguage::C);
+        assert_eq!(SourceLanguage::resolve(None, Some("README.md")), SourceLanguage::Unknown);
+        assert_eq!(SourceLanguage::resolve(None, None), SourceLanguage::Unknown);
+    }
+}
@@ -2,11 +2,36 @@
 //!
 //! Contracts:
 //! - max_chunk_size: 300 lines
-//! - min_chunk_overlap: 0 lines (no overlap)
-//! - max_file_size_mb: 10MB
+//! - min_chunk_overlap: 0 lines by default (configurable via `TextChunker::with_contract`)
+//! - max_file_size_mb: 10MB (larger files go through `TextChunker::stream_chunks_from_file`)
 //! - processing_time_ms: 100ms per 1MB
 
+use std::fmt;
+use std::ops::Range;
 use std::path::Path;
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+
+pub mod chunker;
+pub mod detect;
+pub mod language;
+pub mod overlap;
+pub mod rust;
+pub mod semantic;
+pub mod streaming;
+pub mod structural;
+pub mod symbols;
+pub mod token_budget;
+
+pub use chunker::{CharacterChunker, Chunker, LineChunker};
+pub use detect::{detect_language, DetectionMethod, LanguageDetection};
+pub use language::SourceLanguage;
+pub use rust::RustSemanticChunker;
+pub use streaming::{ChunkStream, StreamingChunker};
+pub use structural::StructuralChunker;
+pub use symbols::{extract_symbols, Symbol};
+pub use token_budget::{prompt_input_tokens, TokenBudgetChunker};
 
 /// TDD-First chunking contract
 pub struct ChunkingContract {
@@ -27,19 +52,168 @@ impl Default for ChunkingContract {
     }
 }
 
+/// Bytes of the SHA-256 digest kept in a `ChunkId` (128 bits, 32 hex chars)
+const CHUNK_ID_BYTES: usize = 16;
+
+/// Content-addressed chunk identifier
+///
+/// Derived from the file path, the primary symbol and the chunk's own
+/// content (overlap context and surrounding whitespace excluded), so it
+/// survives edits elsewhere in the file. Identical code at the same path
+/// and symbol shares an ID.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChunkId(String);
+
+impl ChunkId {
+    pub fn compute(file_path: Option<&str>, symbol: Option<&str>, content: &str) -> Self {
+        let mut hasher = Sha256::new();
+        // Length prefixes keep ("ab", "c") and ("a", "bc") apart
+        for part in [file_path.unwrap_or_default(), symbol.unwrap_or_default(), content] {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+
+        let digest = hasher.finalize();
+        Self(digest[..CHUNK_ID_BYTES].iter().map(|byte| format!("{:02x}", byte)).collect())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ChunkId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 /// Text chunk with metadata
+///
+/// The line range is location metadata only; identity comes from `Chunk::id`.
 #[derive(Debug, Clone)]
 pub struct Chunk {
-    pub id: u64,
     pub line_start: usize,
     pub line_end: usize,
     pub line_count: usize,
     pub content: String,
+    /// Leading lines repeated from the previous chunk for context (0 = no overlap)
+    pub overlap_lines: usize,
+    /// Source file the lines belong to, when known (e.g. a file inside a repository dump)
+    pub file_path: Option<String>,
+    /// Name of the first named item in the chunk (function, type, ...), when known
+    pub symbol: Option<String>,
+    /// Language the chunk was split as (`Unknown` until a `Chunker` sets it)
+    pub language: SourceLanguage,
+    /// How sure the language detection was, in `[0, 1]` (1.0 when named explicitly)
+    pub language_confidence: f32,
+    /// Items whose first line this chunk owns (filled in by `Chunker::chunk`)
+    pub symbols: Vec<Symbol>,
+}
+
+impl Chunk {
+    /// Chunk covering lines `[line_start, line_end)` with no overlap
+    pub fn new(line_start: usize, line_end: usize, content: String) -> Self {
+        Self {
+            line_start,
+            line_end,
+            line_count: line_end.saturating_sub(line_start),
+            content,
+            overlap_lines: 0,
+            file_path: None,
+            symbol: None,
+            language: SourceLanguage::Unknown,
+            language_confidence: 0.0,
+            symbols: Vec::new(),
+        }
+    }
+
+    /// Whether this chunk defines the symbol named `query` (see `Symbol::matches`)
+    pub fn defines(&self, query: &str) -> bool {
+        self.symbols.iter().any(|symbol| symbol.matches(query))
+    }
+
+    /// Name this chunk after the item it covers, when known
+    pub fn with_symbol(mut self, symbol: Option<String>) -> Self {
+        self.symbol = symbol;
+        self
+    }
+
+    /// Stable content-addressed ID (see `ChunkId`)
+    pub fn id(&self) -> ChunkId {
+        ChunkId::compute(self.file_path.as_deref(), self.symbol.as_deref(), self.owned_content().trim())
+    }
+
+    /// Attribute this chunk to `file_path`
+    pub fn with_file_path(mut self, file_path: impl Into<String>) -> Self {
+        self.file_path = Some(file_path.into());
+        self
+    }
+
+    /// Record the language this chunk was split as, named explicitly
+    pub fn with_language(mut self, language: SourceLanguage) -> Self {
+        self.language = language;
+        self.language_confidence = LanguageDetection::explicit(language).confidence;
+        self
+    }
+
+    /// Record a detected language with its confidence
+    pub fn with_detection(mut self, detection: &LanguageDetection) -> Self {
+        self.language = detection.language;
+        self.language_confidence = detection.confidence;
+        self
+    }
+
+    /// First line owned by this chunk rather than shared with its predecessor
+    pub fn owned_line_start(&self) -> usize {
+        self.line_start + self.overlap_lines
+    }
+
+    /// Lines repeated from the previous chunk, if any
+    pub fn overlap_range(&self) -> Option<Range<usize>> {
+        (self.overlap_lines > 0).then(|| self.line_start..self.owned_line_start())
+    }
+
+    /// Content without the overlapped context prefix
+    pub fn owned_content(&self) -> String {
+        self.content
+            .split('\n')
+            .skip(self.overlap_lines)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Merge `next` into this chunk without duplicating its overlapped lines
+    pub fn merge(&self, next: &Chunk) -> Chunk {
+        let next_owned = next.owned_content();
+        let content = if next_owned.is_empty() {
+            self.content.clone()
+        } else {
+            format!("{}\n{}", self.content, next_owned)
+        };
+
+        Chunk {
+            line_start: self.line_start,
+            line_end: next.line_end.max(self.line_end),
+            line_count: next.line_end.max(self.line_end) - self.line_start,
+            content,
+            overlap_lines: self.overlap_lines,
+            file_path: self.file_path.clone(),
+            symbol: self.symbol.clone().or_else(|| next.symbol.clone()),
+            language: self.language,
+            language_confidence: self.language_confidence,
+            symbols: self.symbols.iter().chain(&next.symbols).cloned().collect(),
+        }
+    }
 }
 
+/// Neighbouring chunks shorter than this are merged by `TextChunker`
+const SMALL_CHUNK_LINES: usize = 10;
+
 /// TDD-First text chunker with executable contracts
 pub struct TextChunker {
     contract: ChunkingContract,
+    chunker: Option<Arc<dyn Chunker>>,
 }
 
 impl Default for TextChunker {
@@ -50,9 +224,20 @@ impl Default for TextChunker {
 
 impl TextChunker {
     pub fn new() -> Self {
-        Self {
-            contract: ChunkingContract::default(),
-        }
+        Self::with_contract(ChunkingContract::default())
+    }
+
+    /// Chunker with a custom contract (chunk size, overlap, file size limit)
+    pub fn with_contract(contract: ChunkingContract) -> Self {
+        Self { contract, chunker: None }
+    }
+
+    /// Split files with `chunker` instead of the contract's line windows
+    ///
+    /// File size and timing contracts still apply.
+    pub fn with_chunker(mut self, chunker: Arc<dyn Chunker>) -> Self {
+        self.chunker = Some(chunker);
+        self
     }
 
     /// Create chunks from file path with TDD validation
@@ -67,36 +252,20 @@ impl TextChunker {
         let metadata = std::fs::metadata(path)?;
         let file_size_mb = (metadata.len() / (1024 * 1024)) as usize;
         if file_size_mb > self.contract.max_file_size_mb {
-            return Err(format!("File too large: {}MB > {}MB",
+            return Err(format!("File too large: {}MB > {}MB (use stream_chunks_from_file)",
                 file_size_mb, self.contract.max_file_size_mb).into());
         }
 
         // Read content
         let content = std::fs::read_to_string(path)?;
-        let lines: Vec<&str> = content.lines().collect();
-        let total_lines = lines.len();
 
         // TDD-First: Create chunks with measurable processing
         let start_time = std::time::Instant::now();
 
-        let mut chunks = Vec::new();
-        for chunk_index in (0..total_lines).step_by(self.contract.max_chunk_size) {
-            let line_start = chunk_index;
-            let line_end = (chunk_index + self.contract.max_chunk_size).min(total_lines);
-            let line_count = line_end - line_start;
-
-            let chunk_content: String = lines[line_start..line_end].join("\n");
-
-            let chunk = Chunk {
-                id: chunk_index as u64,
-                line_start,
-                line_end,
-                line_count,
-                content: chunk_content,
-            };
-
-            chunks.push(chunk);
-        }
+        let chunks = match &self.chunker {
+            Some(chunker) => chunker.chunk_file(file_path, &content)?,
+            None => self.chunk_file(file_path, &content)?,
+        };
 
         let processing_time = start_time.elapsed();
         let expected_max_time_ms = if file_size_mb == 0 {
@@ -116,45 +285,33 @@ impl TextChunker {
             return Err("No chunks created".into());
         }
 
-        // TDD-First: REFACTOR phase - optimize if needed
-        let optimized_chunks = self.optimize_chunks(chunks);
-
-        println!("✅ Created {} chunks", optimized_chunks.len());
-        Ok(optimized_chunks)
-    }
-
-    /// TDD-First: Optimize chunk creation for performance
-    fn optimize_chunks(&self, mut chunks: Vec<Chunk>) -> Vec<Chunk> {
-        // TDD-First: Measure and optimize
-        println!("🔧 Optimizing chunks...");
-
-        // Remove empty chunks
-        chunks.retain(|chunk| !chunk.content.trim().is_empty());
-
-        // Merge very small chunks (TDD optimization)
-        if chunks.len() > 1 {
-            let mut i = 0;
-            while i < chunks.len() - 1 {
-                if chunks[i].line_count < 10 && chunks[i + 1].line_count < 10 {
-                    // Merge small chunks
-                    let merged_content = format!("{}\n{}",
-                        chunks[i].content, chunks[i + 1].content);
-                    chunks[i] = Chunk {
-                        id: chunks[i].id,
-                        line_start: chunks[i].line_start,
-                        line_end: chunks[i + 1].line_end,
-                        line_count: chunks[i].line_count + chunks[i + 1].line_count,
-                        content: merged_content,
-                    };
-                    chunks.remove(i + 1);
-                    continue;
-                }
-                i += 1;
-            }
-        }
+        println!("✅ Created {} chunks", chunks.len());
+        Ok(chunks)
+    }
+
+    /// Stream chunks of a file of any size with bounded memory
+    ///
+    /// Chunks follow the contract's `max_chunk_size` in lines; no whole-file
+    /// size limit applies because the file is never fully loaded.
+    pub async fn stream_chunks_from_file(&self, file_path: &str) -> Result<ChunkStream, Box<dyn std::error::Error>> {
+        let stream = StreamingChunker::new(self.contract.max_chunk_size)
+            .chunk_file(file_path)
+            .await?;
+        Ok(stream)
+    }
+}
+
+/// Contract line windows, with small neighbours merged and overlap applied
+impl Chunker for TextChunker {
+    fn name(&self) -> &str {
+        "text"
+    }
 
-        println!("✅ Optimized to {} chunks", chunks.len());
-        chunks
+    fn split(&self, source: &str, language: SourceLanguage) -> crate::errors::Result<Vec<Chunk>> {
+        LineChunker::new(self.contract.max_chunk_size)
+            .with_small_chunk_merging(SMALL_CHUNK_LINES)
+            .with_overlap(self.contract.min_chunk_overlap)
+            .split(source, language)
     }
 }
 
@@ -191,4 +348,55 @@ mod tests {
         assert!(chunks.iter().all(|chunk| chunk.line_count <= 300));
         assert!(chunks.iter().all(|chunk| chunk.line_count > 0));
     }
+
+    #[tokio::test]
+    async fn test_chunking_contract_overlap_applied() {
+        let chunker = TextChunker::with_contract(ChunkingContract {
+            max_chunk_size: 100,
+            min_chunk_overlap: 5,
+            ..ChunkingContract::default()
+        });
+
+        let test_file = "/tmp/test_chunking_overlap.txt";
+        let test_content = (0..250).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n");
+        std::fs::write(test_file, &test_content).unwrap();
+
+        let chunks = chunker.create_chunks_from_file(test_file).await.unwrap();
+        std::fs::remove_file(test_file).ok();
+
+        assert_eq!(chunks[0].overlap_range(), None);
+        assert_eq!(chunks[1].overlap_range(), Some(95..100));
+        assert!(chunks[1].content.starts_with("line 95"));
+        assert!(chunks[1].owned_content().starts_with("line 100"));
+    }
+
+    #[test]
+    fn test_merge_skips_overlapped_lines() {
+        let first = Chunk::new(0, 3, "a\nb\nc".to_string());
+        let mut second = Chunk::new(2, 5, "c\nd\ne".to_string());
+        second.overlap_lines = 1;
+
+        let merged = first.merge(&second);
+
+        assert_eq!(merged.content, "a\nb\nc\nd\ne");
+        assert_eq!((merged.line_start, merged.line_end, merged.line_count), (0, 5, 5));
+    }
+
+    #[test]
+    fn test_chunk_id_survives_line_shifts() {
+        let original = Chunk::new(10, 12, "fn run() {\n}".to_string()).with_file_path("src/lib.rs");
+        let shifted = Chunk::new(14, 16, "fn run() {\n}".to_string()).with_file_path("src/lib.rs");
+        let mut overlapped = Chunk::new(13, 16, "// context\nfn run() {\n}".to_string()).with_file_path("src/lib.rs");
+        overlapped.overlap_lines = 1;
+
+        assert_eq!(original.id(), shifted.id());
+        assert_eq!(original.id(), overlapped.id());
+        assert_eq!(original.id().as_str().len(), 2 * CHUNK_ID_BYTES);
+
+        let elsewhere = Chunk::new(10, 12, "fn run() {\n}".to_string()).with_file_path("src/main.rs");
+        let mut renamed = original.clone();
+        renamed.symbol = Some("run".to_string());
+        assert_ne!(original.id(), elsewhere.id());
+        assert_ne!(original.id(), renamed.id());
+    }
 }
@@ -0,0 +1,123 @@
+//! Context overlap between consecutive chunks
+//!
+//! Contracts:
+//! - Overlap only prepends lines; the lines a chunk owns never change
+//! - Overlap never reaches past the previous chunk's owned start
+//! - `Chunk::overlap_lines` records the repeated prefix so aggregation
+//!   can count every source line exactly once
+
+use super::semantic::measure_lines;
+use super::Chunk;
+
+/// Prepend up to `overlap` lines from before each chunk as context
+pub fn apply_line_overlap(lines: &[&str], chunks: Vec<Chunk>, overlap: usize) -> Vec<Chunk> {
+    apply_overlap(lines, chunks, overlap, measure_lines)
+}
+
+/// Prepend preceding lines to each chunk while their measured size fits `overlap`
+///
+/// `measure` uses the same units as the chunker budget (lines or tokens).
+pub fn apply_overlap<M>(lines: &[&str], mut chunks: Vec<Chunk>, overlap: usize, measure: M) -> Vec<Chunk>
+where
+    M: Fn(&str) -> usize,
+{
+    if overlap == 0 {
+        return chunks;
+    }
+
+    for index in 1..chunks.len() {
+        let previous_owned_start = chunks[index - 1].owned_line_start();
+        let chunk = &mut chunks[index];
+
+        // Continuations of a long line own no lines, so the chunk after them has nothing to repeat
+        let owned_start = chunk.owned_line_start();
+        if owned_start <= previous_owned_start || owned_start > lines.len() {
+            continue;
+        }
+
+        let mut context_start = owned_start;
+        let mut context_size = 0;
+        while context_start > previous_owned_start {
+            let line_size = measure(lines[context_start - 1]);
+            if context_size + line_size > overlap {
+                break;
+            }
+            context_size += line_size;
+            context_start -= 1;
+        }
+
+        if context_start == owned_start {
+            continue;
+        }
+
+        let context = lines[context_start..owned_start].join("\n");
+        chunk.content = format!("{}\n{}", context, chunk.owned_content());
+        chunk.overlap_lines = owned_start - context_start;
+        chunk.line_start = context_start;
+        chunk.line_count = chunk.line_end - context_start;
+    }
+
+    chunks
+}
+
+/// Number of distinct source lines covered, counting overlapped lines once
+pub fn owned_line_count(chunks: &[Chunk]) -> usize {
+    chunks
+        .iter()
+        .map(|chunk| chunk.line_end.saturating_sub(chunk.owned_line_start()))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunking::semantic::split_lines;
+
+    fn numbered_lines(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("line {}", i)).collect()
+    }
+
+    #[test]
+    fn test_line_overlap_records_range_and_keeps_owned_lines() {
+        let source = numbered_lines(30);
+        let lines: Vec<&str> = source.iter().map(String::as_str).collect();
+        let chunks = split_lines(&lines, 0, lines.len(), 10, &measure_lines);
+
+        let overlapped = apply_line_overlap(&lines, chunks, 3);
+
+        assert_eq!(overlapped[0].overlap_range(), None);
+        assert_eq!(overlapped[1].overlap_range(), Some(7..10));
+        assert_eq!(overlapped[1].line_count, 13);
+        assert!(overlapped[1].content.starts_with("line 7\n"));
+        assert_eq!(overlapped[2].owned_line_start(), 20);
+        assert_eq!(owned_line_count(&overlapped), 30);
+    }
+
+    #[test]
+    fn test_overlap_never_reaches_past_previous_chunk() {
+        let source = numbered_lines(6);
+        let lines: Vec<&str> = source.iter().map(String::as_str).collect();
+        let chunks = split_lines(&lines, 0, lines.len(), 2, &measure_lines);
+
+        let overlapped = apply_line_overlap(&lines, chunks, 5);
+
+        assert_eq!(overlapped[1].overlap_range(), Some(0..2));
+        assert_eq!(overlapped[2].overlap_range(), Some(2..4));
+        assert_eq!(owned_line_count(&overlapped), 6);
+    }
+
+    #[test]
+    fn test_token_overlap_uses_measure() {
+        let source = ["a b c".to_string(), "d".to_string(), "e f".to_string(), "g".to_string()];
+        let lines: Vec<&str> = source.iter().map(String::as_str).collect();
+        let chunks = split_lines(&lines, 0, lines.len(), 1, &measure_lines);
+        let words = |line: &str| line.split_whitespace().count();
+
+        let overlapped = apply_overlap(&lines, chunks, 2, words);
+
+        // "a b c" is three words and does not fit a 2-token overlap
+        assert_eq!(overlapped[1].overlap_range(), None);
+        assert_eq!(overlapped[2].overlap_range(), Some(1..2));
+        assert_eq!(overlapped[3].overlap_range(), Some(2..3));
+    }
+}
@@ -0,0 +1,198 @@
+//! Rust-aware semantic chunking at item boundaries
+//!
+//! Contracts:
+//! - Cuts only between top-level items (`fn`, `impl`, `mod`, `struct`, ...)
+//! - Doc comments and attributes stay with the item they annotate
+//! - Packs small items together up to `max_lines`
+//! - Items larger than the budget, and unparsable files, fall back to line splitting
+
+use syn::spanned::Spanned;
+
+use super::chunker::Chunker;
+use super::language::SourceLanguage;
+use super::semantic::{self, ItemKind, SemanticUnit};
+use super::structural;
+use super::Chunk;
+use crate::errors::Result;
+
+/// Semantic chunker for Rust source using `syn`
+pub struct RustSemanticChunker {
+    max_lines: usize,
+}
+
+impl RustSemanticChunker {
+    pub fn new(max_lines: usize) -> Self {
+        Self { max_lines }
+    }
+
+    /// Chunk Rust source at item boundaries within the line budget
+    pub fn chunk_source(&self, source: &str) -> Vec<Chunk> {
+        chunk_rust_source(source, self.max_lines, semantic::measure_lines)
+    }
+}
+
+/// Rust item boundaries whatever the language hint; other sources fall back to lines
+impl Chunker for RustSemanticChunker {
+    fn name(&self) -> &str {
+        "rust"
+    }
+
+    fn split(&self, source: &str, _language: SourceLanguage) -> Result<Vec<Chunk>> {
+        Ok(self.chunk_source(source))
+    }
+}
+
+/// Chunk Rust source at item boundaries using an arbitrary size measure
+///
+/// Falls back to plain line splitting when the source does not parse.
+pub fn chunk_rust_source<M>(source: &str, budget: usize, measure: M) -> Vec<Chunk>
+where
+    M: Fn(&str) -> usize,
+{
+    structural::chunk_structured(source, SourceLanguage::Rust, budget, measure)
+}
+
+/// Line ranges of the top-level items in `source`, or `None` if it does not parse
+pub fn rust_item_units(source: &str) -> Option<Vec<SemanticUnit>> {
+    let file = syn::parse_file(source).ok()?;
+
+    let units = file
+        .items
+        .iter()
+        .map(|item| {
+            let span = item.span();
+            let (kind, name) = describe_item(item);
+            SemanticUnit {
+                // syn lines are 1-based inclusive; units are 0-based half-open
+                line_start: span.start().line.saturating_sub(1),
+                line_end: span.end().line,
+                kind,
+                name,
+            }
+        })
+        .collect();
+
+    Some(units)
+}
+
+pub(super) fn describe_item(item: &syn::Item) -> (ItemKind, Option<String>) {
+    match item {
+        syn::Item::Fn(item) => (ItemKind::Function, Some(item.sig.ident.to_string())),
+        syn::Item::Struct(item) => (ItemKind::Struct, Some(item.ident.to_string())),
+        syn::Item::Enum(item) => (ItemKind::Enum, Some(item.ident.to_string())),
+        syn::Item::Union(item) => (ItemKind::Union, Some(item.ident.to_string())),
+        syn::Item::Trait(item) => (ItemKind::Trait, Some(item.ident.to_string())),
+        syn::Item::TraitAlias(item) => (ItemKind::Trait, Some(item.ident.to_string())),
+        syn::Item::Mod(item) => (ItemKind::Module, Some(item.ident.to_string())),
+        syn::Item::Const(item) => (ItemKind::Const, Some(item.ident.to_string())),
+        syn::Item::Static(item) => (ItemKind::Static, Some(item.ident.to_string())),
+        syn::Item::Type(item) => (ItemKind::TypeAlias, Some(item.ident.to_string())),
+        syn::Item::Macro(item) => (
+            ItemKind::Macro,
+            item.ident
+                .as_ref()
+                .map(|ident| ident.to_string())
+                .or_else(|| item.mac.path.segments.last().map(|segment| segment.ident.to_string())),
+        ),
+        syn::Item::Impl(item) => (ItemKind::Impl, Some(impl_name(item))),
+        syn::Item::Use(_) | syn::Item::ExternCrate(_) => (ItemKind::Use, None),
+        _ => (ItemKind::Other, None),
+    }
+}
+
+/// `Type` or `Trait for Type`, using the last path segment of each
+pub(super) fn impl_name(item: &syn::ItemImpl) -> String {
+    let self_ty = type_name(&item.self_ty);
+    match &item.trait_ {
+        Some((_, path, _)) => {
+            let trait_name = path
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string())
+                .unwrap_or_default();
+            format!("{} for {}", trait_name, self_ty)
+        }
+        None => self_ty,
+    }
+}
+
+pub(super) fn type_name(ty: &syn::Type) -> String {
+    match ty {
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string())
+            .unwrap_or_default(),
+        syn::Type::Reference(reference) => type_name(&reference.elem),
+        _ => "_".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"use std::fmt;
+
+/// A point
+#[derive(Debug)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn sum(&self) -> i32 {
+        self.x + self.y
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+fn helper() {}
+"#;
+
+    #[test]
+    fn test_rust_item_units_reports_kinds_and_names() {
+        let units = rust_item_units(SOURCE).unwrap();
+        let described: Vec<_> = units.iter().map(|u| (u.kind, u.name.clone())).collect();
+
+        assert_eq!(described[1], (ItemKind::Struct, Some("Point".to_string())));
+        assert_eq!(described[2], (ItemKind::Impl, Some("Point".to_string())));
+        assert_eq!(described[3], (ItemKind::Impl, Some("Display for Point".to_string())));
+        assert_eq!(described[4], (ItemKind::Function, Some("helper".to_string())));
+        // Doc comment and attribute belong to the struct
+        assert_eq!(units[1].line_start, 2);
+    }
+
+    #[test]
+    fn test_chunks_never_cut_inside_items() {
+        let chunks = RustSemanticChunker::new(10).chunk_source(SOURCE);
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            let opens = chunk.content.matches('{').count();
+            let closes = chunk.content.matches('}').count();
+            assert_eq!(opens, closes, "chunk cut mid-item: {}", chunk.content);
+        }
+        assert_eq!(chunks.last().unwrap().line_end, SOURCE.lines().count());
+    }
+
+    #[test]
+    fn test_oversized_items_and_invalid_source_fall_back_to_lines() {
+        let chunks = RustSemanticChunker::new(3).chunk_source(SOURCE);
+        assert!(chunks.iter().all(|chunk| chunk.line_count <= 3));
+
+        let chunks = RustSemanticChunker::new(2).chunk_source("not rust {\nat all\nreally\n");
+        assert_eq!(chunks.len(), 2);
+    }
+}
@@ -0,0 +1,244 @@
+//! Boundary-aware chunk packing shared by the structural chunkers
+//!
+//! Contracts:
+//! - Units are contiguous line ranges that never get split unless oversized
+//! - Small units are packed together until the budget is reached
+//! - Oversized units fall back to line splitting within the same budget
+//! - Every input line lands in exactly one chunk, in order
+
+use super::Chunk;
+
+/// Kind of top-level item a semantic unit was cut around
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemKind {
+    Function,
+    Struct,
+    Enum,
+    Union,
+    Trait,
+    Class,
+    Impl,
+    Module,
+    Const,
+    Static,
+    TypeAlias,
+    Macro,
+    Use,
+    Other,
+}
+
+impl ItemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemKind::Function => "fn",
+            ItemKind::Struct => "struct",
+            ItemKind::Enum => "enum",
+            ItemKind::Union => "union",
+            ItemKind::Trait => "trait",
+            ItemKind::Class => "class",
+            ItemKind::Impl => "impl",
+            ItemKind::Module => "mod",
+            ItemKind::Const => "const",
+            ItemKind::Static => "static",
+            ItemKind::TypeAlias => "type",
+            ItemKind::Macro => "macro",
+            ItemKind::Use => "use",
+            ItemKind::Other => "other",
+        }
+    }
+
+    /// Inverse of `as_str`; unknown names map to `Other`
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "fn" => ItemKind::Function,
+            "struct" => ItemKind::Struct,
+            "enum" => ItemKind::Enum,
+            "union" => ItemKind::Union,
+            "trait" => ItemKind::Trait,
+            "class" => ItemKind::Class,
+            "impl" => ItemKind::Impl,
+            "mod" => ItemKind::Module,
+            "const" => ItemKind::Const,
+            "static" => ItemKind::Static,
+            "type" => ItemKind::TypeAlias,
+            "macro" => ItemKind::Macro,
+            "use" => ItemKind::Use,
+            _ => ItemKind::Other,
+        }
+    }
+}
+
+/// Contiguous line range `[line_start, line_end)` holding one item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticUnit {
+    pub line_start: usize,
+    pub line_end: usize,
+    pub kind: ItemKind,
+    pub name: Option<String>,
+}
+
+/// Extend item ranges so they tile `[0, total_lines)` without gaps
+///
+/// Lines between items (comments, blank lines) attach to the following
+/// item so leading comments stay with the code they describe; trailing
+/// lines attach to the last item.
+pub fn cover_lines(mut units: Vec<SemanticUnit>, total_lines: usize) -> Vec<SemanticUnit> {
+    units.sort_by_key(|unit| unit.line_start);
+
+    let mut next_start = 0;
+    for unit in units.iter_mut() {
+        unit.line_start = next_start;
+        unit.line_end = unit.line_end.max(next_start);
+        next_start = unit.line_end;
+    }
+
+    if let Some(last) = units.last_mut() {
+        last.line_end = total_lines.max(last.line_end);
+    }
+
+    units
+}
+
+/// Pack units into chunks whose measured size stays within `budget`
+///
+/// `measure` sizes a piece of text in budget units (lines, characters or
+/// tokens). A unit larger than the whole budget is split line by line.
+pub fn pack_units<M>(lines: &[&str], units: &[SemanticUnit], budget: usize, measure: M) -> Vec<Chunk>
+where
+    M: Fn(&str) -> usize,
+{
+    let budget = budget.max(1);
+    let mut chunks = Vec::new();
+    let mut current: Option<(usize, usize, Option<String>)> = None;
+    let mut current_size = 0;
+
+    for unit in units {
+        let end = unit.line_end.min(lines.len());
+        if unit.line_start >= end {
+            continue;
+        }
+
+        let unit_size: usize = lines[unit.line_start..end].iter().map(|line| measure(line)).sum();
+
+        if unit_size > budget {
+            if let Some((start, current_end, symbol)) = current.take() {
+                chunks.push(build_chunk(lines, start, current_end).with_symbol(symbol));
+            }
+            current_size = 0;
+            chunks.extend(
+                split_lines(lines, unit.line_start, end, budget, &measure)
+                    .into_iter()
+                    .map(|chunk| chunk.with_symbol(unit.name.clone())),
+            );
+            continue;
+        }
+
+        match current.take() {
+            Some((start, _, symbol)) if current_size + unit_size <= budget => {
+                // The chunk is named after its first named unit
+                current = Some((start, end, symbol.or_else(|| unit.name.clone())));
+                current_size += unit_size;
+            }
+            Some((start, current_end, symbol)) => {
+                chunks.push(build_chunk(lines, start, current_end).with_symbol(symbol));
+                current = Some((unit.line_start, end, unit.name.clone()));
+                current_size = unit_size;
+            }
+            None => {
+                current = Some((unit.line_start, end, unit.name.clone()));
+                current_size = unit_size;
+            }
+        }
+    }
+
+    if let Some((start, end, symbol)) = current {
+        chunks.push(build_chunk(lines, start, end).with_symbol(symbol));
+    }
+
+    chunks.retain(|chunk| !chunk.content.trim().is_empty());
+    chunks
+}
+
+/// Split `[start, end)` into line windows that each fit the budget
+///
+/// A single line larger than the budget still becomes its own chunk.
+pub fn split_lines<M>(lines: &[&str], start: usize, end: usize, budget: usize, measure: &M) -> Vec<Chunk>
+where
+    M: Fn(&str) -> usize,
+{
+    let mut chunks = Vec::new();
+    let mut window_start = start;
+    let mut window_size = 0;
+
+    for index in start..end {
+        let line_size = measure(lines[index]);
+        if index > window_start && window_size + line_size > budget {
+            chunks.push(build_chunk(lines, window_start, index));
+            window_start = index;
+            window_size = 0;
+        }
+        window_size += line_size;
+    }
+
+    if window_start < end {
+        chunks.push(build_chunk(lines, window_start, end));
+    }
+
+    chunks
+}
+
+fn build_chunk(lines: &[&str], line_start: usize, line_end: usize) -> Chunk {
+    Chunk::new(line_start, line_end, lines[line_start..line_end].join("\n"))
+}
+
+/// Line-count measure: every line costs one unit of budget
+pub fn measure_lines(_line: &str) -> usize {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(line_start: usize, line_end: usize) -> SemanticUnit {
+        SemanticUnit { line_start, line_end, kind: ItemKind::Function, name: None }
+    }
+
+    #[test]
+    fn test_cover_lines_attaches_gaps_to_next_item() {
+        let units = cover_lines(vec![unit(2, 4), unit(6, 8)], 10);
+
+        assert_eq!((units[0].line_start, units[0].line_end), (0, 4));
+        assert_eq!((units[1].line_start, units[1].line_end), (4, 10));
+    }
+
+    #[test]
+    fn test_pack_units_respects_budget_and_splits_oversized() {
+        let source = (0..20).map(|i| format!("line {}", i)).collect::<Vec<_>>();
+        let lines: Vec<&str> = source.iter().map(String::as_str).collect();
+        let units = vec![unit(0, 3), unit(3, 6), unit(6, 20)];
+
+        let chunks = pack_units(&lines, &units, 6, measure_lines);
+
+        assert_eq!((chunks[0].line_start, chunks[0].line_end), (0, 6));
+        assert!(chunks[1..].iter().all(|chunk| chunk.line_count <= 6));
+        assert_eq!(chunks.last().unwrap().line_end, 20);
+        assert_eq!(chunks.iter().map(|chunk| chunk.line_count).sum::<usize>(), 20);
+    }
+
+    #[test]
+    fn test_pack_units_names_chunks_after_first_named_unit() {
+        let source = (0..6).map(|i| format!("line {}", i)).collect::<Vec<_>>();
+        let lines: Vec<&str> = source.iter().map(String::as_str).collect();
+        let named = |line_start, line_end, name: &str| SemanticUnit {
+            name: Some(name.to_string()),
+            ..unit(line_start, line_end)
+        };
+        let units = vec![unit(0, 1), named(1, 3, "first"), named(3, 4, "second"), named(4, 6, "third")];
+
+        let chunks = pack_units(&lines, &units, 4, measure_lines);
+
+        assert_eq!(chunks[0].symbol.as_deref(), Some("first"));
+        assert_eq!(chunks[1].symbol.as_deref(), Some("third"));
+    }
+}
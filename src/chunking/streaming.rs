@@ -0,0 +1,185 @@
+//! Streaming chunker for files beyond the in-memory size contract
+//!
+//! Contracts:
+//! - Reads through a buffered reader; never holds more than one chunk in memory
+//! - Chunks hold at most `max_lines` lines and `max_chunk_bytes` bytes
+//! - Lines longer than the byte budget (minified files) are cut on UTF-8
+//!   boundaries; every piece keeps the line number it came from
+//! - Concatenating the chunks (with line breaks) reproduces the input
+
+use std::path::Path;
+use std::pin::Pin;
+
+use async_stream::try_stream;
+use futures::Stream;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+
+use super::Chunk;
+use crate::errors::{ProcessingError, Result};
+
+/// Read buffer used for files opened by `StreamingChunker::chunk_file`
+pub const READ_BUFFER_BYTES: usize = 64 * 1024;
+
+/// Default cap on the bytes of a single chunk
+pub const DEFAULT_MAX_CHUNK_BYTES: usize = 1024 * 1024;
+
+/// Chunks produced incrementally from a reader
+pub type ChunkStream = Pin<Box<dyn Stream<Item = Result<Chunk>> + Send>>;
+
+/// Line-based chunker that yields chunks while reading
+pub struct StreamingChunker {
+    max_lines: usize,
+    max_chunk_bytes: usize,
+}
+
+impl StreamingChunker {
+    pub fn new(max_lines: usize) -> Self {
+        Self {
+            max_lines: max_lines.max(1),
+            max_chunk_bytes: DEFAULT_MAX_CHUNK_BYTES,
+        }
+    }
+
+    /// Cap the bytes held per chunk (at least one UTF-8 character)
+    pub fn with_max_chunk_bytes(mut self, max_chunk_bytes: usize) -> Self {
+        self.max_chunk_bytes = max_chunk_bytes.max(4);
+        self
+    }
+
+    /// Stream chunks of the file at `path`
+    ///
+    /// # Errors
+    /// * `ProcessingError::ChunkingFailed` - the file cannot be opened
+    pub async fn chunk_file(&self, path: impl AsRef<Path>) -> Result<ChunkStream> {
+        let path = path.as_ref();
+        let file = tokio::fs::File::open(path).await.map_err(|e| ProcessingError::ChunkingFailed {
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        })?;
+
+        Ok(self.chunk_reader(BufReader::with_capacity(READ_BUFFER_BYTES, file)))
+    }
+
+    /// Stream chunks from any buffered reader
+    pub fn chunk_reader<R>(&self, mut reader: R) -> ChunkStream
+    where
+        R: AsyncBufRead + Unpin + Send + 'static,
+    {
+        let max_lines = self.max_lines;
+        let max_chunk_bytes = self.max_chunk_bytes;
+
+        Box::pin(try_stream! {
+            let mut pending: Vec<u8> = Vec::new();
+            let mut chunk_line_start = 0;
+            let mut chunk_lines = 0;
+            let mut line = 0;
+
+            loop {
+                let available = reader.fill_buf().await?;
+                if available.is_empty() {
+                    break;
+                }
+
+                // Take up to the next line break, without overrunning the byte budget
+                let room = max_chunk_bytes.saturating_sub(pending.len()).max(1);
+                let window = &available[..available.len().min(room)];
+                let (taken, ends_line) = match window.iter().position(|&b| b == b'\n') {
+                    Some(newline) => (newline + 1, true),
+                    None => (window.len(), false),
+                };
+                pending.extend_from_slice(&window[..taken]);
+                reader.consume(taken);
+
+                if ends_line {
+                    line += 1;
+                    chunk_lines += 1;
+                }
+
+                if chunk_lines >= max_lines || pending.len() >= max_chunk_bytes {
+                    // A chunk cut mid-line still covers that line
+                    let line_end = if ends_line { line } else { line + 1 };
+                    if let Some(chunk) = take_chunk(&mut pending, chunk_line_start, line_end) {
+                        yield chunk;
+                    }
+                    chunk_line_start = line;
+                    chunk_lines = 0;
+                }
+            }
+
+            let line_end = if pending.ends_with(b"\n") { line } else { line + 1 };
+            if let Some(chunk) = take_chunk(&mut pending, chunk_line_start, line_end) {
+                yield chunk;
+            }
+        })
+    }
+}
+
+/// Turn the complete UTF-8 prefix of `pending` into a chunk, keeping any cut character
+fn take_chunk(pending: &mut Vec<u8>, line_start: usize, line_end: usize) -> Option<Chunk> {
+    let rest = pending.split_off(complete_utf8_len(pending));
+    let mut content = String::from_utf8_lossy(pending).replace("\r\n", "\n");
+    *pending = rest;
+
+    // Like `lines()`, the chunk does not end with the final line break
+    if content.ends_with('\n') {
+        content.pop();
+    }
+
+    if content.trim().is_empty() {
+        return None;
+    }
+
+    Some(Chunk::new(line_start, line_end, content))
+}
+
+/// Length of `bytes` without a trailing, incomplete UTF-8 sequence
+fn complete_utf8_len(bytes: &[u8]) -> usize {
+    match std::str::from_utf8(bytes) {
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        // Valid text, or invalid bytes that lossy decoding will replace anyway
+        _ => bytes.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt;
+    use std::io::Cursor;
+
+    async fn collect(stream: ChunkStream) -> Vec<Chunk> {
+        stream.try_collect().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_streams_line_chunks_with_line_ranges() {
+        let text = (0..25).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n") + "\n";
+        let chunker = StreamingChunker::new(10);
+
+        let chunks = collect(chunker.chunk_reader(Cursor::new(text.into_bytes()))).await;
+
+        let ranges: Vec<(usize, usize)> = chunks.iter().map(|chunk| (chunk.line_start, chunk.line_end)).collect();
+        assert_eq!(ranges, vec![(0, 10), (10, 20), (20, 25)]);
+        assert!(chunks[1].content.starts_with("line 10\n"));
+        assert!(chunks[2].content.ends_with("line 24"));
+    }
+
+    #[tokio::test]
+    async fn test_long_lines_are_cut_on_char_boundaries() {
+        let minified = "é".repeat(1000);
+        let chunker = StreamingChunker::new(10).with_max_chunk_bytes(101);
+
+        let chunks = collect(chunker.chunk_reader(Cursor::new(minified.clone().into_bytes()))).await;
+
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.content.len() <= 101));
+        assert!(chunks.iter().all(|chunk| (chunk.line_start, chunk.line_end) == (0, 1)));
+        assert_eq!(chunks.iter().map(|chunk| chunk.content.as_str()).collect::<String>(), minified);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_file_reports_missing_files() {
+        let result = StreamingChunker::new(10).chunk_file("/tmp/does_not_exist_streaming.txt").await;
+        assert!(matches!(result, Err(ProcessingError::ChunkingFailed { .. })));
+    }
+}
@@ -0,0 +1,608 @@
+//! Structural chunking for Rust, Python, TypeScript/JavaScript, Go and C
+//!
+//! Contracts:
+//! - Cuts only between top-level units (functions, classes, types, imports, ...)
+//! - Python units are column-0 statements with their indented blocks
+//! - Brace languages end a unit when brackets balance at a `}` or `;` (any line for Go)
+//! - Comments and strings never open or close a unit
+//! - Unknown languages, and Rust that does not parse, fall back to line splitting
+
+use super::chunker::Chunker;
+use super::language::SourceLanguage;
+use super::overlap::apply_overlap;
+use super::rust::rust_item_units;
+use super::semantic::{self, ItemKind, SemanticUnit};
+use super::Chunk;
+use crate::cozodb::CodeRecord;
+use crate::errors::Result;
+
+/// Characters per token assumed when sizing by estimated tokens
+pub const ESTIMATED_CHARS_PER_TOKEN: usize = 4;
+
+/// Budgeted chunker that picks its unit finder from the source language
+///
+/// The budget is in lines, or in estimated tokens when built with
+/// `by_estimated_tokens` (for callers without a tokenizer).
+pub struct StructuralChunker {
+    budget: usize,
+    overlap: usize,
+    chars_per_token: Option<usize>,
+}
+
+impl StructuralChunker {
+    pub fn new(max_lines: usize) -> Self {
+        Self {
+            budget: max_lines,
+            overlap: 0,
+            chars_per_token: None,
+        }
+    }
+
+    /// Budget of `max_tokens`, estimated at `ESTIMATED_CHARS_PER_TOKEN` characters each
+    pub fn by_estimated_tokens(max_tokens: usize) -> Self {
+        Self {
+            budget: max_tokens,
+            overlap: 0,
+            chars_per_token: Some(ESTIMATED_CHARS_PER_TOKEN),
+        }
+    }
+
+    /// Repeat preceding lines, up to `overlap` budget units, at the start of each chunk
+    pub fn with_overlap(mut self, overlap: usize) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// Chunk `source` at the top-level units of `language`
+    pub fn chunk_source(&self, source: &str, language: SourceLanguage) -> Vec<Chunk> {
+        let measure = |line: &str| match self.chars_per_token {
+            Some(chars_per_token) => line.len() / chars_per_token + 1,
+            None => semantic::measure_lines(line),
+        };
+
+        let chunks = chunk_structured(source, language, self.budget, measure);
+        let lines: Vec<&str> = source.lines().collect();
+        apply_overlap(&lines, chunks, self.overlap, measure)
+    }
+
+    /// Chunk a stored record, using its language or else its file path
+    pub fn chunk_record(&self, record: &CodeRecord) -> Vec<Chunk> {
+        self.chunk_source(&record.content, record.source_language())
+    }
+}
+
+impl Chunker for StructuralChunker {
+    fn name(&self) -> &str {
+        "structural"
+    }
+
+    fn split(&self, source: &str, language: SourceLanguage) -> Result<Vec<Chunk>> {
+        Ok(self.chunk_source(source, language))
+    }
+}
+
+/// Chunk `source` at the top-level units of `language` using an arbitrary size measure
+pub fn chunk_structured<M>(source: &str, language: SourceLanguage, budget: usize, measure: M) -> Vec<Chunk>
+where
+    M: Fn(&str) -> usize,
+{
+    let lines: Vec<&str> = source.lines().collect();
+
+    match language_units(source, language) {
+        Some(units) if !units.is_empty() => {
+            let units = semantic::cover_lines(units, lines.len());
+            semantic::pack_units(&lines, &units, budget, measure)
+        }
+        _ => {
+            let mut chunks = semantic::split_lines(&lines, 0, lines.len(), budget.max(1), &measure);
+            chunks.retain(|chunk| !chunk.content.trim().is_empty());
+            chunks
+        }
+    }
+}
+
+/// Top-level units of `source`, or `None` when the language has no unit finder
+pub fn language_units(source: &str, language: SourceLanguage) -> Option<Vec<SemanticUnit>> {
+    match language {
+        SourceLanguage::Rust => rust_item_units(source),
+        SourceLanguage::Python => Some(python_units(source)),
+        SourceLanguage::TypeScript | SourceLanguage::JavaScript | SourceLanguage::Go | SourceLanguage::C => {
+            Some(brace_units(source, language))
+        }
+        SourceLanguage::Unknown => None,
+    }
+}
+
+// === Python ===
+
+/// Bracket and triple-quote state carried across Python lines
+#[derive(Default)]
+struct PythonScanner {
+    depth: usize,
+    triple_quote: Option<&'static [u8]>,
+}
+
+impl PythonScanner {
+    fn in_statement(&self) -> bool {
+        self.depth > 0 || self.triple_quote.is_some()
+    }
+
+    fn scan_line(&mut self, line: &str) {
+        let bytes = line.as_bytes();
+        let mut i = 0;
+
+        while i < bytes.len() {
+            if let Some(quote) = self.triple_quote {
+                if bytes[i..].starts_with(quote) {
+                    self.triple_quote = None;
+                    i += quote.len();
+                } else {
+                    i += 1;
+                }
+                continue;
+            }
+
+            match bytes[i] {
+                b'#' => break,
+                quote @ (b'\'' | b'"') => {
+                    let triple: &'static [u8] = if quote == b'"' { b"\"\"\"" } else { b"'''" };
+                    if bytes[i..].starts_with(triple) {
+                        self.triple_quote = Some(triple);
+                        i += triple.len();
+                        continue;
+                    }
+                    // Single-line string: skip to the closing quote
+                    i += 1;
+                    while i < bytes.len() && bytes[i] != quote {
+                        i += if bytes[i] == b'\\' { 2 } else { 1 };
+                    }
+                }
+                b'(' | b'[' | b'{' => self.depth += 1,
+                b')' | b']' | b'}' => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+/// Column-0 statements of a Python module, each with its indented block
+pub fn python_units(source: &str) -> Vec<SemanticUnit> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut scanner = PythonScanner::default();
+    let mut starts = Vec::new();
+    let mut continued = false;
+    let mut after_decorator = false;
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        let at_column_zero = !trimmed.is_empty() && trimmed.len() == line.len();
+
+        if at_column_zero && !scanner.in_statement() && !continued && starts_python_unit(trimmed) {
+            // Decorators stay with the definition they decorate
+            if !after_decorator {
+                starts.push(index);
+            }
+            after_decorator = trimmed.starts_with('@');
+        }
+
+        scanner.scan_line(line);
+        continued = !scanner.in_statement() && line.trim_end().ends_with('\\');
+    }
+
+    starts
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &start)| {
+            let next_start = starts.get(i + 1).copied().unwrap_or(lines.len());
+            // Blank lines and column-0 comments before the next unit belong to it
+            let end = (start..next_start)
+                .rev()
+                .find(|&index| is_python_content(lines[index]))
+                .map(|index| index + 1)?;
+            let (kind, name) = describe_python_unit(&lines[start..end]);
+            Some(SemanticUnit { line_start: start, line_end: end, kind, name })
+        })
+        .collect()
+}
+
+fn starts_python_unit(trimmed: &str) -> bool {
+    const CONTINUATIONS: [&str; 4] = ["else", "elif", "except", "finally"];
+
+    let (word, _) = split_word(trimmed);
+    !trimmed.starts_with('#')
+        && !trimmed.starts_with([')', ']', '}'])
+        && !CONTINUATIONS.contains(&word)
+}
+
+fn is_python_content(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    let top_level_comment = trimmed.len() == line.len() && trimmed.starts_with('#');
+    !trimmed.is_empty() && !top_level_comment
+}
+
+fn describe_python_unit(lines: &[&str]) -> (ItemKind, Option<String>) {
+    let header = lines
+        .iter()
+        .map(|line| line.trim())
+        .find(|line| !line.starts_with('@') && !line.starts_with('#') && !line.is_empty())
+        .unwrap_or_default();
+
+    let (word, rest) = split_word(header);
+    let (word, rest) = if word == "async" { split_word(rest) } else { (word, rest) };
+
+    match word {
+        "def" => (ItemKind::Function, identifier(rest)),
+        "class" => (ItemKind::Class, identifier(rest)),
+        "import" | "from" => (ItemKind::Use, None),
+        _ => (ItemKind::Other, None),
+    }
+}
+
+// === TypeScript / JavaScript, Go, C ===
+
+/// Bracket, string and comment state carried across lines of brace languages
+#[derive(Default)]
+struct BraceScanner {
+    depth: usize,
+    in_block_comment: bool,
+    open_string: Option<char>,
+}
+
+impl BraceScanner {
+    fn at_top_level(&self) -> bool {
+        self.depth == 0 && !self.in_block_comment && self.open_string.is_none()
+    }
+
+    /// Scan one line, returning its last significant character outside comments
+    fn scan_line(&mut self, line: &str, raw_backticks: bool) -> Option<char> {
+        let chars: Vec<char> = line.chars().collect();
+        let mut last = None;
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+
+            if self.in_block_comment {
+                if c == '*' && next == Some('/') {
+                    self.in_block_comment = false;
+                    i += 1;
+                }
+                i += 1;
+                continue;
+            }
+
+            if let Some(quote) = self.open_string {
+                if c == '\\' && !(raw_backticks && quote == '`') {
+                    i += 2;
+                    continue;
+                }
+                if c == quote {
+                    self.open_string = None;
+                    last = Some(c);
+                }
+                i += 1;
+                continue;
+            }
+
+            match (c, next) {
+                ('/', Some('/')) => break,
+                ('/', Some('*')) => {
+                    self.in_block_comment = true;
+                    i += 2;
+                    continue;
+                }
+                ('"' | '\'' | '`', _) => self.open_string = Some(c),
+                ('{' | '(' | '[', _) => self.depth += 1,
+                ('}' | ')' | ']', _) => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+
+            if !c.is_whitespace() {
+                last = Some(c);
+            }
+            i += 1;
+        }
+
+        // Only backtick strings (JS templates, Go raw strings) span lines
+        if matches!(self.open_string, Some('"') | Some('\'')) {
+            self.open_string = None;
+        }
+
+        last
+    }
+}
+
+/// Top-level declarations of a brace-delimited source file
+pub fn brace_units(source: &str, language: SourceLanguage) -> Vec<SemanticUnit> {
+    let lines: Vec<&str> = source.lines().collect();
+    let raw_backticks = language == SourceLanguage::Go;
+    let mut scanner = BraceScanner::default();
+    let mut units = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+    let mut in_directive = false;
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+
+        // C preprocessor lines (with `\` continuations) are units of their own
+        let directive = language == SourceLanguage::C
+            && scanner.at_top_level()
+            && (in_directive || trimmed.starts_with('#'));
+
+        let last = if directive {
+            in_directive = trimmed.ends_with('\\');
+            trimmed.chars().last()
+        } else {
+            scanner.scan_line(line, raw_backticks)
+        };
+
+        if last.is_none() {
+            continue;
+        }
+
+        let start = current.map_or(index, |(start, _)| start);
+        current = Some((start, index + 1));
+
+        let complete = if directive {
+            !in_directive
+        } else {
+            scanner.at_top_level()
+                && match language {
+                    // Go terminates statements at line ends
+                    SourceLanguage::Go => true,
+                    _ => matches!(last, Some('}') | Some(';')),
+                }
+        };
+
+        if complete {
+            units.push(brace_unit(&lines, start, index + 1, language));
+            current = None;
+        }
+    }
+
+    if let Some((start, end)) = current {
+        units.push(brace_unit(&lines, start, end, language));
+    }
+
+    units
+}
+
+fn brace_unit(lines: &[&str], line_start: usize, line_end: usize, language: SourceLanguage) -> SemanticUnit {
+    let header = unit_header(&lines[line_start..line_end]);
+    let (kind, name) = match language {
+        SourceLanguage::Go => describe_go_unit(&header),
+        SourceLanguage::C => describe_c_unit(&header),
+        _ => describe_script_unit(&header),
+    };
+    SemanticUnit { line_start, line_end, kind, name }
+}
+
+/// Declaration text up to the body: code lines joined, cut at the first `{` or `;`
+fn unit_header(lines: &[&str]) -> String {
+    let header = lines
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with("//") && !line.starts_with("/*") && !line.starts_with('*'))
+        .filter(|line| !line.starts_with('@'))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    match header.find(['{', ';']) {
+        Some(end) if !header.starts_with('#') => header[..end].to_string(),
+        _ => header,
+    }
+}
+
+fn describe_script_unit(header: &str) -> (ItemKind, Option<String>) {
+    const MODIFIERS: [&str; 5] = ["export", "default", "declare", "async", "abstract"];
+
+    let (keyword, rest) = skip_modifiers(header, &MODIFIERS);
+    match keyword {
+        "function" => (ItemKind::Function, identifier(rest)),
+        "class" => (ItemKind::Class, identifier(rest)),
+        "interface" => (ItemKind::Trait, identifier(rest)),
+        "type" => (ItemKind::TypeAlias, identifier(rest)),
+        "enum" => (ItemKind::Enum, identifier(rest)),
+        "namespace" | "module" => (ItemKind::Module, identifier(rest)),
+        "const" | "let" | "var" if rest.contains("=>") || rest.contains("function") => {
+            (ItemKind::Function, identifier(rest))
+        }
+        "const" | "let" | "var" => (ItemKind::Const, identifier(rest)),
+        "import" => (ItemKind::Use, None),
+        _ => (ItemKind::Other, None),
+    }
+}
+
+fn describe_go_unit(header: &str) -> (ItemKind, Option<String>) {
+    let (keyword, rest) = split_word(header);
+    match keyword {
+        "func" => {
+            // Methods carry a receiver before the name: `func (s *Server) Run()`
+            let rest = rest.trim_start();
+            let name_part = match rest.strip_prefix('(') {
+                Some(receiver) => receiver.split_once(')').map_or("", |(_, after)| after),
+                None => rest,
+            };
+            (ItemKind::Function, identifier(name_part))
+        }
+        "type" => {
+            let (name, after) = split_word(rest);
+            let kind = match split_word(after).0 {
+                "struct" => ItemKind::Struct,
+                "interface" => ItemKind::Trait,
+                _ => ItemKind::TypeAlias,
+            };
+            (kind, (!name.is_empty()).then(|| name.to_string()))
+        }
+        "var" => (ItemKind::Static, identifier(rest)),
+        "const" => (ItemKind::Const, identifier(rest)),
+        "import" => (ItemKind::Use, None),
+        "package" => (ItemKind::Module, identifier(rest)),
+        _ => (ItemKind::Other, None),
+    }
+}
+
+fn describe_c_unit(header: &str) -> (ItemKind, Option<String>) {
+    const MODIFIERS: [&str; 4] = ["static", "extern", "inline", "const"];
+
+    if let Some(directive) = header.strip_prefix('#') {
+        let (word, rest) = split_word(directive);
+        return match word {
+            "include" => (ItemKind::Use, None),
+            "define" => (ItemKind::Macro, identifier(rest)),
+            _ => (ItemKind::Other, None),
+        };
+    }
+
+    let (keyword, rest) = skip_modifiers(header, &MODIFIERS);
+    if let Some(paren) = header.find('(') {
+        // Function definition or prototype: the name precedes the parameter list
+        let before = header[..paren].trim_end();
+        let start = before.rfind(|c: char| !is_identifier_char(c)).map_or(0, |i| i + 1);
+        let name = &before[start..];
+        if !name.is_empty() && keyword != "typedef" {
+            return (ItemKind::Function, Some(name.to_string()));
+        }
+    }
+
+    match keyword {
+        "struct" => (ItemKind::Struct, identifier(rest)),
+        "enum" => (ItemKind::Enum, identifier(rest)),
+        "union" => (ItemKind::Union, identifier(rest)),
+        "typedef" => (ItemKind::TypeAlias, None),
+        _ => (ItemKind::Static, None),
+    }
+}
+
+// === Header parsing helpers ===
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Leading identifier-like word and the text after it
+fn split_word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    let end = text.find(|c: char| !is_identifier_char(c)).unwrap_or(text.len());
+    (&text[..end], &text[end..])
+}
+
+fn skip_modifiers<'a>(header: &'a str, modifiers: &[&str]) -> (&'a str, &'a str) {
+    let mut rest = header;
+    loop {
+        let (word, after) = split_word(rest);
+        if !modifiers.contains(&word) {
+            return (word, after);
+        }
+        rest = after;
+    }
+}
+
+/// Identifier at the start of `text`, skipping a generator `*`
+fn identifier(text: &str) -> Option<String> {
+    let (word, _) = split_word(text.trim_start().trim_start_matches('*'));
+    (!word.is_empty()).then(|| word.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds_and_names(units: &[SemanticUnit]) -> Vec<(ItemKind, Option<&str>)> {
+        units.iter().map(|unit| (unit.kind, unit.name.as_deref())).collect()
+    }
+
+    #[test]
+    fn test_python_units_follow_indentation_blocks() {
+        let source = r#"import os
+
+# Loads settings
+@cached
+def load(path):
+    data = open(path).read()
+    return parse(data, """
+not a unit
+""")
+
+class Config(
+    Base,
+):
+    def get(self):
+        return 1
+"#;
+        let units = python_units(source);
+
+        assert_eq!(
+            kinds_and_names(&units),
+            vec![
+                (ItemKind::Use, None),
+                (ItemKind::Function, Some("load")),
+                (ItemKind::Class, Some("Config")),
+            ]
+        );
+        assert_eq!((units[1].line_start, units[1].line_end), (3, 9));
+        assert_eq!(units[2].line_end, 15);
+    }
+
+    #[test]
+    fn test_brace_units_for_typescript_go_and_c() {
+        let typescript = "import { a } from './a';\n\n/* { */\nexport async function run() {\n  return `}`;\n}\nexport const handler = () => {\n  run();\n};\ninterface Props {\n  id: string;\n}\n";
+        assert_eq!(
+            kinds_and_names(&brace_units(typescript, SourceLanguage::TypeScript)),
+            vec![
+                (ItemKind::Use, None),
+                (ItemKind::Function, Some("run")),
+                (ItemKind::Function, Some("handler")),
+                (ItemKind::Trait, Some("Props")),
+            ]
+        );
+
+        let go = "package main\n\nimport (\n\t\"fmt\"\n)\n\nfunc (s *Server) Run() error {\n\treturn nil\n}\n\ntype Server struct {\n\tname string\n}\n";
+        assert_eq!(
+            kinds_and_names(&brace_units(go, SourceLanguage::Go)),
+            vec![
+                (ItemKind::Module, Some("main")),
+                (ItemKind::Use, None),
+                (ItemKind::Function, Some("Run")),
+                (ItemKind::Struct, Some("Server")),
+            ]
+        );
+
+        let c = "#include <stdio.h>\n#define MAX(a, b) \\\n  ((a) > (b) ? (a) : (b))\n\nstatic int\ncount(const char *s)\n{\n  return 0; /* } */\n}\n\nstruct point {\n  int x;\n};\n";
+        let units = brace_units(c, SourceLanguage::C);
+        assert_eq!(
+            kinds_and_names(&units),
+            vec![
+                (ItemKind::Use, None),
+                (ItemKind::Macro, Some("MAX")),
+                (ItemKind::Function, Some("count")),
+                (ItemKind::Struct, Some("point")),
+            ]
+        );
+        assert_eq!((units[2].line_start, units[2].line_end), (4, 9));
+    }
+
+    #[test]
+    fn test_unknown_language_falls_back_to_lines() {
+        let source = (0..25).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n");
+
+        let chunks = StructuralChunker::new(10).chunk_source(&source, SourceLanguage::Unknown);
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!((chunks[2].line_start, chunks[2].line_end), (20, 25));
+    }
+
+    #[test]
+    fn test_chunk_record_uses_language_then_path() {
+        let source = "def a():\n    return 1\n\ndef b():\n    return 2\n";
+        let mut record = CodeRecord::new("r1", source, "text");
+        record.update_metadata(crate::cozodb::record::FILE_PATH_KEY, serde_json::json!("pkg/mod.py"));
+
+        let chunks = StructuralChunker::new(3).chunk_record(&record);
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].content, "\ndef b():\n    return 2");
+    }
+}
@@ -0,0 +1,259 @@
+//! Symbols defined by a source file, attached to the chunks that define them
+//!
+//! Contracts:
+//! - Rust symbols come from `syn`, including associated items (`Point::new`,
+//!   `Display for Point::fmt`) and inline modules (`tests::helper`)
+//! - Other languages report their named top-level units
+//! - A symbol belongs to the chunk that owns its first line, so overlapped
+//!   context never lists a symbol twice
+//! - Lines are 0-based and end-exclusive like chunks; JSON uses 1-based
+//!   inclusive lines as an editor shows them
+
+use serde_json::{json, Value};
+use syn::spanned::Spanned;
+
+use super::language::SourceLanguage;
+use super::rust::{describe_item, impl_name, type_name};
+use super::semantic::ItemKind;
+use super::structural::language_units;
+use super::Chunk;
+
+/// One defined item: function, type, trait, impl, const, ...
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    /// Path-qualified name, e.g. `Point::new`
+    pub name: String,
+    pub kind: ItemKind,
+    pub line_start: usize,
+    pub line_end: usize,
+}
+
+impl Symbol {
+    pub fn new(name: impl Into<String>, kind: ItemKind, line_start: usize, line_end: usize) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            line_start,
+            line_end,
+        }
+    }
+
+    /// Whether `query` names this symbol: the full path, or any `::`-suffix of it
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim();
+        !query.is_empty()
+            && (self.name == query || self.name.ends_with(&format!("::{}", query)))
+    }
+
+    /// `{"name", "kind", "line_start", "line_end"}` with 1-based inclusive lines
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "kind": self.kind.as_str(),
+            "line_start": self.line_start + 1,
+            "line_end": self.line_end,
+        })
+    }
+
+    /// Inverse of `to_json`
+    pub fn from_json(value: &Value) -> Option<Self> {
+        let line_start = value.get("line_start")?.as_u64()? as usize;
+        Some(Self {
+            name: value.get("name")?.as_str()?.to_string(),
+            kind: ItemKind::from_name(value.get("kind")?.as_str()?),
+            line_start: line_start.saturating_sub(1),
+            line_end: value.get("line_end")?.as_u64()? as usize,
+        })
+    }
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.kind.as_str(), self.name)
+    }
+}
+
+/// Symbols defined in `source`, ordered by first line
+pub fn extract_symbols(source: &str, language: SourceLanguage) -> Vec<Symbol> {
+    let mut symbols = match language {
+        SourceLanguage::Rust => match syn::parse_file(source) {
+            Ok(file) => {
+                let mut symbols = Vec::new();
+                rust_symbols(&file.items, "", &mut symbols);
+                symbols
+            }
+            Err(_) => Vec::new(),
+        },
+        _ => language_units(source, language)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|unit| !matches!(unit.kind, ItemKind::Use | ItemKind::Other))
+            .filter_map(|unit| Some(Symbol::new(unit.name?, unit.kind, unit.line_start, unit.line_end)))
+            .collect(),
+    };
+    symbols.sort_by_key(|symbol| symbol.line_start);
+    symbols
+}
+
+/// Give every chunk the symbols whose first line it owns
+pub fn attach_symbols(chunks: &mut [Chunk], symbols: &[Symbol]) {
+    for chunk in chunks.iter_mut() {
+        let owned = chunk.owned_line_start()..chunk.line_end;
+        chunk.symbols = symbols
+            .iter()
+            .filter(|symbol| owned.contains(&symbol.line_start))
+            .cloned()
+            .collect();
+    }
+}
+
+fn rust_symbols(items: &[syn::Item], prefix: &str, symbols: &mut Vec<Symbol>) {
+    for item in items {
+        let (kind, name) = describe_item(item);
+        let Some(name) = name.filter(|_| !matches!(kind, ItemKind::Use | ItemKind::Other)) else {
+            continue;
+        };
+        let (line_start, line_end) = lines_of(item_ident_span(item).unwrap_or_else(|| item.span()), item.span());
+        symbols.push(Symbol::new(format!("{}{}", prefix, name), kind, line_start, line_end));
+
+        match item {
+            syn::Item::Impl(item) => {
+                // Trait impls keep the trait in the path: `Display for Point::fmt`
+                let owner = match &item.trait_ {
+                    Some(_) => impl_name(item),
+                    None => type_name(&item.self_ty),
+                };
+                for associated in &item.items {
+                    let (kind, ident) = match associated {
+                        syn::ImplItem::Fn(f) => (ItemKind::Function, &f.sig.ident),
+                        syn::ImplItem::Const(c) => (ItemKind::Const, &c.ident),
+                        syn::ImplItem::Type(t) => (ItemKind::TypeAlias, &t.ident),
+                        _ => continue,
+                    };
+                    let (line_start, line_end) = lines_of(ident.span(), associated.span());
+                    symbols.push(Symbol::new(format!("{}{}::{}", prefix, owner, ident), kind, line_start, line_end));
+                }
+            }
+            syn::Item::Trait(item) => {
+                for associated in &item.items {
+                    let (kind, ident) = match associated {
+                        syn::TraitItem::Fn(f) => (ItemKind::Function, &f.sig.ident),
+                        syn::TraitItem::Const(c) => (ItemKind::Const, &c.ident),
+                        syn::TraitItem::Type(t) => (ItemKind::TypeAlias, &t.ident),
+                        _ => continue,
+                    };
+                    let (line_start, line_end) = lines_of(ident.span(), associated.span());
+                    symbols.push(Symbol::new(format!("{}{}::{}", prefix, item.ident, ident), kind, line_start, line_end));
+                }
+            }
+            syn::Item::Mod(module) => {
+                if let Some((_, items)) = &module.content {
+                    rust_symbols(items, &format!("{}{}::", prefix, module.ident), symbols);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Span of the item's name, so symbol lines skip doc comments and attributes
+fn item_ident_span(item: &syn::Item) -> Option<proc_macro2::Span> {
+    match item {
+        syn::Item::Fn(item) => Some(item.sig.ident.span()),
+        syn::Item::Struct(item) => Some(item.ident.span()),
+        syn::Item::Enum(item) => Some(item.ident.span()),
+        syn::Item::Union(item) => Some(item.ident.span()),
+        syn::Item::Trait(item) => Some(item.ident.span()),
+        syn::Item::Mod(item) => Some(item.ident.span()),
+        syn::Item::Const(item) => Some(item.ident.span()),
+        syn::Item::Static(item) => Some(item.ident.span()),
+        syn::Item::Type(item) => Some(item.ident.span()),
+        syn::Item::Impl(item) => Some(item.impl_token.span),
+        _ => None,
+    }
+}
+
+/// 0-based start line of `name`, end-exclusive end line of `whole`
+fn lines_of(name: proc_macro2::Span, whole: proc_macro2::Span) -> (usize, usize) {
+    // syn lines are 1-based inclusive
+    (name.start().line.saturating_sub(1), whole.end().line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunking::{Chunker, StructuralChunker};
+
+    const SOURCE: &str = r#"use std::fmt;
+
+/// A point
+pub struct Point {
+    x: i32,
+}
+
+impl Point {
+    pub const ORIGIN: i32 = 0;
+
+    pub fn new(x: i32) -> Self {
+        Self { x }
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.x)
+    }
+}
+
+mod tests {
+    fn helper() {}
+}
+"#;
+
+    #[test]
+    fn test_extracts_nested_rust_symbols_with_kinds_and_lines() {
+        let symbols = extract_symbols(SOURCE, SourceLanguage::Rust);
+        let named: Vec<(&str, &str)> = symbols.iter().map(|s| (s.name.as_str(), s.kind.as_str())).collect();
+        assert_eq!(
+            named,
+            vec![
+                ("Point", "struct"),
+                ("Point", "impl"),
+                ("Point::ORIGIN", "const"),
+                ("Point::new", "fn"),
+                ("Display for Point", "impl"),
+                ("Display for Point::fmt", "fn"),
+                ("tests", "mod"),
+                ("tests::helper", "fn"),
+            ]
+        );
+
+        let new = symbols.iter().find(|s| s.matches("Point::new")).unwrap();
+        assert_eq!((new.line_start, new.line_end), (10, 13));
+        assert_eq!(Symbol::from_json(&new.to_json()).as_ref(), Some(new));
+        assert!(symbols.iter().any(|s| s.matches("fmt")));
+
+        let python = extract_symbols("import os\n\nclass A:\n    pass\n\ndef run():\n    pass\n", SourceLanguage::Python);
+        let named: Vec<String> = python.iter().map(Symbol::to_string).collect();
+        assert_eq!(named, vec!["class A", "fn run"]);
+    }
+
+    #[test]
+    fn test_chunks_carry_the_symbols_they_define() {
+        let chunks = StructuralChunker::new(8)
+            .with_overlap(2)
+            .chunk(SOURCE, SourceLanguage::Rust, Some("src/point.rs"))
+            .unwrap();
+
+        let owners: Vec<usize> = ["Point::new", "Display for Point::fmt", "tests::helper"]
+            .iter()
+            .map(|name| {
+                let holders: Vec<usize> = (0..chunks.len()).filter(|&i| chunks[i].defines(name)).collect();
+                assert_eq!(holders.len(), 1, "{} listed in {:?}", name, holders);
+                holders[0]
+            })
+            .collect();
+        assert!(owners.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(chunks.iter().map(|chunk| chunk.symbols.len()).sum::<usize>(), 8);
+    }
+}
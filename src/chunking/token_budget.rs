@@ -0,0 +1,381 @@
+//! Token-budget chunking with the model's real tokenizer
+//!
+//! Contracts:
+//! - Chunk size is measured in tokens of the loaded `tokenizers::Tokenizer`
+//! - Budget = context window - prompt tokens - max_new_tokens - template reserve
+//! - With templates, prompt tokens are counted per file on the prompt rendered
+//!   without the chunk, filled with the file's path and every symbol it defines,
+//!   so no chunk's expansions can push it past the window
+//! - A prompt's input is its chunk-less rendering plus the chunk, whether the
+//!   template embeds `{{chunk}}` or the chunk is sent alongside
+//! - Every chunk fits the budget by construction (verified after packing)
+//! - Structural unit boundaries are kept for the configured language
+//! - Optional token overlap is carved out of the same budget
+
+use std::sync::Arc;
+
+use log::warn;
+use tokenizers::Tokenizer;
+
+use super::chunker::{with_metadata, Chunker};
+use super::language::SourceLanguage;
+use super::overlap::apply_overlap;
+use super::structural::chunk_structured;
+use super::symbols::extract_symbols;
+use super::Chunk;
+use crate::errors::{ProcessingError, Result};
+use crate::prompt::PromptTemplate;
+
+/// Tokens held back for chat-template markers around prompt and chunk
+pub const PROMPT_TEMPLATE_RESERVE_TOKENS: usize = 32;
+
+/// Chunker that sizes chunks in real model tokens
+pub struct TokenBudgetChunker {
+    tokenizer: Arc<Tokenizer>,
+    max_chunk_tokens: usize,
+    overlap_tokens: usize,
+    language: SourceLanguage,
+    /// Templates and their max_new_tokens, re-budgeted per file when set
+    prompts: Vec<(PromptTemplate, usize)>,
+    context_window: usize,
+}
+
+impl TokenBudgetChunker {
+    pub fn new(tokenizer: Arc<Tokenizer>, max_chunk_tokens: usize) -> Self {
+        Self {
+            tokenizer,
+            max_chunk_tokens: max_chunk_tokens.max(1),
+            overlap_tokens: 0,
+            language: SourceLanguage::Rust,
+            prompts: Vec::new(),
+            context_window: 0,
+        }
+    }
+
+    /// Language whose unit boundaries chunks should follow (default: Rust,
+    /// which falls back to lines when the source does not parse)
+    pub fn with_language(mut self, language: SourceLanguage) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Repeat up to `overlap_tokens` of preceding lines at the start of each chunk
+    ///
+    /// The overlap is reserved inside `max_chunk_tokens` (capped at half of it),
+    /// so overlapped chunks still fit the context window.
+    pub fn with_overlap_tokens(mut self, overlap_tokens: usize) -> Self {
+        self.overlap_tokens = overlap_tokens.min(self.max_chunk_tokens / 2);
+        self
+    }
+
+    /// Chunker whose budget leaves room for `prompt` and `max_new_tokens`
+    ///
+    /// # Errors
+    /// * `ProcessingError::ContractViolation` - prompt and generation leave no room for code
+    pub fn for_context(
+        tokenizer: Arc<Tokenizer>,
+        context_window: usize,
+        prompt: &str,
+        max_new_tokens: usize,
+    ) -> Result<Self> {
+        let prompt_tokens = count_tokens(&tokenizer, prompt)?;
+        let budget = budget_after(context_window, prompt_tokens, max_new_tokens, None)?;
+        Ok(Self::new(tokenizer, budget))
+    }
+
+    /// Chunker whose budget leaves room for each of `prompts` (template and
+    /// its max_new_tokens) rendered around a chunk
+    ///
+    /// Each file is budgeted on its own: the templates are rendered without the
+    /// chunk but with the file's path and all of its symbols, which bound what
+    /// `{{file_path}}` and `{{symbols}}` expand to for any chunk of the file.
+    /// `max_chunk_tokens` is the budget before those expansions.
+    ///
+    /// # Errors
+    /// * `ProcessingError::ContractViolation` - a prompt and its generation leave no room for code
+    pub fn for_prompts(
+        tokenizer: Arc<Tokenizer>,
+        context_window: usize,
+        prompts: &[(&PromptTemplate, usize)],
+    ) -> Result<Self> {
+        let mut chunker = Self::new(tokenizer, context_window);
+        chunker.context_window = context_window;
+        chunker.prompts = prompts.iter().map(|(template, max_new_tokens)| ((*template).clone(), *max_new_tokens)).collect();
+        chunker.max_chunk_tokens = chunker.budget_around(&Chunk::new(0, 0, String::new()))?.max(1);
+        Ok(chunker)
+    }
+
+    /// Tokens left for code once every prompt, rendered around `frame`, and its generation fit
+    fn budget_around(&self, frame: &Chunk) -> Result<usize> {
+        self.prompts.iter().try_fold(self.context_window, |budget, (template, max_new_tokens)| {
+            let prompt_tokens = count_tokens(&self.tokenizer, &template.render_frame(frame))?;
+            let file_budget = budget_after(self.context_window, prompt_tokens, *max_new_tokens, frame.file_path.as_deref())?;
+            Ok(budget.min(file_budget))
+        })
+    }
+
+    pub fn max_chunk_tokens(&self) -> usize {
+        self.max_chunk_tokens
+    }
+
+    /// Token count of `text`, falling back to a 4 chars/token estimate on tokenizer errors
+    pub fn count_tokens(&self, text: &str) -> usize {
+        count_tokens(&self.tokenizer, text).unwrap_or_else(|e| {
+            warn!("Token counting failed, using estimate: {}", e);
+            text.len().div_ceil(4)
+        })
+    }
+
+    /// Chunk source so that every chunk fits `max_chunk_tokens`
+    pub fn chunk_source(&self, source: &str) -> Vec<Chunk> {
+        self.chunk_source_as(source, self.language)
+    }
+
+    /// Chunk source of a specific language (e.g. one file of a repository dump)
+    pub fn chunk_source_as(&self, source: &str, language: SourceLanguage) -> Vec<Chunk> {
+        // Per-line counts (plus the newline) approximate the joined text closely;
+        // the exact check below catches merges across line breaks.
+        let line_tokens = |line: &str| self.count_tokens(line) + 1;
+        let owned_budget = self.max_chunk_tokens - self.overlap_tokens;
+        let packed = chunk_structured(source, language, owned_budget, line_tokens);
+
+        let chunks = packed
+            .into_iter()
+            .flat_map(|chunk| self.enforce_budget(chunk, owned_budget))
+            .collect();
+
+        let lines: Vec<&str> = source.lines().collect();
+        apply_overlap(&lines, chunks, self.overlap_tokens, line_tokens)
+    }
+
+    /// Re-split a chunk whose exact token count exceeds the budget
+    fn enforce_budget(&self, chunk: Chunk, budget: usize) -> Vec<Chunk> {
+        if self.count_tokens(&chunk.content) <= budget {
+            return vec![chunk];
+        }
+
+        let lines: Vec<&str> = chunk.content.lines().collect();
+        let mut chunks = Vec::new();
+        let mut window_start = 0;
+
+        for end in 1..=lines.len() {
+            let window = lines[window_start..end].join("\n");
+            if self.count_tokens(&window) <= budget {
+                continue;
+            }
+
+            if end - 1 > window_start {
+                chunks.push(offset_chunk(&chunk, &lines, window_start, end - 1));
+                window_start = end - 1;
+            }
+
+            // A single line over budget is cut at token boundaries
+            if self.count_tokens(lines[window_start]) > budget {
+                chunks.extend(self.split_long_line(&chunk, lines[window_start], window_start, budget));
+                window_start = end;
+            }
+        }
+
+        if window_start < lines.len() {
+            chunks.push(offset_chunk(&chunk, &lines, window_start, lines.len()));
+        }
+
+        chunks
+    }
+
+    /// Cut one over-budget line at token boundaries
+    ///
+    /// The first piece owns the line; the rest are continuations owning no
+    /// lines, so line counts and anchors don't repeat it.
+    fn split_long_line(&self, parent: &Chunk, line: &str, line_index: usize, budget: usize) -> Vec<Chunk> {
+        let line_number = parent.line_start + line_index;
+        let offsets = match self.tokenizer.encode(line, false) {
+            Ok(encoding) => encoding.get_offsets().to_vec(),
+            Err(_) => Vec::new(),
+        };
+
+        let mut cuts: Vec<usize> = offsets
+            .iter()
+            .step_by(budget)
+            .skip(1)
+            .map(|(start, _)| *start)
+            .filter(|cut| line.is_char_boundary(*cut))
+            .collect();
+        cuts.push(line.len());
+
+        let mut pieces = Vec::new();
+        let mut from = 0;
+        for cut in cuts {
+            if cut > from {
+                let owned_start = if pieces.is_empty() { line_number } else { line_number + 1 };
+                pieces.push(
+                    Chunk::new(owned_start, line_number + 1, line[from..cut].to_string())
+                        .with_symbol(parent.symbol.clone()),
+                );
+                from = cut;
+            }
+        }
+        pieces
+    }
+}
+
+impl Chunker for TokenBudgetChunker {
+    fn name(&self) -> &str {
+        "tokens"
+    }
+
+    fn split(&self, source: &str, language: SourceLanguage) -> Result<Vec<Chunk>> {
+        Ok(self.chunk_source_as(source, language))
+    }
+
+    fn chunk(&self, source: &str, language: SourceLanguage, file_path: Option<&str>) -> Result<Vec<Chunk>> {
+        if self.prompts.is_empty() {
+            return Ok(with_metadata(self.split(source, language)?, source, language, file_path));
+        }
+        // The whole file's path, line count and symbols stand in for any one chunk's
+        let mut frame = Chunk::new(0, source.lines().count(), String::new()).with_language(language);
+        if let Some(path) = file_path {
+            frame = frame.with_file_path(path);
+        }
+        frame.symbols = extract_symbols(source, language);
+
+        let file_chunker = Self::new(Arc::clone(&self.tokenizer), self.budget_around(&frame)?)
+            .with_overlap_tokens(self.overlap_tokens);
+        Ok(with_metadata(file_chunker.split(source, language)?, source, language, file_path))
+    }
+}
+
+/// Tokens the model reads for `chunk` under `template`: the prompt rendered
+/// without the chunk, plus the chunk itself
+///
+/// Falls back to a 4 chars/token estimate on tokenizer errors.
+pub fn prompt_input_tokens(tokenizer: &Tokenizer, template: &PromptTemplate, chunk: &Chunk) -> usize {
+    let count = |text: &str| count_tokens(tokenizer, text).unwrap_or_else(|_| text.len().div_ceil(4));
+    count(&template.render_frame(chunk)) + count(&chunk.content)
+}
+
+/// Context left for code after a prompt, its generation and the template reserve
+fn budget_after(context_window: usize, prompt_tokens: usize, max_new_tokens: usize, file_path: Option<&str>) -> Result<usize> {
+    let reserved = prompt_tokens + max_new_tokens + PROMPT_TEMPLATE_RESERVE_TOKENS;
+    if reserved >= context_window {
+        return Err(ProcessingError::ContractViolation {
+            contract_name: "token_budget".to_string(),
+            violation: format!(
+                "prompt ({} tokens{}) + max_new_tokens ({}) + reserve ({}) leave no room in a {}-token context",
+                prompt_tokens,
+                file_path.map(|path| format!(" for {}", path)).unwrap_or_default(),
+                max_new_tokens,
+                PROMPT_TEMPLATE_RESERVE_TOKENS,
+                context_window
+            ),
+        });
+    }
+    Ok(context_window - reserved)
+}
+
+fn count_tokens(tokenizer: &Tokenizer, text: &str) -> Result<usize> {
+    tokenizer
+        .encode(text, false)
+        .map(|encoding| encoding.len())
+        .map_err(|e| ProcessingError::TokenizationError(e.to_string()))
+}
+
+fn offset_chunk(parent: &Chunk, lines: &[&str], start: usize, end: usize) -> Chunk {
+    Chunk::new(
+        parent.line_start + start,
+        parent.line_start + end,
+        lines[start..end].join("\n"),
+    )
+    .with_symbol(parent.symbol.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tokenizers::models::wordlevel::WordLevel;
+    use tokenizers::pre_tokenizers::whitespace::Whitespace;
+
+    /// Word-level tokenizer: one token per whitespace-separated word or symbol
+    fn word_tokenizer() -> Arc<Tokenizer> {
+        let vocab: HashMap<String, u32> = HashMap::from([("[UNK]".to_string(), 0)]);
+        let model = WordLevel::builder()
+            .vocab(vocab)
+            .unk_token("[UNK]".to_string())
+            .build()
+            .unwrap();
+        let mut tokenizer = Tokenizer::new(model);
+        tokenizer.with_pre_tokenizer(Whitespace {});
+        Arc::new(tokenizer)
+    }
+
+    #[test]
+    fn test_budget_leaves_room_for_prompt_and_generation() {
+        let chunker = TokenBudgetChunker::for_context(word_tokenizer(), 200, "summarize this code", 60).unwrap();
+        assert_eq!(chunker.max_chunk_tokens(), 200 - 3 - 60 - PROMPT_TEMPLATE_RESERVE_TOKENS);
+
+        assert!(TokenBudgetChunker::for_context(word_tokenizer(), 64, "summarize", 60).is_err());
+    }
+
+    #[test]
+    fn test_budget_counts_symbol_expansions_of_the_rendered_prompt() {
+        let source: String = (0..30).map(|i| format!("fn handler_{} ( ) {{ }}\n", i)).collect();
+        let template = PromptTemplate::parse("Summarize {{file_path}} which defines {{symbols}} :").unwrap();
+        let fits = |chunk: &Chunk| prompt_input_tokens(&word_tokenizer(), &template, chunk) + 40 + PROMPT_TEMPLATE_RESERVE_TOKENS <= 200;
+
+        // Budgeted on the template as written, the symbol list pushes prompts past the window
+        let raw = TokenBudgetChunker::for_context(word_tokenizer(), 200, template.source(), 40).unwrap();
+        let raw_chunks = raw.chunk(&source, SourceLanguage::Rust, Some("src/handlers.rs")).unwrap();
+        assert!(!raw_chunks.iter().all(fits));
+
+        let chunker = TokenBudgetChunker::for_prompts(word_tokenizer(), 200, &[(&template, 40)]).unwrap();
+        let chunks = chunker.chunk(&source, SourceLanguage::Rust, Some("src/handlers.rs")).unwrap();
+        assert!(chunks.len() > raw_chunks.len());
+        assert!(chunks.iter().all(fits));
+        assert_eq!(crate::chunking::overlap::owned_line_count(&chunks), 30);
+
+        let crowded = PromptTemplate::parse("{{symbols}} {{symbols}} {{symbols}} {{symbols}} {{chunk}}").unwrap();
+        let overflow = TokenBudgetChunker::for_prompts(word_tokenizer(), 200, &[(&crowded, 40)]).unwrap();
+        let error = overflow.chunk(&source, SourceLanguage::Rust, Some("src/handlers.rs")).unwrap_err();
+        assert!(error.to_string().contains("src/handlers.rs"));
+    }
+
+    #[test]
+    fn test_every_chunk_fits_the_token_budget() {
+        let source = "alpha beta gamma delta\n".repeat(40) + &"word ".repeat(50);
+        let chunker = TokenBudgetChunker::new(word_tokenizer(), 12);
+
+        let chunks = chunker.chunk_source(&source);
+
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunker.count_tokens(&chunk.content) <= 12));
+        let words: usize = chunks.iter().map(|chunk| chunker.count_tokens(&chunk.content)).sum();
+        assert_eq!(words, 40 * 4 + 50);
+    }
+
+    #[test]
+    fn test_long_line_pieces_own_the_line_once() {
+        let source = "alpha beta\n".to_string() + &"word ".repeat(30) + "\ngamma delta";
+        let chunker = TokenBudgetChunker::new(word_tokenizer(), 12);
+
+        let chunks = chunker.chunk_source(&source);
+
+        let pieces: Vec<&Chunk> = chunks.iter().filter(|chunk| chunk.content.starts_with("word")).collect();
+        assert!(pieces.len() > 1);
+        assert_eq!((pieces[0].line_start, pieces[0].line_end), (1, 2));
+        assert!(pieces[1..].iter().all(|piece| piece.line_count == 0 && piece.line_start == 2));
+        assert_eq!(crate::chunking::overlap::owned_line_count(&chunks), 3);
+    }
+
+    #[test]
+    fn test_overlap_is_reserved_inside_the_budget() {
+        let source = "alpha beta gamma delta\n".repeat(40);
+        let chunker = TokenBudgetChunker::new(word_tokenizer(), 12).with_overlap_tokens(5);
+
+        let chunks = chunker.chunk_source(&source);
+
+        assert!(chunks[1..].iter().all(|chunk| chunk.overlap_lines == 1));
+        assert!(chunks.iter().all(|chunk| chunker.count_tokens(&chunk.content) <= 12));
+        assert_eq!(crate::chunking::overlap::owned_line_count(&chunks), 40);
+    }
+}
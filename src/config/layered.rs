@@ -0,0 +1,526 @@
+//! Layered settings: defaults, a TOML file, a named profile, `DOBBY_*` variables, then flags
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::ValueEnum;
+use toml::{Table, Value};
+
+use super::{ChunkingMode, GenerationConfig, ModelConfig, OutputFormat, SamplingStrategy};
+use crate::errors::{ProcessingError, Result};
+
+/// Read when neither `--config` nor `DOBBY_CONFIG` names a file, if it exists
+pub const DEFAULT_CONFIG_FILE: &str = "dobby.toml";
+
+/// `generation.temperature` is set by `DOBBY_GENERATION_TEMPERATURE`
+pub const ENV_PREFIX: &str = "DOBBY_";
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Text,
+    Choice(&'static [&'static str]),
+    Integer,
+    Float,
+    Bool,
+    List,
+}
+
+/// Every setting and its type; file sections are the part before the dot
+const SETTINGS: &[(&str, Kind)] = &[
+    ("model.name", Kind::Text),
+    ("model.path", Kind::Text),
+    ("model.tokenizer_dir", Kind::Text),
+    ("generation.sampling_strategy", Kind::Choice(&["sampling", "beam"])),
+    ("generation.temperature", Kind::Float),
+    ("generation.top_p", Kind::Float),
+    ("generation.top_k", Kind::Integer),
+    ("generation.num_beams", Kind::Integer),
+    ("generation.length_penalty", Kind::Float),
+    ("generation.early_stopping", Kind::Bool),
+    ("generation.max_new_tokens", Kind::Integer),
+    ("generation.min_length", Kind::Integer),
+    ("generation.repetition_penalty", Kind::Float),
+    ("generation.no_repeat_ngram_size", Kind::Integer),
+    ("generation.stop_sequences", Kind::List),
+    ("run.agent_count", Kind::Integer),
+    ("run.max_concurrent", Kind::Integer),
+    ("run.max_chunk_time_s", Kind::Integer),
+    ("run.max_job_time_s", Kind::Integer),
+    ("chunking.loc", Kind::Integer),
+    ("chunking.mode", Kind::Choice(&["lines", "semantic", "tokens"])),
+    ("chunking.overlap", Kind::Integer),
+    ("output.format", Kind::Choice(&["text", "markdown", "json", "jsonl", "html"])),
+];
+
+/// Values used when no layer sets them; settings missing here have no default
+const DEFAULTS: &str = r#"
+[generation]
+sampling_strategy = "sampling"
+temperature = 0.35
+top_p = 0.85
+top_k = 40
+num_beams = 3
+length_penalty = 1.05
+early_stopping = false
+max_new_tokens = 60
+min_length = 35
+repetition_penalty = 1.15
+no_repeat_ngram_size = 3
+stop_sequences = []
+
+[run]
+max_chunk_time_s = 2
+max_job_time_s = 300
+
+[chunking]
+mode = "lines"
+overlap = 0
+"#;
+
+/// Profiles any config file can select; a file's `[profiles.<name>]` extends them
+const BUILTIN_PROFILES: &str = r#"
+[fast.generation]
+sampling_strategy = "sampling"
+temperature = 0.2
+max_new_tokens = 40
+min_length = 10
+
+[fast.run]
+max_chunk_time_s = 1
+
+[quality.generation]
+sampling_strategy = "beam"
+num_beams = 5
+max_new_tokens = 120
+min_length = 40
+repetition_penalty = 1.2
+
+[quality.run]
+max_chunk_time_s = 10
+"#;
+
+/// The layer an effective value came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigSource {
+    Default,
+    File(PathBuf),
+    Profile(String),
+    /// The environment variable that set it
+    Env(String),
+    Flag,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::File(path) => write!(f, "file {}", path.display()),
+            Self::Profile(name) => write!(f, "profile {}", name),
+            Self::Env(var) => write!(f, "env {}", var),
+            Self::Flag => write!(f, "flag"),
+        }
+    }
+}
+
+/// Effective settings, each with the layer that set it
+///
+/// Later layers win: defaults, the config file, the selected profile,
+/// `DOBBY_*` environment variables, then `set` calls for command-line flags.
+#[derive(Debug, Clone)]
+pub struct LayeredConfig {
+    values: BTreeMap<&'static str, (Value, ConfigSource)>,
+    file: Option<PathBuf>,
+    profile: Option<String>,
+}
+
+impl LayeredConfig {
+    /// Built-in defaults only
+    pub fn defaults() -> Self {
+        let mut config = Self {
+            values: BTreeMap::new(),
+            file: None,
+            profile: None,
+        };
+        let defaults: Table = DEFAULTS.parse().expect("built-in defaults are valid TOML");
+        config
+            .apply_sections(&defaults, &ConfigSource::Default, "defaults")
+            .expect("built-in defaults are valid settings");
+        config
+    }
+
+    /// Layer a config file, a profile and `DOBBY_*` variables from `env` over the defaults
+    ///
+    /// The file is `file`, else `DOBBY_CONFIG`, else `dobby.toml` when present.
+    /// The profile is `profile`, else `DOBBY_PROFILE`, else the file's top-level
+    /// `profile` key.
+    ///
+    /// # Errors
+    /// * `ProcessingError::Io` - a named config file cannot be read
+    /// * `ProcessingError::InvalidConfig` - malformed TOML, unknown keys or profiles, or mistyped values
+    pub fn load(
+        file: Option<&Path>,
+        profile: Option<&str>,
+        env: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self> {
+        let env: BTreeMap<String, String> = env.into_iter().filter(|(name, _)| name.starts_with(ENV_PREFIX)).collect();
+        let mut config = Self::defaults();
+
+        let file = match file.map(Path::to_path_buf).or_else(|| env.get("DOBBY_CONFIG").map(PathBuf::from)) {
+            Some(path) => Some(path),
+            None => Some(PathBuf::from(DEFAULT_CONFIG_FILE)).filter(|path| path.is_file()),
+        };
+        let mut table = Table::new();
+        if let Some(path) = &file {
+            table = std::fs::read_to_string(path)?
+                .parse()
+                .map_err(|e: toml::de::Error| invalid(path.display(), e.message()))?;
+        }
+        let origin = file.as_ref().map(|path| path.display().to_string()).unwrap_or_default();
+
+        let file_profiles = match table.remove("profiles") {
+            Some(Value::Table(profiles)) => profiles,
+            Some(_) => return Err(invalid(&origin, "\"profiles\" must be a table of profiles")),
+            None => Table::new(),
+        };
+        let file_profile = match table.remove("profile") {
+            Some(Value::String(name)) => Some(name),
+            Some(_) => return Err(invalid(&origin, "\"profile\" must be a profile name")),
+            None => None,
+        };
+        if let Some(path) = &file {
+            config.apply_sections(&table, &ConfigSource::File(path.clone()), &origin)?;
+        }
+        config.file = file;
+
+        let profile = profile.map(str::to_string).or_else(|| env.get("DOBBY_PROFILE").cloned()).or(file_profile);
+        if let Some(name) = profile {
+            config.apply_profile(&name, &file_profiles, &origin)?;
+            config.profile = Some(name);
+        }
+
+        for (var, raw) in &env {
+            if var == "DOBBY_CONFIG" || var == "DOBBY_PROFILE" {
+                continue;
+            }
+            match SETTINGS.iter().find(|(key, _)| env_var(key) == *var) {
+                Some(&(key, kind)) => {
+                    let value = parse_env(kind, raw).map_err(|reason| invalid(var, reason))?;
+                    config.set(key, value, ConfigSource::Env(var.clone()))?;
+                }
+                None => log::warn!("⚠️ Ignoring unknown setting variable {}", var),
+            }
+        }
+        Ok(config)
+    }
+
+    /// Set `key` from `source`, e.g. a command-line flag
+    ///
+    /// # Errors
+    /// * `ProcessingError::InvalidConfig` - unknown key or value of the wrong type
+    pub fn set(&mut self, key: &str, value: Value, source: ConfigSource) -> Result<()> {
+        let Some(&(key, kind)) = SETTINGS.iter().find(|(name, _)| *name == key) else {
+            return Err(invalid(&source, format!("unknown setting \"{}\"", key)));
+        };
+        let value = check_kind(kind, value).map_err(|reason| invalid(&source, format!("{}: {}", key, reason)))?;
+        self.values.insert(key, (value, source));
+        Ok(())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key).map(|(value, _)| value)
+    }
+
+    pub fn source(&self, key: &str) -> Option<&ConfigSource> {
+        self.values.get(key).map(|(_, source)| source)
+    }
+
+    pub fn text(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Value::as_str)
+    }
+
+    pub fn usize(&self, key: &str) -> Option<usize> {
+        self.get(key).and_then(Value::as_integer).map(|value| value as usize)
+    }
+
+    pub fn float(&self, key: &str) -> Option<f32> {
+        self.get(key).and_then(Value::as_float).map(|value| value as f32)
+    }
+
+    /// The config file that was read, if any
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_deref()
+    }
+
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Generation settings; every `generation.*` key has a default
+    pub fn generation_config(&self) -> GenerationConfig {
+        let count = |key: &str| self.usize(key).unwrap_or_default();
+        let float = |key: &str| self.float(key).unwrap_or_default();
+        GenerationConfig {
+            strategy: choice(self.text("generation.sampling_strategy")).unwrap_or(SamplingStrategy::Sampling),
+            temperature: float("generation.temperature"),
+            top_p: float("generation.top_p"),
+            top_k: count("generation.top_k"),
+            num_beams: count("generation.num_beams"),
+            length_penalty: float("generation.length_penalty"),
+            early_stopping: self.get("generation.early_stopping").and_then(Value::as_bool).unwrap_or(false),
+            max_new_tokens: count("generation.max_new_tokens"),
+            min_length: count("generation.min_length"),
+            repetition_penalty: float("generation.repetition_penalty"),
+            no_repeat_ngram_size: count("generation.no_repeat_ngram_size"),
+            stop_sequences: self
+                .get("generation.stop_sequences")
+                .and_then(Value::as_array)
+                .map(|items| items.iter().filter_map(Value::as_str).map(str::to_string).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The model named by `model.name`, if any
+    pub fn model_config(&self) -> Option<ModelConfig> {
+        let name = self.text("model.name")?;
+        Some(ModelConfig::from_name(
+            name,
+            self.text("model.path").map(PathBuf::from),
+            self.text("model.tokenizer_dir").map(PathBuf::from),
+        ))
+    }
+
+    /// Per-chunk generation deadline from `run.max_chunk_time_s`
+    pub fn chunk_time_limit(&self) -> Duration {
+        Duration::from_secs(self.usize("run.max_chunk_time_s").unwrap_or_default() as u64)
+    }
+
+    /// Whole-job deadline for the layer 1 pipeline from `run.max_job_time_s`
+    pub fn job_time_limit(&self) -> Duration {
+        Duration::from_secs(self.usize("run.max_job_time_s").unwrap_or_default() as u64)
+    }
+
+    pub fn chunking_mode(&self) -> ChunkingMode {
+        choice(self.text("chunking.mode")).unwrap_or(ChunkingMode::Lines)
+    }
+
+    /// The `output.format` asked for; unset lets the command pick by its other options
+    pub fn output_format(&self) -> Option<OutputFormat> {
+        choice(self.text("output.format"))
+    }
+
+    /// One `key = value  # source` line per setting, unset ones included
+    pub fn render(&self) -> String {
+        let width = SETTINGS.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+        let mut lines = vec![
+            format!("# config file: {}", self.file.as_ref().map(|path| path.display().to_string()).unwrap_or_else(|| "none".to_string())),
+            format!("# profile: {}", self.profile.as_deref().unwrap_or("none")),
+        ];
+        for (key, _) in SETTINGS {
+            lines.push(match self.values.get(key) {
+                Some((value, source)) => format!("{:<width$} = {:<24} # {}", key, value.to_string(), source, width = width),
+                None => format!("{:<width$} = {:<24} # unset", key, "", width = width),
+            });
+        }
+        lines.join("\n")
+    }
+
+    /// Apply `[section] key = value` tables, rejecting unknown sections and keys
+    fn apply_sections(&mut self, table: &Table, source: &ConfigSource, origin: &str) -> Result<()> {
+        for (section, entries) in table {
+            let Value::Table(entries) = entries else {
+                return Err(invalid(origin, format!("\"{}\" must be a [section] of settings", section)));
+            };
+            for (name, value) in entries {
+                let key = format!("{}.{}", section, name);
+                if !SETTINGS.iter().any(|(known, _)| *known == key) {
+                    return Err(invalid(origin, format!("unknown setting \"{}\"", key)));
+                }
+                self.set(&key, value.clone(), source.clone())
+                    .map_err(|_| invalid(origin, format!("{}: expected {}", key, describe(kind_of(&key)))))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Built-in profile `name`, then the file's `[profiles.<name>]` over it
+    fn apply_profile(&mut self, name: &str, file_profiles: &Table, origin: &str) -> Result<()> {
+        let builtin: Table = BUILTIN_PROFILES.parse().expect("built-in profiles are valid TOML");
+        let source = ConfigSource::Profile(name.to_string());
+        let mut found = false;
+        if let Some(Value::Table(profile)) = builtin.get(name) {
+            self.apply_sections(profile, &source, "built-in profiles")?;
+            found = true;
+        }
+        match file_profiles.get(name) {
+            Some(Value::Table(profile)) => {
+                self.apply_sections(profile, &source, origin)?;
+                found = true;
+            }
+            Some(_) => return Err(invalid(origin, format!("profile \"{}\" must be a table", name))),
+            None => {}
+        }
+        if !found {
+            let mut known: Vec<&str> = builtin.keys().chain(file_profiles.keys()).map(String::as_str).collect();
+            known.sort_unstable();
+            known.dedup();
+            return Err(invalid(origin, format!("unknown profile \"{}\" (known: {})", name, known.join(", "))));
+        }
+        Ok(())
+    }
+}
+
+impl Default for LayeredConfig {
+    fn default() -> Self {
+        Self::defaults()
+    }
+}
+
+/// `generation.top_k` -> `DOBBY_GENERATION_TOP_K`
+pub fn env_var(key: &str) -> String {
+    format!("{}{}", ENV_PREFIX, key.replace('.', "_").to_uppercase())
+}
+
+fn invalid(origin: impl fmt::Display, reason: impl Into<String>) -> ProcessingError {
+    ProcessingError::InvalidConfig {
+        origin: origin.to_string(),
+        reason: reason.into(),
+    }
+}
+
+fn kind_of(key: &str) -> Kind {
+    SETTINGS.iter().find(|(name, _)| *name == key).map(|(_, kind)| *kind).unwrap_or(Kind::Text)
+}
+
+fn describe(kind: Kind) -> String {
+    match kind {
+        Kind::Text => "a string".to_string(),
+        Kind::Choice(names) => format!("one of {}", names.join(", ")),
+        Kind::Integer => "a non-negative integer".to_string(),
+        Kind::Float => "a number".to_string(),
+        Kind::Bool => "true or false".to_string(),
+        Kind::List => "an array of strings".to_string(),
+    }
+}
+
+/// `value` as `kind`; integers are accepted where numbers are expected
+fn check_kind(kind: Kind, value: Value) -> std::result::Result<Value, String> {
+    let ok = match (&kind, &value) {
+        (Kind::Text, Value::String(_)) | (Kind::Bool, Value::Boolean(_)) | (Kind::Float, Value::Float(_)) => true,
+        (Kind::Choice(names), Value::String(text)) => names.contains(&text.as_str()),
+        (Kind::Integer, Value::Integer(number)) => *number >= 0,
+        (Kind::Float, Value::Integer(number)) => return Ok(Value::Float(*number as f64)),
+        (Kind::List, Value::Array(items)) => items.iter().all(Value::is_str),
+        _ => false,
+    };
+    if ok {
+        Ok(value)
+    } else {
+        Err(format!("expected {}, got {}", describe(kind), value))
+    }
+}
+
+/// Environment text as `kind`; lists are comma-separated
+fn parse_env(kind: Kind, raw: &str) -> std::result::Result<Value, String> {
+    let bad = || format!("expected {}, got \"{}\"", describe(kind), raw);
+    Ok(match kind {
+        Kind::Text | Kind::Choice(_) => Value::String(raw.to_string()),
+        Kind::Integer => Value::Integer(raw.trim().parse().map_err(|_| bad())?),
+        Kind::Float => Value::Float(raw.trim().parse().map_err(|_| bad())?),
+        Kind::Bool => Value::Boolean(match raw.trim() {
+            "1" | "true" => true,
+            "0" | "false" => false,
+            _ => return Err(bad()),
+        }),
+        Kind::List => Value::Array(raw.split(',').filter(|item| !item.is_empty()).map(|item| Value::String(item.to_string())).collect()),
+    })
+}
+
+fn choice<T: ValueEnum>(name: Option<&str>) -> Option<T> {
+    T::from_str(name?, false).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_layers_apply_in_order_and_record_their_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dobby.toml");
+        std::fs::write(&path, r#"
+            profile = "fast"
+
+            [model]
+            name = "smollm2-135m"
+
+            [generation]
+            temperature = 0.5
+            top_k = 20
+
+            [run]
+            agent_count = 8
+
+            [profiles.fast.generation]
+            top_p = 0.9
+        "#).unwrap();
+
+        let mut config = LayeredConfig::load(Some(&path), None, env(&[
+            ("DOBBY_GENERATION_TOP_K", "30"),
+            ("DOBBY_GENERATION_STOP_SEQUENCES", "\n\n,###"),
+            ("HOME", "/root"),
+        ])).unwrap();
+        config.set("run.agent_count", Value::Integer(4), ConfigSource::Flag).unwrap();
+
+        assert_eq!(config.profile(), Some("fast"));
+        assert_eq!(config.source("generation.num_beams"), Some(&ConfigSource::Default));
+        // The profile outranks the file; the built-in profile and the file's extension both apply
+        assert_eq!(config.source("generation.temperature"), Some(&ConfigSource::Profile("fast".to_string())));
+        assert_eq!(config.float("generation.top_p"), Some(0.9));
+        assert_eq!(config.usize("generation.top_k"), Some(30));
+        assert_eq!(config.source("generation.top_k"), Some(&ConfigSource::Env("DOBBY_GENERATION_TOP_K".to_string())));
+        assert_eq!(config.usize("run.agent_count"), Some(4));
+        assert_eq!(config.source("model.name"), Some(&ConfigSource::File(path.clone())));
+        assert_eq!(config.get("chunking.loc"), None);
+
+        let generation = config.generation_config();
+        assert_eq!(generation.max_new_tokens, 40);
+        assert_eq!(generation.stop_sequences, vec!["\n\n", "###"]);
+        assert_eq!(config.model_config().unwrap().name, "smollm2-135m");
+
+        let rendered = config.render();
+        assert!(rendered.contains("# profile: fast"));
+        assert!(rendered.lines().any(|line| line.starts_with("run.agent_count") && line.ends_with("# flag")));
+        assert!(rendered.lines().any(|line| line.starts_with("chunking.loc") && line.ends_with("# unset")));
+    }
+
+    #[test]
+    fn test_invalid_layers_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let load = |text: &str, profile: Option<&str>, vars: &[(&str, &str)]| {
+            let path = dir.path().join("bad.toml");
+            std::fs::write(&path, text).unwrap();
+            LayeredConfig::load(Some(&path), profile, env(vars)).unwrap_err().to_string()
+        };
+
+        assert!(load("[generation]\ntemprature = 0.2\n", None, &[]).contains("unknown setting \"generation.temprature\""));
+        assert!(load("[generation]\ntop_k = \"many\"\n", None, &[]).contains("expected a non-negative integer"));
+        assert!(load("[chunking]\nmode = \"pages\"\n", None, &[]).contains("one of lines, semantic, tokens"));
+        assert!(load("", Some("turbo"), &[]).contains("unknown profile \"turbo\" (known: fast, quality)"));
+        assert!(load("", None, &[("DOBBY_RUN_AGENT_COUNT", "lots")]).contains("DOBBY_RUN_AGENT_COUNT"));
+        assert!(load("[generation\n", None, &[]).contains("bad.toml"));
+
+        let missing = LayeredConfig::load(Some(&dir.path().join("missing.toml")), None, env(&[]));
+        assert!(matches!(missing, Err(ProcessingError::Io(_))));
+
+        let mut config = LayeredConfig::defaults();
+        assert!(config.set("generation.temperature", Value::Integer(1), ConfigSource::Flag).is_ok());
+        assert_eq!(config.float("generation.temperature"), Some(1.0));
+        assert!(config.set("model.size", Value::String("xl".to_string()), ConfigSource::Flag).is_err());
+    }
+}
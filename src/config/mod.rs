@@ -0,0 +1,186 @@
+//! Configuration structures for generation and model settings
+//!
+//! `layered` resolves them from defaults, a TOML file, a profile, the
+//! environment and command-line flags.
+
+use std::path::PathBuf;
+use clap::ValueEnum;
+
+pub mod layered;
+
+pub use layered::{ConfigSource, LayeredConfig};
+
+/// Strategy for text generation
+#[derive(Debug, Clone, ValueEnum)]
+pub enum SamplingStrategy {
+    #[value(name = "sampling")]
+    Sampling,
+    #[value(name = "beam")]
+    Beam,
+}
+
+/// Strategy for splitting input into chunks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ChunkingMode {
+    /// Fixed windows of `--loc` lines
+    #[value(name = "lines")]
+    Lines,
+    /// Top-level units of Rust, Python, TS/JS, Go and C files, found by
+    /// language detection and packed up to `--loc` lines; other files are
+    /// split into plain line windows
+    #[value(name = "semantic")]
+    Semantic,
+    /// Packed by real tokenizer tokens to fit the model context window
+    #[value(name = "tokens")]
+    Tokens,
+}
+
+/// How chunks of the same file relate while being summarized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SummaryStrategy {
+    /// Every chunk summarized on its own, all at once
+    #[value(name = "parallel")]
+    Parallel,
+    /// Chunks of a file summarized in order, each seeing the summary so far
+    #[value(name = "refine")]
+    Refine,
+}
+
+/// File format of the summaries a run writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Summaries separated by blank lines, grouped under file headers
+    #[value(name = "text")]
+    Text,
+    /// Per-file headings with a linkable anchor per chunk line range
+    #[value(name = "markdown")]
+    Markdown,
+    /// One document holding run metadata and every chunk
+    #[value(name = "json")]
+    Json,
+    /// One chunk object per line
+    #[value(name = "jsonl")]
+    Jsonl,
+    /// A single self-contained page
+    #[value(name = "html")]
+    Html,
+}
+
+/// Generation configuration parameters
+#[derive(Debug, Clone)]
+pub struct GenerationConfig {
+    pub strategy: SamplingStrategy,
+
+    // Sampling parameters
+    pub temperature: f32,
+    pub top_p: f32,
+    pub top_k: usize,
+
+    // Beam search parameters
+    pub num_beams: usize,
+    pub length_penalty: f32,
+    pub early_stopping: bool,
+
+    // Universal generation controls
+    pub max_new_tokens: usize,
+    pub min_length: usize,
+    pub repetition_penalty: f32,
+    pub no_repeat_ngram_size: usize,
+    pub stop_sequences: Vec<String>,
+}
+
+impl Default for GenerationConfig {
+    fn default() -> Self {
+        Self {
+            strategy: SamplingStrategy::Sampling,
+            temperature: 0.35,
+            top_p: 0.85,
+            top_k: 40,
+            num_beams: 3,
+            length_penalty: 1.05,
+            early_stopping: false,
+            max_new_tokens: 60,
+            min_length: 35,
+            repetition_penalty: 1.15,
+            no_repeat_ngram_size: 3,
+            stop_sequences: vec!["\n\n".to_string()],
+        }
+    }
+}
+
+/// Context window assumed when the model config does not declare one
+pub const DEFAULT_CONTEXT_WINDOW: usize = 2048;
+
+/// Model names `ModelConfig::from_name` knows; any other name resolves to `./models/<name>`
+pub const KNOWN_MODELS: &[&str] = &["qwen2.5-0.5b-int4", "smollm2-135m", "smollm2-360m"];
+
+/// Model configuration
+#[derive(Debug, Clone)]
+pub struct ModelConfig {
+    pub name: String,
+    pub model_path: PathBuf,
+    pub tokenizer_path: Option<PathBuf>,
+}
+
+impl ModelConfig {
+    pub fn new(name: String, model_path: PathBuf, tokenizer_path: Option<PathBuf>) -> Self {
+        Self {
+            name,
+            model_path,
+            tokenizer_path,
+        }
+    }
+
+    /// Resolve default model paths based on model name
+    pub fn from_name(name: &str, custom_path: Option<PathBuf>, tokenizer_path: Option<PathBuf>) -> Self {
+        let model_path = custom_path.unwrap_or_else(|| match name {
+            "qwen2.5-0.5b-int4" => PathBuf::from("./models/qwen2.5-0.5b-int4"),
+            "smollm2-135m" => PathBuf::from("./models/smollm2-135m"),
+            "smollm2-360m" => PathBuf::from("./models/smollm2-360m"),
+            _ => PathBuf::from(format!("./models/{}", name)),
+        });
+
+        Self {
+            name: name.to_string(),
+            model_path,
+            tokenizer_path,
+        }
+    }
+
+    /// Context window in tokens from `config.json` (`max_position_embeddings`)
+    ///
+    /// Falls back to `DEFAULT_CONTEXT_WINDOW` when the file or field is missing.
+    pub fn context_window(&self) -> usize {
+        std::fs::read_to_string(self.model_path.join("config.json"))
+            .ok()
+            .and_then(|raw| serde_json::from_str::<serde_json::Value>(&raw).ok())
+            .and_then(|config| config.get("max_position_embeddings").and_then(|v| v.as_u64()))
+            .map(|window| window as usize)
+            .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+    }
+
+    /// Get tokenizer path (model_path/tokenizer_dir if not specified)
+    pub fn tokenizer_path(&self) -> PathBuf {
+        self.tokenizer_path.clone().unwrap_or_else(|| {
+            match self.name.as_str() {
+                "qwen2.5-0.5b-int4" => PathBuf::from("./tokenizer_dir"),
+                _ => self.model_path.join("tokenizer"),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_window_from_model_config() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = ModelConfig::new("custom".to_string(), temp_dir.path().to_path_buf(), None);
+        assert_eq!(config.context_window(), DEFAULT_CONTEXT_WINDOW);
+
+        std::fs::write(temp_dir.path().join("config.json"), r#"{"max_position_embeddings": 32768}"#).unwrap();
+        assert_eq!(config.context_window(), 32768);
+    }
+}
@@ -3,6 +3,8 @@
 //! This module provides the database-first integration with CozoDB for high-performance
 //! neural processing operations. It implements connection pooling, CRUD operations,
 //! and streaming queries to support 1000+ records/minute throughput.
+//! The connection and pool types only simulate CozoDB (the `cozo` dependency
+//! is disabled) and do not build yet, so no CLI command stores records.
 
 pub mod connection_pool;
 pub mod record;
@@ -17,4 +19,4 @@ pub use connection_pool::{CozoConnectionPool, ConnectionPoolConfig};
 pub use record::CodeRecord;
 pub use connection::CozoConnection;
 pub use error::CozoError;
-pub use query::{QueryStream, QueryParams};
\ No newline at end of file
+pub use query::{QueryStream, QueryParams};
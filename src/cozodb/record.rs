@@ -7,6 +7,20 @@ use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
 
+use crate::chunking::{detect_language, DetectionMethod, LanguageDetection, SourceLanguage, Symbol};
+
+/// Metadata key holding the source file path of a record, when known
+pub const FILE_PATH_KEY: &str = "file_path";
+
+/// Metadata key holding the symbols the record's code defines (see `Symbol::to_json`)
+pub const SYMBOLS_KEY: &str = "symbols";
+
+/// Metadata key holding the confidence of a detected `language`, in `[0, 1]`
+pub const LANGUAGE_CONFIDENCE_KEY: &str = "language_confidence";
+
+/// Metadata key holding the model summary of the record's code
+pub const SUMMARY_KEY: &str = "summary";
+
 /// Database record for code storage and processing
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CodeRecord {
@@ -82,6 +96,74 @@ impl CodeRecord {
         self.metadata.get(key)
     }
 
+    /// Source file path stored under `FILE_PATH_KEY`, if any
+    pub fn file_path(&self) -> Option<&str> {
+        self.get_metadata(FILE_PATH_KEY).and_then(|value| value.as_str())
+    }
+
+    /// Record the symbols this record's code defines under `SYMBOLS_KEY`
+    pub fn update_symbols(&mut self, symbols: &[Symbol]) {
+        let symbols = symbols.iter().map(Symbol::to_json).collect();
+        self.update_metadata(SYMBOLS_KEY, serde_json::Value::Array(symbols));
+    }
+
+    /// Symbols stored under `SYMBOLS_KEY`; malformed entries are skipped
+    pub fn symbols(&self) -> Vec<Symbol> {
+        match self.get_metadata(SYMBOLS_KEY) {
+            Some(serde_json::Value::Array(values)) => values.iter().filter_map(Symbol::from_json).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether this record defines the symbol named `query` (see `Symbol::matches`)
+    pub fn defines(&self, query: &str) -> bool {
+        self.symbols().iter().any(|symbol| symbol.matches(query))
+    }
+
+    /// Language for chunking: the `language` field, else detected from path and content
+    pub fn source_language(&self) -> SourceLanguage {
+        self.language_detection().language
+    }
+
+    /// The `language` field when it names a language, else a fresh detection
+    pub fn language_detection(&self) -> LanguageDetection {
+        match SourceLanguage::from_name(&self.language) {
+            SourceLanguage::Unknown => detect_language(self.file_path(), &self.content),
+            named => LanguageDetection::new(
+                named,
+                self.language_confidence().unwrap_or(1.0),
+                DetectionMethod::Explicit,
+            ),
+        }
+    }
+
+    /// Fill in `language` when it is blank or unrecognised, storing the confidence
+    ///
+    /// A language that was already named is kept as is.
+    pub fn detect_language(&mut self) -> LanguageDetection {
+        let detection = self.language_detection();
+        if detection.method != DetectionMethod::Explicit {
+            self.language = detection.language.as_str().to_string();
+            self.update_metadata(LANGUAGE_CONFIDENCE_KEY, serde_json::json!(detection.confidence));
+        }
+        detection
+    }
+
+    /// Confidence stored under `LANGUAGE_CONFIDENCE_KEY` by `detect_language`
+    pub fn language_confidence(&self) -> Option<f32> {
+        self.get_metadata(LANGUAGE_CONFIDENCE_KEY).and_then(|value| value.as_f64()).map(|value| value as f32)
+    }
+
+    /// Summary stored under `SUMMARY_KEY`, if the record has been summarized
+    pub fn summary(&self) -> Option<&str> {
+        self.get_metadata(SUMMARY_KEY).and_then(|value| value.as_str())
+    }
+
+    /// Store `summary` under `SUMMARY_KEY`
+    pub fn update_summary(&mut self, summary: impl Into<String>) {
+        self.update_metadata(SUMMARY_KEY, serde_json::Value::String(summary.into()));
+    }
+
     /// Check if record is recent (created within last N seconds)
     pub fn is_recent(&self, seconds: i64) -> bool {
         let now = Utc::now();
@@ -38,6 +38,42 @@ pub enum ProcessingError {
         message: String,
     },
 
+    #[error("Invalid output schema: {0}")]
+    InvalidSchema(String),
+
+    #[error("Invalid prompt template: {0}")]
+    InvalidPromptTemplate(String),
+
+    #[error("Invalid evaluation dataset {path}: {reason}")]
+    InvalidDataset {
+        path: String,
+        reason: String,
+    },
+
+    #[error("Invalid task definitions {path}: {reason}")]
+    InvalidTasks {
+        path: String,
+        reason: String,
+    },
+
+    #[error("Invalid benchmark report {path}: {reason}")]
+    InvalidBenchReport {
+        path: String,
+        reason: String,
+    },
+
+    #[error("Invalid path pattern {pattern}: {reason}")]
+    InvalidPattern {
+        pattern: String,
+        reason: String,
+    },
+
+    #[error("Invalid configuration in {origin}: {reason}")]
+    InvalidConfig {
+        origin: String,
+        reason: String,
+    },
+
     #[error("Contract violation: {contract_name} - {violation}")]
     ContractViolation {
         contract_name: String,
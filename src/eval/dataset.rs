@@ -0,0 +1,108 @@
+//! JSONL datasets of (code, reference summary) pairs
+
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::chunking::{extract_symbols, Chunk, Chunker, SourceLanguage};
+use crate::errors::{ProcessingError, Result};
+
+/// One code sample with the summary a good model should produce
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalItem {
+    pub id: String,
+    pub code: String,
+    pub reference: String,
+    pub file_path: Option<String>,
+    pub language: SourceLanguage,
+}
+
+impl EvalItem {
+    /// The whole sample as one chunk, so prompt templates see its metadata
+    pub fn chunk(&self) -> Chunk {
+        let mut chunk = Chunk::new(0, self.code.lines().count(), self.code.clone()).with_language(self.language);
+        chunk.symbols = extract_symbols(&self.code, self.language);
+        match &self.file_path {
+            Some(path) => chunk.with_file_path(path.clone()),
+            None => chunk,
+        }
+    }
+
+    /// The sample split by `chunker`, as the CLI would split a file
+    pub fn chunks(&self, chunker: &dyn Chunker) -> Result<Vec<Chunk>> {
+        chunker.chunk(&self.code, self.language, self.file_path.as_deref())
+    }
+}
+
+/// Load a dataset file; see `parse_dataset` for the format
+pub fn load_dataset(path: impl AsRef<Path>) -> Result<Vec<EvalItem>> {
+    let path = path.as_ref();
+    parse_dataset(&std::fs::read_to_string(path)?, &path.display().to_string())
+}
+
+/// Parse JSONL with `code` and `reference` per line
+///
+/// Optional fields: `id` (defaults to the 1-based line number), `file_path`
+/// and `language` (defaults to the file path's extension). Blank lines are
+/// skipped.
+///
+/// # Errors
+/// * `ProcessingError::InvalidDataset` - malformed JSON, missing fields or duplicate ids
+pub fn parse_dataset(text: &str, source: &str) -> Result<Vec<EvalItem>> {
+    let invalid = |line: usize, reason: String| ProcessingError::InvalidDataset {
+        path: source.to_string(),
+        reason: format!("line {}: {}", line, reason),
+    };
+
+    let mut items: Vec<EvalItem> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let value: Value = serde_json::from_str(line).map_err(|e| invalid(number, e.to_string()))?;
+        let field = |name: &str| value.get(name).and_then(Value::as_str).map(str::to_string);
+        let code = field("code").ok_or_else(|| invalid(number, "missing string field \"code\"".to_string()))?;
+        let reference =
+            field("reference").ok_or_else(|| invalid(number, "missing string field \"reference\"".to_string()))?;
+        let id = match value.get("id") {
+            Some(Value::String(id)) => id.clone(),
+            Some(Value::Number(id)) => id.to_string(),
+            _ => number.to_string(),
+        };
+        if items.iter().any(|item| item.id == id) {
+            return Err(invalid(number, format!("duplicate id \"{}\"", id)));
+        }
+
+        let file_path = field("file_path");
+        let language = SourceLanguage::resolve(field("language").as_deref(), file_path.as_deref());
+        items.push(EvalItem { id, code, reference, file_path, language });
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dataset_fills_defaults_and_rejects_bad_lines() {
+        let text = concat!(
+            r#"{"code": "fn main() {}", "reference": "Entry point.", "file_path": "src/main.rs"}"#,
+            "\n\n",
+            r#"{"id": "py-1", "code": "def f(): pass", "reference": "No-op.", "language": "python"}"#,
+            "\n",
+        );
+        let items = parse_dataset(text, "eval.jsonl").unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!((items[0].id.as_str(), items[0].language), ("1", SourceLanguage::Rust));
+        assert_eq!((items[1].id.as_str(), items[1].language), ("py-1", SourceLanguage::Python));
+        assert_eq!(items[0].chunk().file_path.as_deref(), Some("src/main.rs"));
+
+        let missing = parse_dataset(r#"{"code": "x"}"#, "eval.jsonl").unwrap_err();
+        assert!(missing.to_string().contains("line 1: missing string field \"reference\""));
+        let duplicate = format!("{}\n{}", r#"{"id": 1, "code": "a", "reference": "b"}"#, r#"{"id": 1, "code": "c", "reference": "d"}"#);
+        assert!(matches!(parse_dataset(&duplicate, "eval.jsonl"), Err(ProcessingError::InvalidDataset { .. })));
+    }
+}
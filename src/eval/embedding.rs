@@ -0,0 +1,81 @@
+//! Built-in `Embedder`s for semantic similarity without a separate model
+
+use std::sync::Arc;
+
+use clap::ValueEnum;
+
+use super::harness::Embedder;
+use super::metrics::tokenize;
+use crate::errors::Result;
+
+/// Dimensions `BagOfTokensEmbedder::default` hashes tokens into
+pub const DEFAULT_EMBEDDING_DIMENSIONS: usize = 4096;
+
+/// Embedder an eval run can select by name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EmbedderKind {
+    /// Hashed counts of lowercased word tokens (see `BagOfTokensEmbedder`)
+    BagOfTokens,
+}
+
+impl EmbedderKind {
+    pub fn build(self) -> Arc<dyn Embedder> {
+        match self {
+            EmbedderKind::BagOfTokens => Arc::new(BagOfTokensEmbedder::default()),
+        }
+    }
+}
+
+/// Bag-of-tokens baseline: each word token counts towards one of `dimensions`
+/// buckets picked by a stable hash
+///
+/// Word order and synonyms are ignored, so scores track shared vocabulary
+/// much like ROUGE-1, but as a cosine the reference length does not cap them.
+#[derive(Debug, Clone, Copy)]
+pub struct BagOfTokensEmbedder {
+    dimensions: usize,
+}
+
+impl BagOfTokensEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        Self { dimensions: dimensions.max(1) }
+    }
+}
+
+impl Default for BagOfTokensEmbedder {
+    fn default() -> Self {
+        Self::new(DEFAULT_EMBEDDING_DIMENSIONS)
+    }
+}
+
+impl Embedder for BagOfTokensEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut embedding = vec![0.0; self.dimensions];
+        for token in tokenize(text) {
+            embedding[(fnv1a(&token) % self.dimensions as u64) as usize] += 1.0;
+        }
+        Ok(embedding)
+    }
+}
+
+/// FNV-1a: fixed across runs and platforms, unlike `DefaultHasher`
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::metrics::cosine_similarity;
+
+    #[test]
+    fn test_bag_of_tokens_scores_shared_vocabulary() {
+        let embedder = EmbedderKind::BagOfTokens.build();
+        let similarity = |a: &str, b: &str| cosine_similarity(&embedder.embed(a).unwrap(), &embedder.embed(b).unwrap());
+
+        assert!((similarity("Parses the config file", "the config file parses") - 1.0).abs() < 1e-9);
+        assert!(similarity("Parses the config file", "Reads the config") > similarity("Parses the config file", "Starts a server"));
+        assert_eq!(similarity("", "Starts a server"), 0.0);
+        assert_eq!(BagOfTokensEmbedder::new(8).embed("a b c d").unwrap().len(), 8);
+    }
+}
@@ -0,0 +1,265 @@
+//! Runs a dataset through the summarizer and scores every summary
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use super::dataset::EvalItem;
+use super::metrics::{bleu, cosine_similarity, rouge_l, word_diff};
+use crate::chunking::{Chunk, Chunker};
+use crate::errors::Result;
+use crate::prompt::PromptTemplate;
+use crate::summarization::Reducer;
+
+/// Sentence embedding model used for semantic similarity
+pub trait Embedder: Send + Sync {
+    fn embed(&self, text: &str) -> Result<Vec<f32>>;
+}
+
+/// Scores of one dataset item
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemScore {
+    pub id: String,
+    pub reference: String,
+    pub candidate: String,
+    pub rouge_l: f64,
+    pub bleu: f64,
+    pub embedding_similarity: Option<f64>,
+    /// Word diff of the candidate against the reference (see `word_diff`)
+    pub diff: String,
+}
+
+impl ItemScore {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "rouge_l": self.rouge_l,
+            "bleu": self.bleu,
+            "embedding_similarity": self.embedding_similarity,
+            "reference": self.reference,
+            "candidate": self.candidate,
+            "diff": self.diff,
+        })
+    }
+}
+
+/// Scores of a whole run, serializable for tracking over time
+#[derive(Debug, Clone)]
+pub struct EvalReport {
+    /// Free-form run name, e.g. the model and prompt being compared
+    pub label: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub items: Vec<ItemScore>,
+}
+
+impl EvalReport {
+    pub fn mean_rouge_l(&self) -> f64 {
+        mean(self.items.iter().map(|item| item.rouge_l))
+    }
+
+    pub fn mean_bleu(&self) -> f64 {
+        mean(self.items.iter().map(|item| item.bleu))
+    }
+
+    /// Mean embedding similarity, when every item has one
+    pub fn mean_embedding_similarity(&self) -> Option<f64> {
+        let scores: Option<Vec<f64>> = self.items.iter().map(|item| item.embedding_similarity).collect();
+        scores.filter(|scores| !scores.is_empty()).map(|scores| mean(scores.into_iter()))
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "label": self.label,
+            "created_at": self.created_at.to_rfc3339(),
+            "items_evaluated": self.items.len(),
+            "metrics": {
+                "rouge_l": self.mean_rouge_l(),
+                "bleu": self.mean_bleu(),
+                "embedding_similarity": self.mean_embedding_similarity(),
+            },
+            "items": self.items.iter().map(ItemScore::to_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn mean(scores: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = scores.fold((0.0, 0usize), |(sum, count), score| (sum + score, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+/// Summarizes dataset items with a prompt template and scores the results
+pub struct EvalHarness {
+    template: PromptTemplate,
+    embedder: Option<Arc<dyn Embedder>>,
+    chunker: Option<Arc<dyn Chunker>>,
+    label: Option<String>,
+}
+
+impl EvalHarness {
+    pub fn new(template: PromptTemplate) -> Self {
+        Self {
+            template,
+            embedder: None,
+            chunker: None,
+            label: None,
+        }
+    }
+
+    /// Split items with `chunker` instead of sending each one whole
+    pub fn with_chunker(mut self, chunker: Arc<dyn Chunker>) -> Self {
+        self.chunker = Some(chunker);
+        self
+    }
+
+    /// Also report embedding similarity using `embedder`
+    pub fn with_embedder(mut self, embedder: Arc<dyn Embedder>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    /// Name the run in the report
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Summarize every chunk of every item in one parallel batch and score
+    /// each item's joined chunk summaries
+    ///
+    /// # Errors
+    /// * Any error returned by the chunker, `reducer` or the embedder
+    pub async fn run<R>(&self, items: &[EvalItem], reducer: &R) -> Result<EvalReport>
+    where
+        R: Reducer + ?Sized,
+    {
+        let item_chunks = items
+            .iter()
+            .map(|item| match &self.chunker {
+                Some(chunker) => item.chunks(chunker.as_ref()),
+                None => Ok(vec![item.chunk()]),
+            })
+            .collect::<Result<Vec<Vec<Chunk>>>>()?;
+        let inputs = item_chunks
+            .iter()
+            .flatten()
+            .map(|chunk| (chunk.content.clone(), self.template.render(chunk)))
+            .collect();
+        let mut summaries = reducer.reduce_each(inputs).await?.into_iter();
+
+        let items = items
+            .iter()
+            .zip(&item_chunks)
+            .map(|(item, chunks)| {
+                let candidate = summaries.by_ref().take(chunks.len()).collect::<Vec<_>>().join("\n\n");
+                self.score(item, candidate)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(EvalReport {
+            label: self.label.clone(),
+            created_at: Utc::now(),
+            items,
+        })
+    }
+
+    /// Score one candidate summary against its item's reference
+    pub fn score(&self, item: &EvalItem, candidate: String) -> Result<ItemScore> {
+        let embedding_similarity = match &self.embedder {
+            Some(embedder) => Some(cosine_similarity(
+                &embedder.embed(&item.reference)?,
+                &embedder.embed(&candidate)?,
+            )),
+            None => None,
+        };
+
+        Ok(ItemScore {
+            id: item.id.clone(),
+            rouge_l: rouge_l(&item.reference, &candidate),
+            bleu: bleu(&item.reference, &candidate),
+            embedding_similarity,
+            diff: word_diff(&item.reference, &candidate),
+            reference: item.reference.clone(),
+            candidate,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::dataset::parse_dataset;
+    use async_trait::async_trait;
+
+    /// Answers with the rendered prompt itself
+    struct PromptEcho;
+
+    #[async_trait]
+    impl Reducer for PromptEcho {
+        async fn reduce(&self, prompt: &str, inputs: Vec<String>) -> Result<Vec<String>> {
+            Ok(inputs.iter().map(|_| prompt.to_string()).collect())
+        }
+    }
+
+    /// Counts vowels: crude, but deterministic and similar for similar text
+    struct VowelEmbedder;
+
+    impl Embedder for VowelEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            Ok("aeiou".chars().map(|v| text.matches(v).count() as f32).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_scores_every_item_and_serializes_report() {
+        let items = parse_dataset(
+            concat!(
+                r#"{"id": "a", "code": "fn parse() {}", "reference": "Defines parse in src/a.rs", "file_path": "src/a.rs"}"#,
+                "\n",
+                r#"{"id": "b", "code": "fn run() {}", "reference": "Starts the server", "file_path": "src/b.rs"}"#,
+            ),
+            "eval.jsonl",
+        )
+        .unwrap();
+        let harness = EvalHarness::new(PromptTemplate::parse("Defines {{symbols}} in {{file_path}}").unwrap())
+            .with_label("echo");
+
+        let report = harness.run(&items, &PromptEcho).await.unwrap();
+        assert_eq!(report.items[0].candidate, "Defines fn parse in src/a.rs");
+        assert!((report.items[0].rouge_l - 12.0 / 13.0).abs() < 1e-9);
+        assert_eq!(report.items[1].rouge_l, 0.0);
+        assert_eq!(report.mean_embedding_similarity(), None);
+
+        let json = report.to_json();
+        assert_eq!(json["label"], "echo");
+        assert_eq!(json["items_evaluated"], 2);
+        assert_eq!(json["metrics"]["embedding_similarity"], Value::Null);
+        assert_eq!(json["items"][0]["diff"], "defines {+fn+} parse in src a rs");
+
+        let embedded = harness.with_embedder(Arc::new(VowelEmbedder)).run(&items, &PromptEcho).await.unwrap();
+        assert!(embedded.mean_embedding_similarity().unwrap() > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_run_with_chunker_joins_chunk_summaries_per_item() {
+        let items = parse_dataset(
+            concat!(
+                r#"{"id": "a", "code": "fn parse() {}\nfn run() {}", "reference": "Lines 1-1 Lines 2-2", "file_path": "src/a.rs"}"#,
+                "\n",
+                r#"{"id": "b", "code": "fn stop() {}", "reference": "Lines 1-1", "file_path": "src/b.rs"}"#,
+            ),
+            "eval.jsonl",
+        )
+        .unwrap();
+        let harness = EvalHarness::new(PromptTemplate::parse("Lines {{line_start}}-{{line_end}}").unwrap())
+            .with_chunker(Arc::new(crate::chunking::LineChunker::new(1)));
+
+        let report = harness.run(&items, &PromptEcho).await.unwrap();
+        assert_eq!(report.items[0].candidate, "Lines 1-1\n\nLines 2-2");
+        assert_eq!(report.items[1].candidate, "Lines 1-1");
+        assert_eq!(report.mean_rouge_l(), 1.0);
+    }
+}
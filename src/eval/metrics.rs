@@ -0,0 +1,172 @@
+//! Text-overlap metrics between a reference and a candidate summary
+
+/// Lowercased word tokens; identifiers keep their underscores
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// ROUGE-L F1: longest common subsequence against both lengths
+pub fn rouge_l(reference: &str, candidate: &str) -> f64 {
+    let (reference, candidate) = (tokenize(reference), tokenize(candidate));
+    if reference.is_empty() || candidate.is_empty() {
+        return 0.0;
+    }
+
+    let common = lcs_pairs(&reference, &candidate).len() as f64;
+    if common == 0.0 {
+        return 0.0;
+    }
+    let precision = common / candidate.len() as f64;
+    let recall = common / reference.len() as f64;
+    2.0 * precision * recall / (precision + recall)
+}
+
+/// Sentence BLEU-4 with brevity penalty
+///
+/// Uses add-one smoothing for 2- to 4-grams (Lin & Och, 2004) so short
+/// summaries without a matching 4-gram still score above zero.
+pub fn bleu(reference: &str, candidate: &str) -> f64 {
+    const MAX_ORDER: usize = 4;
+
+    let (reference, candidate) = (tokenize(reference), tokenize(candidate));
+    if reference.is_empty() || candidate.is_empty() {
+        return 0.0;
+    }
+
+    let mut log_precision = 0.0;
+    for order in 1..=MAX_ORDER {
+        let candidate_ngrams = ngrams(&candidate, order);
+        let mut reference_ngrams = ngrams(&reference, order);
+        let matches = candidate_ngrams
+            .iter()
+            .filter(|ngram| match reference_ngrams.iter().position(|r| r == *ngram) {
+                // Clipped counts: each reference n-gram matches once
+                Some(index) => {
+                    reference_ngrams.swap_remove(index);
+                    true
+                }
+                None => false,
+            })
+            .count() as f64;
+        let total = candidate_ngrams.len() as f64;
+
+        let precision = match order {
+            1 if matches == 0.0 => return 0.0,
+            1 => matches / total,
+            _ => (matches + 1.0) / (total + 1.0),
+        };
+        log_precision += precision.ln() / MAX_ORDER as f64;
+    }
+
+    let brevity = if candidate.len() >= reference.len() {
+        1.0
+    } else {
+        (1.0 - reference.len() as f64 / candidate.len() as f64).exp()
+    };
+    brevity * log_precision.exp()
+}
+
+/// Cosine of the angle between two embeddings; 0 for empty or mismatched vectors
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| *x as f64 * *y as f64).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| (*x as f64).powi(2)).sum::<f64>().sqrt();
+    match norm(a) * norm(b) {
+        0.0 => 0.0,
+        norms => dot / norms,
+    }
+}
+
+/// Word diff of `candidate` against `reference`: `[-missing-]` and `{+extra+}`
+pub fn word_diff(reference: &str, candidate: &str) -> String {
+    let (reference, candidate) = (tokenize(reference), tokenize(candidate));
+    let pairs = lcs_pairs(&reference, &candidate);
+
+    let mut parts = Vec::new();
+    let (mut r, mut c) = (0, 0);
+    for (next_r, next_c) in pairs.into_iter().chain(std::iter::once((reference.len(), candidate.len()))) {
+        if r < next_r {
+            parts.push(format!("[-{}-]", reference[r..next_r].join(" ")));
+        }
+        if c < next_c {
+            parts.push(format!("{{+{}+}}", candidate[c..next_c].join(" ")));
+        }
+        if next_r < reference.len() {
+            parts.push(reference[next_r].clone());
+        }
+        r = next_r + 1;
+        c = next_c + 1;
+    }
+    parts.join(" ")
+}
+
+fn ngrams(words: &[String], order: usize) -> Vec<&[String]> {
+    words.windows(order).collect()
+}
+
+/// Index pairs `(reference, candidate)` of one longest common subsequence, in order
+fn lcs_pairs(a: &[String], b: &[String]) -> Vec<(usize, usize)> {
+    // lengths[i][j] = LCS length of a[i..] and b[j..]
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i] == b[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::with_capacity(lengths[0][0]);
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlap_metrics_rank_closer_summaries_higher() {
+        let reference = "Parses command line arguments into a Config struct.";
+        let close = "Parses the command line arguments into a Config.";
+        let far = "Opens one database connection pool.";
+
+        assert!((rouge_l(reference, reference) - 1.0).abs() < 1e-9);
+        assert!((bleu(reference, reference) - 1.0).abs() < 1e-9);
+        assert!(rouge_l(reference, close) > rouge_l(reference, far));
+        assert!(bleu(reference, close) > bleu(reference, far));
+        assert_eq!(bleu(reference, far), 0.0);
+        assert_eq!(rouge_l(reference, ""), 0.0);
+
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-9);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 2.0]), 0.0);
+    }
+
+    #[test]
+    fn test_word_diff_marks_missing_and_extra_words() {
+        assert_eq!(
+            word_diff("Reads the config file", "Reads a config file quickly"),
+            "reads [-the-] {+a+} config file {+quickly+}"
+        );
+        assert_eq!(word_diff("same text", "Same text."), "same text");
+    }
+}
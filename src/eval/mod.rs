@@ -0,0 +1,22 @@
+//! Offline evaluation of summary quality against reference summaries
+//!
+//! Contracts:
+//! - Datasets are JSONL with `code` and `reference` per line (see `parse_dataset`)
+//! - Every item is summarized through `Reducer`, with the same prompt template
+//!   the CLI uses, so model, prompt and `GenerationConfig` changes are comparable
+//! - With a `Chunker`, items are split as the CLI would split them and their
+//!   chunk summaries, joined in order, form the candidate
+//! - ROUGE-L and BLEU are computed on lowercased word tokens; embedding
+//!   similarity is reported only when an `Embedder` is configured, e.g. the
+//!   built-in `BagOfTokensEmbedder`
+//! - Reports serialize to JSON with run-level means and per-item scores and diffs
+
+pub mod dataset;
+pub mod embedding;
+pub mod harness;
+pub mod metrics;
+
+pub use dataset::{load_dataset, parse_dataset, EvalItem};
+pub use embedding::{BagOfTokensEmbedder, EmbedderKind};
+pub use harness::{Embedder, EvalHarness, EvalReport, ItemScore};
+pub use metrics::{bleu, cosine_similarity, rouge_l, word_diff};
@@ -25,12 +25,183 @@
 use anyhow::Result;
 use candle_core::Device;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokenizers::Tokenizer;
 use log::{info, warn, debug};
 
 use crate::config::GenerationConfig;
 use crate::layer1::traits::error::InferenceError;
+use crate::structured::{JsonConstraint, OutputSchema};
+
+/// Shared cancellation flag checked by the decode loop between tokens
+///
+/// Cheap to clone; all clones observe the same flag, so one token can stop
+/// every in-flight generation of a run.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of every generation observing this token
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Why a generation stopped before reaching its natural end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TruncationReason {
+    Cancelled,
+    DeadlineExceeded,
+}
+
+impl std::fmt::Display for TruncationReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TruncationReason::Cancelled => write!(f, "cancelled"),
+            TruncationReason::DeadlineExceeded => write!(f, "deadline exceeded"),
+        }
+    }
+}
+
+/// Per-generation stop conditions: run-wide cancellation plus an optional deadline
+#[derive(Debug, Clone, Default)]
+pub struct GenerationControl {
+    pub cancellation: CancellationToken,
+    pub deadline: Option<Instant>,
+}
+
+impl GenerationControl {
+    /// Control whose deadline is `timeout` from now
+    pub fn with_timeout(cancellation: CancellationToken, timeout: Duration) -> Self {
+        Self {
+            cancellation,
+            deadline: Some(Instant::now() + timeout),
+        }
+    }
+
+    /// Returns the reason generation must stop, if any
+    pub fn check(&self) -> Option<TruncationReason> {
+        if self.cancellation.is_cancelled() {
+            return Some(TruncationReason::Cancelled);
+        }
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Some(TruncationReason::DeadlineExceeded),
+            _ => None,
+        }
+    }
+}
+
+/// Generated text plus how the generation ended
+#[derive(Debug, Clone)]
+pub struct GenerationOutput {
+    pub text: String,
+    pub tokens_generated: usize,
+    /// `Some` when generation was stopped early; `text` then holds the partial output
+    pub truncated: Option<TruncationReason>,
+}
+
+impl GenerationOutput {
+    pub fn is_truncated(&self) -> bool {
+        self.truncated.is_some()
+    }
+}
+
+/// Token-by-token decode loop honouring `control` between tokens
+///
+/// `next_token` yields the next decoded piece, or `None` once generation
+/// finishes naturally. Cancellation and deadline are checked before every
+/// step, so a runaway generation stops with its partial output instead of
+/// holding a blocking thread indefinitely.
+pub fn decode_loop<F>(control: &GenerationControl, mut next_token: F) -> Result<GenerationOutput>
+where
+    F: FnMut() -> Result<Option<String>>,
+{
+    let mut text = String::new();
+    let mut tokens_generated = 0;
+
+    loop {
+        if let Some(reason) = control.check() {
+            debug!("Generation stopped after {} tokens: {}", tokens_generated, reason);
+            return Ok(GenerationOutput {
+                text,
+                tokens_generated,
+                truncated: Some(reason),
+            });
+        }
+
+        match next_token()? {
+            Some(piece) => {
+                text.push_str(&piece);
+                tokens_generated += 1;
+            }
+            None => {
+                return Ok(GenerationOutput {
+                    text,
+                    tokens_generated,
+                    truncated: None,
+                });
+            }
+        }
+    }
+}
+
+/// Decode loop that only accepts pieces keeping `constraint` satisfiable
+///
+/// `next_candidates` yields the model's next pieces in preference order
+/// (highest logit first), or `None` at end of sequence; the first piece the
+/// constraint allows is taken. When the model stops, runs out of valid
+/// candidates or is cut off by `control`, the document is closed with the
+/// shortest valid completion, so `text` always parses against the schema.
+pub fn constrained_decode_loop<F>(
+    control: &GenerationControl,
+    mut constraint: JsonConstraint,
+    mut next_candidates: F,
+) -> Result<GenerationOutput>
+where
+    F: FnMut(&JsonConstraint) -> Result<Option<Vec<String>>>,
+{
+    let mut tokens_generated = 0;
+
+    let truncated = loop {
+        if constraint.is_complete() {
+            break None;
+        }
+        if let Some(reason) = control.check() {
+            debug!("Structured generation stopped after {} tokens: {}", tokens_generated, reason);
+            break Some(reason);
+        }
+
+        let Some(candidates) = next_candidates(&constraint)? else { break None };
+        match candidates.iter().find(|piece| constraint.allows(piece)) {
+            Some(piece) => {
+                constraint.push(piece);
+                tokens_generated += 1;
+            }
+            None => {
+                debug!("No schema-valid candidate after {} tokens - closing document", tokens_generated);
+                break None;
+            }
+        }
+    };
+
+    Ok(GenerationOutput {
+        text: constraint.finish(),
+        tokens_generated,
+        truncated,
+    })
+}
 
 /// Candle-only inference engine MVP (no ONNX).
 /// Loads tokenizer and selects Device (Metal if available), returns deterministic summaries for now.
@@ -151,11 +322,91 @@ impl OptimizedInferenceEngine {
         prompt: &str,
         config: &GenerationConfig,
     ) -> Result<String> {
+        let output = self.summarize_chunk_with_control(
+            chunk,
+            prompt,
+            config,
+            &GenerationControl::default(),
+        )?;
+        Ok(output.text)
+    }
+
+    /// Summarize with generation config under cancellation and deadline control
+    ///
+    /// # Arguments
+    /// * `chunk` - Text chunk to summarize
+    /// * `prompt` - Custom prompt (MVP ignores but logs for future)
+    /// * `config` - Generation configuration (MVP ignores but logs for future)
+    /// * `control` - Cancellation token and deadline checked between tokens
+    ///
+    /// # Returns
+    /// * `GenerationOutput` - Full summary, or partial summary marked as truncated
+    pub fn summarize_chunk_with_control(
+        &self,
+        chunk: &str,
+        prompt: &str,
+        config: &GenerationConfig,
+        control: &GenerationControl,
+    ) -> Result<GenerationOutput> {
         debug!("MVP: Prompt '{}' and config {:?} noted for future implementation",
                prompt, config);
 
-        // MVP: Reuse simple summarize; wire generation params later
-        self.summarize_chunk(chunk)
+        // MVP: emit the deterministic summary word by word through the decode loop;
+        // real sampling will replace this step function
+        let summary = self.summarize_chunk(chunk)?;
+        let mut pieces = summary.split_inclusive(' ');
+        decode_loop(control, || Ok(pieces.next().map(str::to_string)))
+    }
+
+    /// Summarize into JSON valid against `schema` under cancellation and deadline control
+    ///
+    /// # Returns
+    /// * `GenerationOutput` - `text` is a complete JSON document even when truncated
+    pub fn summarize_chunk_structured(
+        &self,
+        chunk: &str,
+        prompt: &str,
+        config: &GenerationConfig,
+        schema: &OutputSchema,
+        control: &GenerationControl,
+    ) -> Result<GenerationOutput> {
+        debug!("MVP: Prompt '{}' with schema hint '{}' and config {:?} noted for future implementation",
+               prompt, schema.prompt_hint(), config);
+
+        // MVP: the deterministic summary placed into the schema is the model's
+        // only candidate at each step; real sampling will rank the vocabulary
+        let draft = schema.fill(&self.summarize_chunk(chunk)?).to_string();
+        let mut pieces = draft.split_inclusive([',', ':', ' ']);
+        constrained_decode_loop(control, schema.constraint(), |_| {
+            Ok(pieces.next().map(|piece| vec![piece.to_string()]))
+        })
+    }
+
+    /// Run several task prompts against one chunk, one after another
+    ///
+    /// Each task is a full generation over its own prompt; the MVP engine
+    /// keeps no prefix cache, so nothing is shared between tasks yet.
+    ///
+    /// # Arguments
+    /// * `chunk` - Text chunk shared by every task
+    /// * `tasks` - (prompt, generation config) per task, in output order
+    /// * `control` - Fresh control per task, so each task gets the full deadline
+    ///
+    /// # Returns
+    /// * `Vec<Result<GenerationOutput>>` - One output per task; a failed task does not stop the rest
+    pub fn summarize_chunk_tasks<C>(
+        &self,
+        chunk: &str,
+        tasks: &[(String, GenerationConfig)],
+        control: C,
+    ) -> Result<Vec<Result<GenerationOutput>>>
+    where
+        C: Fn() -> GenerationControl,
+    {
+        Ok(tasks
+            .iter()
+            .map(|(prompt, config)| self.summarize_chunk_with_control(chunk, prompt, config, &control()))
+            .collect())
     }
 
     /// Get device information
@@ -186,6 +437,11 @@ impl OptimizedInferenceEngine {
         })
     }
 
+    /// Shared handle to the loaded tokenizer (for token-budget chunking)
+    pub fn tokenizer(&self) -> Arc<Tokenizer> {
+        Arc::clone(&self.tokenizer)
+    }
+
     /// Get tokenizer info
     pub fn tokenizer_info(&self) -> Result<String> {
         let vocab_size = self.tokenizer.get_vocab_size(true);
@@ -247,4 +503,67 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_decode_loop_stops_when_cancelled() -> Result<()> {
+        let control = GenerationControl::default();
+        let mut emitted = 0;
+
+        let output = decode_loop(&control, || {
+            emitted += 1;
+            if emitted == 3 {
+                control.cancellation.cancel();
+            }
+            Ok(Some("tok ".to_string()))
+        })?;
+
+        assert_eq!(output.truncated, Some(TruncationReason::Cancelled));
+        assert_eq!(output.tokens_generated, 3);
+        assert_eq!(output.text, "tok tok tok ");
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_loop_enforces_deadline() -> Result<()> {
+        let control = GenerationControl::with_timeout(CancellationToken::new(), Duration::from_millis(20));
+
+        let output = decode_loop(&control, || {
+            std::thread::sleep(Duration::from_millis(5));
+            Ok(Some("x".to_string()))
+        })?;
+
+        assert_eq!(output.truncated, Some(TruncationReason::DeadlineExceeded));
+        assert!(output.tokens_generated > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_constrained_decode_loop_masks_and_closes_output() -> Result<()> {
+        let schema = OutputSchema::code_summary();
+        let mut steps = vec![
+            vec!["Sure! ".to_string(), "{\"purpose\":\"".to_string()],
+            vec!["Parses args\",".to_string()],
+            vec!["\"extra\":".to_string(), "\"public_items\":[\"Args\"".to_string()],
+        ]
+        .into_iter();
+
+        let output = constrained_decode_loop(&GenerationControl::default(), schema.constraint(), |_| Ok(steps.next()))?;
+
+        let value: serde_json::Value = serde_json::from_str(&output.text)?;
+        assert_eq!(value["purpose"], "Parses args");
+        assert_eq!(value["public_items"], serde_json::json!(["Args"]));
+        assert_eq!(output.tokens_generated, 3);
+        assert!(!output.is_truncated());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_loop_completes_without_truncation() -> Result<()> {
+        let mut pieces = vec!["a ", "b"].into_iter();
+        let output = decode_loop(&GenerationControl::default(), || Ok(pieces.next().map(str::to_string)))?;
+
+        assert!(!output.is_truncated());
+        assert_eq!(output.text, "a b");
+        Ok(())
+    }
 }
\ No newline at end of file
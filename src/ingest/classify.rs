@@ -0,0 +1,198 @@
+//! Content classification: which inputs are worth summarizing
+//!
+//! Contracts:
+//! - Binary, generated, vendored, minified, lockfile and encoded-blob input
+//!   is reported with a `SkipReason`; everything else is `None`
+//! - Path checks run first, then markers in the file header, then shape
+//!   heuristics over the whole text
+//! - Classification never reads more than the given text
+
+use std::fmt;
+use std::path::Path;
+
+/// Bytes inspected for NUL bytes (the same window git uses)
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Leading bytes searched for generated-code markers
+const HEADER_SCAN_BYTES: usize = 4096;
+
+/// Lines at least this long count as minified
+pub const MINIFIED_LINE_CHARS: usize = 1000;
+
+/// Share of encoded-alphabet characters above which a long line is a blob
+const ENCODED_BLOB_RATIO: f64 = 0.97;
+
+const BINARY_EXTENSIONS: [&str; 24] = [
+    "png", "jpg", "jpeg", "gif", "bmp", "ico", "webp", "pdf", "zip", "gz", "tgz", "xz", "bz2", "7z", "jar",
+    "class", "exe", "dll", "so", "dylib", "wasm", "woff", "woff2", "ttf",
+];
+
+const LOCKFILES: [&str; 11] = [
+    "Cargo.lock", "package-lock.json", "yarn.lock", "pnpm-lock.yaml", "bun.lockb", "poetry.lock",
+    "Pipfile.lock", "Gemfile.lock", "composer.lock", "go.sum", "flake.lock",
+];
+
+const VENDOR_DIRECTORIES: [&str; 5] = ["vendor", "node_modules", "third_party", "third-party", "bower_components"];
+
+const GENERATED_SUFFIXES: [&str; 6] = [".pb.go", "_pb2.py", "_pb2_grpc.py", ".g.dart", ".designer.cs", ".generated.ts"];
+
+/// Why an input is left out of summarization
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    Binary,
+    Lockfile,
+    Vendored { directory: String },
+    Generated { marker: String },
+    Minified { longest_line: usize },
+    EncodedBlob,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Binary => write!(f, "binary content"),
+            SkipReason::Lockfile => write!(f, "dependency lockfile"),
+            SkipReason::Vendored { directory } => write!(f, "vendored code ({}/)", directory),
+            SkipReason::Generated { marker } => write!(f, "generated code ({})", marker),
+            SkipReason::Minified { longest_line } => write!(f, "minified code ({} char line)", longest_line),
+            SkipReason::EncodedBlob => write!(f, "encoded data blob"),
+        }
+    }
+}
+
+/// Classify raw bytes: NUL bytes or invalid UTF-8 mean binary, otherwise as text
+pub fn classify_bytes(path: Option<&str>, bytes: &[u8]) -> Option<SkipReason> {
+    let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+    if sniff.contains(&0) {
+        return Some(SkipReason::Binary);
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => classify_text(path, text),
+        Err(_) => Some(SkipReason::Binary),
+    }
+}
+
+/// Classify text by path, header markers and line shape
+pub fn classify_text(path: Option<&str>, text: &str) -> Option<SkipReason> {
+    path.and_then(classify_path)
+        .or_else(|| generated_marker(text).map(|marker| SkipReason::Generated { marker }))
+        .or_else(|| classify_shape(text))
+}
+
+/// Classify by file name and directory alone
+pub fn classify_path(path: &str) -> Option<SkipReason> {
+    let path_ref = Path::new(path);
+    let file_name = path_ref.file_name().and_then(|name| name.to_str()).unwrap_or(path);
+    let extension = path_ref.extension().and_then(|ext| ext.to_str()).map(str::to_ascii_lowercase);
+
+    if extension.as_deref().is_some_and(|ext| BINARY_EXTENSIONS.contains(&ext)) {
+        return Some(SkipReason::Binary);
+    }
+    if LOCKFILES.contains(&file_name) {
+        return Some(SkipReason::Lockfile);
+    }
+    if let Some(directory) = path_ref
+        .components()
+        .filter_map(|component| component.as_os_str().to_str())
+        .find(|component| VENDOR_DIRECTORIES.contains(component))
+    {
+        return Some(SkipReason::Vendored { directory: directory.to_string() });
+    }
+    if file_name.contains(".min.") {
+        return Some(SkipReason::Minified { longest_line: 0 });
+    }
+    GENERATED_SUFFIXES
+        .iter()
+        .find(|suffix| file_name.ends_with(*suffix))
+        .map(|suffix| SkipReason::Generated { marker: format!("*{}", suffix) })
+}
+
+/// Generated-code marker in the file header, e.g. `@generated` or `Code generated ... DO NOT EDIT`
+pub fn generated_marker(text: &str) -> Option<String> {
+    let mut end = text.len().min(HEADER_SCAN_BYTES);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    text[..end].lines().find_map(|line| {
+        let lower = line.to_ascii_lowercase();
+        if lower.contains("@generated") {
+            Some("@generated".to_string())
+        } else if lower.contains("do not edit") && lower.contains("generated") {
+            Some("DO NOT EDIT".to_string())
+        } else if lower.contains("auto-generated") || lower.contains("autogenerated") {
+            Some("auto-generated".to_string())
+        } else if lower.contains("automatically generated") {
+            Some("automatically generated".to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// Minified code and encoded blobs: most of the text sits on very long lines
+fn classify_shape(text: &str) -> Option<SkipReason> {
+    let longest = text.lines().max_by_key(|line| line.len())?;
+    if longest.len() < MINIFIED_LINE_CHARS {
+        return None;
+    }
+
+    let long_line_bytes: usize = text
+        .lines()
+        .map(str::len)
+        .filter(|&len| len >= MINIFIED_LINE_CHARS)
+        .sum();
+    if long_line_bytes * 2 < text.len() {
+        return None;
+    }
+
+    let encoded = longest
+        .bytes()
+        .filter(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'=' | b'-' | b'_'))
+        .count();
+    if encoded as f64 >= longest.len() as f64 * ENCODED_BLOB_RATIO {
+        Some(SkipReason::EncodedBlob)
+    } else {
+        Some(SkipReason::Minified { longest_line: longest.len() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_and_marker_classification() {
+        assert_eq!(classify_text(Some("assets/logo.png"), "https://example.com/logo.png"), Some(SkipReason::Binary));
+        assert_eq!(classify_text(Some("web/Cargo.lock"), "[[package]]"), Some(SkipReason::Lockfile));
+        assert_eq!(
+            classify_text(Some("web/node_modules/x/index.js"), "module.exports = 1;"),
+            Some(SkipReason::Vendored { directory: "node_modules".to_string() })
+        );
+        assert_eq!(
+            classify_text(Some("api/types.go"), "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n"),
+            Some(SkipReason::Generated { marker: "DO NOT EDIT".to_string() })
+        );
+        assert_eq!(
+            classify_text(None, "// @generated by build.rs\nfn main() {}\n"),
+            Some(SkipReason::Generated { marker: "@generated".to_string() })
+        );
+        assert_eq!(classify_text(Some("src/main.rs"), "fn main() {\n    println!(\"hi\");\n}\n"), None);
+    }
+
+    #[test]
+    fn test_shape_detects_minified_code_and_blobs() {
+        let minified = "var a=function(b){return b+1};".repeat(100);
+        assert!(matches!(classify_text(Some("app.js"), &minified), Some(SkipReason::Minified { .. })));
+
+        let blob = format!("const DATA = \"\n{}\n\";", "QUJDRA==".repeat(500));
+        assert_eq!(classify_text(Some("data.ts"), &blob), Some(SkipReason::EncodedBlob));
+
+        let mostly_code = format!("{}\n{}", "fn a() {}\n".repeat(300), "x".repeat(MINIFIED_LINE_CHARS));
+        assert_eq!(classify_text(Some("lib.rs"), &mostly_code), None);
+
+        assert_eq!(classify_bytes(None, b"\x7fELF\x00\x01"), Some(SkipReason::Binary));
+        assert_eq!(classify_bytes(None, &[0xff, 0xfe, b'a']), Some(SkipReason::Binary));
+    }
+}
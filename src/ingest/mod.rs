@@ -0,0 +1,18 @@
+//! Input ingestion: turning raw inputs into per-file sources
+//!
+//! Contracts:
+//! - Every ingested source keeps the path of the file it came from
+//! - Concatenated repository dumps are split before chunking, so no chunk
+//!   crosses a file boundary
+//! - Binary, generated, vendored and minified sources are classified and
+//!   skipped with a reason instead of being summarized
+//! - Directory and glob inputs honour `.gitignore`/`.ignore` and yield files
+//!   sorted by path, whatever order the parallel walk found them in
+
+pub mod classify;
+pub mod repo_dump;
+pub mod walk;
+
+pub use classify::{classify_bytes, classify_path, classify_text, SkipReason};
+pub use repo_dump::{split_repo_dump, DumpFormat, DumpedFile};
+pub use walk::{is_glob, SourceWalker, WalkedSources};
@@ -0,0 +1,217 @@
+//! Repository-dump parsing: one text file holding many source files
+//!
+//! Supported formats:
+//! - Separated headers: `/path/to/file:` between two lines of 80 dashes,
+//!   optionally numbered `N | content` lines, preceded by a tree listing
+//! - Head banners: `==> path/to/file <==` as printed by `head`/`tail` on many files
+//!
+//! Contracts:
+//! - Returns `None` for text that is not a recognised dump
+//! - Paths are repository-relative (leading `/` stripped)
+//! - Line-number gutters are removed; file content is otherwise unchanged
+//! - Separator blank lines between files are not part of any file
+
+use crate::chunking::{detect_language, LanguageDetection, SourceLanguage};
+use crate::cozodb::record::{CodeRecord, FILE_PATH_KEY};
+
+/// Separator line used by the separated-headers format
+const DASH_SEPARATOR_WIDTH: usize = 80;
+
+/// Recognised repository-dump layouts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    SeparatedHeaders,
+    HeadBanners,
+}
+
+/// One source file, extracted from a repository dump or found by `SourceWalker`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DumpedFile {
+    pub path: String,
+    pub content: String,
+}
+
+impl DumpedFile {
+    /// Language detected from the file path and content
+    pub fn language(&self) -> SourceLanguage {
+        self.detect_language().language
+    }
+
+    pub fn detect_language(&self) -> LanguageDetection {
+        detect_language(Some(&self.path), &self.content)
+    }
+
+    /// Code record keyed by path, with the path stored under `FILE_PATH_KEY`
+    /// and the detected language's confidence under `LANGUAGE_CONFIDENCE_KEY`
+    pub fn into_record(self) -> CodeRecord {
+        let mut record = CodeRecord::new(self.path.clone(), self.content, "");
+        record.update_metadata(FILE_PATH_KEY, serde_json::Value::String(self.path));
+        record.detect_language();
+        record
+    }
+}
+
+/// Detect which dump layout `text` uses, if any
+pub fn detect_format(text: &str) -> Option<DumpFormat> {
+    let lines: Vec<&str> = text.lines().collect();
+
+    if (0..lines.len()).any(|index| separated_header_path(&lines, index).is_some()) {
+        Some(DumpFormat::SeparatedHeaders)
+    } else if lines.iter().any(|line| head_banner_path(line).is_some()) {
+        Some(DumpFormat::HeadBanners)
+    } else {
+        None
+    }
+}
+
+/// Split a repository dump into its files, or `None` if `text` is not a dump
+pub fn split_repo_dump(text: &str) -> Option<Vec<DumpedFile>> {
+    let lines: Vec<&str> = text.lines().collect();
+
+    let files = match detect_format(text)? {
+        DumpFormat::SeparatedHeaders => split_separated_headers(&lines),
+        DumpFormat::HeadBanners => split_head_banners(&lines),
+    };
+
+    (!files.is_empty()).then_some(files)
+}
+
+fn is_dash_separator(line: &str) -> bool {
+    line.len() == DASH_SEPARATOR_WIDTH && line.bytes().all(|b| b == b'-')
+}
+
+/// Path of a `/path:` header line that is followed by a dash separator
+fn separated_header_path<'a>(lines: &[&'a str], index: usize) -> Option<&'a str> {
+    let line = lines[index];
+    let next_is_separator = lines.get(index + 1).is_some_and(|next| is_dash_separator(next));
+
+    line.strip_prefix('/')
+        .and_then(|rest| rest.strip_suffix(':'))
+        .filter(|path| !path.is_empty() && next_is_separator)
+}
+
+fn head_banner_path(line: &str) -> Option<&str> {
+    line.strip_prefix("==> ")
+        .and_then(|rest| rest.strip_suffix(" <=="))
+        .filter(|path| !path.is_empty())
+}
+
+fn split_separated_headers(lines: &[&str]) -> Vec<DumpedFile> {
+    let headers: Vec<(usize, &str)> = (0..lines.len())
+        .filter_map(|index| separated_header_path(lines, index).map(|path| (index, path)))
+        .collect();
+
+    headers
+        .iter()
+        .enumerate()
+        .map(|(i, &(header, path))| {
+            let body_start = header + 2;
+            let body_end = headers.get(i + 1).map_or(lines.len(), |&(next, _)| next);
+            let mut body = &lines[body_start.min(body_end)..body_end];
+
+            // Drop the closing separator and the blank lines around it
+            while let Some((last, rest)) = body.split_last() {
+                if last.trim().is_empty() || is_dash_separator(last) {
+                    body = rest;
+                } else {
+                    break;
+                }
+            }
+
+            DumpedFile {
+                path: path.to_string(),
+                content: strip_line_numbers(body),
+            }
+        })
+        .collect()
+}
+
+fn split_head_banners(lines: &[&str]) -> Vec<DumpedFile> {
+    let banners: Vec<(usize, &str)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| head_banner_path(line).map(|path| (index, path)))
+        .collect();
+
+    banners
+        .iter()
+        .enumerate()
+        .map(|(i, &(banner, path))| {
+            let body_end = banners.get(i + 1).map_or(lines.len(), |&(next, _)| next);
+            let mut body = &lines[banner + 1..body_end];
+
+            // `head` separates files with a single blank line
+            if i + 1 < banners.len() {
+                if let Some((last, rest)) = body.split_last() {
+                    if last.is_empty() {
+                        body = rest;
+                    }
+                }
+            }
+
+            DumpedFile {
+                path: path.trim_start_matches('/').to_string(),
+                content: body.join("\n"),
+            }
+        })
+        .collect()
+}
+
+/// Remove `N | ` gutters when every line of the body carries one
+fn strip_line_numbers(body: &[&str]) -> String {
+    let stripped: Option<Vec<&str>> = body.iter().map(|line| strip_line_number(line)).collect();
+
+    match stripped {
+        Some(lines) if !lines.is_empty() => lines.join("\n"),
+        _ => body.join("\n"),
+    }
+}
+
+fn strip_line_number(line: &str) -> Option<&str> {
+    let (number, content) = line.split_once(" |")?;
+    let number = number.trim_start();
+    if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    // The gutter is "N | content"; empty lines are printed as "N | " or "N |"
+    Some(content.strip_prefix(' ').unwrap_or(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_separated_headers_fixture_splits_per_file() {
+        let dump = std::fs::read_to_string("tests/fixtures/iggy_apache.txt").unwrap();
+
+        let files = split_repo_dump(&dump).unwrap();
+
+        assert_eq!(files.len(), 275);
+        assert_eq!(files[0].path, ".convco");
+        assert!(files[0].content.starts_with("scopeRegex: "));
+
+        let dockerignore = files.iter().find(|file| file.path == ".dockerignore").unwrap();
+        assert!(dockerignore.content.starts_with(".config\n.dockerignore"));
+
+        // Binary assets are dumped as a single URL line without a gutter
+        let image = files.iter().find(|file| file.path == "assets/bench.png").unwrap();
+        assert!(image.content.starts_with("https://"));
+        assert!(!image.content.contains('\n'));
+        assert!(files.iter().all(|file| !file.content.contains(&"-".repeat(DASH_SEPARATOR_WIDTH))));
+    }
+
+    #[test]
+    fn test_head_banners_and_plain_text() {
+        let dump = "==> src/lib.rs <==\npub mod a;\n\n==> src/a.py <==\ndef f():\n    pass\n";
+
+        let files = split_repo_dump(dump).unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0], DumpedFile { path: "src/lib.rs".into(), content: "pub mod a;".into() });
+        assert_eq!(files[1].language(), SourceLanguage::Python);
+        assert_eq!(files[1].clone().into_record().file_path(), Some("src/a.py"));
+
+        assert!(split_repo_dump("fn main() {}\n").is_none());
+    }
+}
@@ -0,0 +1,295 @@
+//! Directory and glob inputs: many source files from one argument
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use globset::{Glob, GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
+use ignore::{WalkBuilder, WalkState};
+
+use super::classify::{classify_bytes, SkipReason};
+use super::repo_dump::DumpedFile;
+use crate::errors::{ProcessingError, Result};
+
+/// Files found under the inputs, sorted by path
+#[derive(Debug, Default)]
+pub struct WalkedSources {
+    pub files: Vec<DumpedFile>,
+    /// Files that could not be read as text
+    pub skipped: Vec<(String, SkipReason)>,
+}
+
+/// Whether `input` is a glob pattern rather than a path
+pub fn is_glob(input: &str) -> bool {
+    input.contains(['*', '?', '[', '{'])
+}
+
+/// Walks files, directories and glob patterns in parallel
+///
+/// Directories honour `.gitignore`, `.ignore` and global git excludes (also
+/// outside a git checkout) and skip hidden entries. Include and exclude
+/// patterns without a `/` match the file name; others match the path below
+/// the walked directory. Files named directly are always read.
+pub struct SourceWalker {
+    includes: Vec<String>,
+    excludes: Vec<String>,
+    threads: usize,
+}
+
+impl SourceWalker {
+    pub fn new() -> Self {
+        Self {
+            includes: Vec::new(),
+            excludes: Vec::new(),
+            threads: 0,
+        }
+    }
+
+    /// Only keep files matching at least one of `patterns`
+    pub fn with_includes(mut self, patterns: Vec<String>) -> Self {
+        self.includes = patterns;
+        self
+    }
+
+    /// Drop files matching any of `patterns`
+    pub fn with_excludes(mut self, patterns: Vec<String>) -> Self {
+        self.excludes = patterns;
+        self
+    }
+
+    /// Walker threads (0 = chosen by the walker)
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Read every file named by, or found under, `input`
+    ///
+    /// # Errors
+    /// * `ProcessingError::InvalidPattern` - a glob or include/exclude pattern does not parse
+    /// * `ProcessingError::Io` - `input` is neither a path nor a glob, or a directory cannot be walked
+    pub fn walk(&self, input: &str) -> Result<WalkedSources> {
+        let includes = PathFilter::new(&self.includes)?;
+        let excludes = PathFilter::new(&self.excludes)?;
+
+        let path = Path::new(input);
+        if path.is_file() {
+            let mut sources = WalkedSources::default();
+            read_into(path, &mut sources);
+            return Ok(sources);
+        }
+
+        let (root, pattern) = if path.is_dir() {
+            (path.to_path_buf(), None)
+        } else if is_glob(input) {
+            let matcher = GlobBuilder::new(input)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| invalid_pattern(input, e))?
+                .compile_matcher();
+            (glob_root(input), Some(matcher))
+        } else {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("no such file or directory: {}", input)).into());
+        };
+
+        let found = Mutex::new(WalkedSources::default());
+        let walk_error = Mutex::new(None);
+        let (found_ref, error_ref, root_ref, pattern_ref, includes_ref, excludes_ref) =
+            (&found, &walk_error, &root, &pattern, &includes, &excludes);
+        WalkBuilder::new(&root)
+            .require_git(false)
+            .threads(self.threads)
+            .build_parallel()
+            .run(|| {
+                Box::new(move |entry| {
+                    let entry = match entry {
+                        Ok(entry) => entry,
+                        Err(e) => {
+                            error_ref.lock().unwrap().get_or_insert(e);
+                            return WalkState::Quit;
+                        }
+                    };
+                    if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+                        return WalkState::Continue;
+                    }
+
+                    let relative = entry.path().strip_prefix(root_ref).unwrap_or(entry.path());
+                    let wanted = pattern_ref.as_ref().is_none_or(|glob| matches_glob(glob, entry.path()))
+                        && (includes_ref.is_empty() || includes_ref.matches(relative))
+                        && !excludes_ref.matches(relative);
+                    if wanted {
+                        let mut sources = WalkedSources::default();
+                        read_into(entry.path(), &mut sources);
+                        let mut found = found_ref.lock().unwrap();
+                        found.files.append(&mut sources.files);
+                        found.skipped.append(&mut sources.skipped);
+                    }
+                    WalkState::Continue
+                })
+            });
+
+        if let Some(e) = walk_error.into_inner().unwrap() {
+            return Err(std::io::Error::other(e).into());
+        }
+        let mut sources = found.into_inner().unwrap();
+        sources.files.sort_by(|a, b| a.path.cmp(&b.path));
+        sources.skipped.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(sources)
+    }
+}
+
+impl Default for SourceWalker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Read `path` as text, or record why it was skipped
+fn read_into(path: &Path, sources: &mut WalkedSources) {
+    let display = display_path(path);
+    match std::fs::read(path) {
+        Ok(bytes) => match String::from_utf8(bytes) {
+            Ok(content) => sources.files.push(DumpedFile { path: display, content }),
+            Err(e) => {
+                let reason = classify_bytes(Some(&display), e.as_bytes()).unwrap_or(SkipReason::Binary);
+                sources.skipped.push((display, reason));
+            }
+        },
+        Err(e) => log::warn!("⚠️ Cannot read {}: {}", display, e),
+    }
+}
+
+/// Path as shown in output: `./src/a.rs` becomes `src/a.rs`
+fn display_path(path: &Path) -> String {
+    let cleaned: PathBuf = path.components().filter(|component| *component != Component::CurDir).collect();
+    cleaned.display().to_string()
+}
+
+/// Deepest directory of `pattern` without glob characters: `src/**/*.rs` -> `src`
+fn glob_root(pattern: &str) -> PathBuf {
+    let literal: PathBuf = Path::new(pattern)
+        .components()
+        .take_while(|component| !is_glob(&component.as_os_str().to_string_lossy()))
+        .collect();
+    if literal.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        literal
+    }
+}
+
+/// Glob match ignoring a leading `./` on either side
+fn matches_glob(glob: &GlobMatcher, path: &Path) -> bool {
+    glob.is_match(path) || glob.is_match(display_path(path))
+}
+
+fn invalid_pattern(pattern: &str, error: globset::Error) -> ProcessingError {
+    ProcessingError::InvalidPattern {
+        pattern: pattern.to_string(),
+        reason: error.kind().to_string(),
+    }
+}
+
+/// Include/exclude patterns, gitignore-style: `*.rs` matches at any depth
+struct PathFilter {
+    by_name: GlobSet,
+    by_path: GlobSet,
+    empty: bool,
+}
+
+impl PathFilter {
+    fn new(patterns: &[String]) -> Result<Self> {
+        let mut by_name = GlobSetBuilder::new();
+        let mut by_path = GlobSetBuilder::new();
+        for pattern in patterns {
+            let glob = Glob::new(pattern.trim_start_matches('/')).map_err(|e| invalid_pattern(pattern, e))?;
+            if pattern.contains('/') {
+                by_path.add(glob);
+            } else {
+                by_name.add(glob);
+            }
+        }
+        let build = |builder: GlobSetBuilder| builder.build().map_err(|e| invalid_pattern(&patterns.join(","), e));
+        Ok(Self {
+            by_name: build(by_name)?,
+            by_path: build(by_path)?,
+            empty: patterns.is_empty(),
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.empty
+    }
+
+    fn matches(&self, relative: &Path) -> bool {
+        relative.file_name().is_some_and(|name| self.by_name.is_match(name)) || self.by_path.is_match(relative)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in [
+            ("src/lib.rs", "pub mod a;\n"),
+            ("src/a.rs", "pub fn a() {}\n"),
+            ("src/a_test.rs", "#[test]\nfn t() {}\n"),
+            ("src/gen/out.rs", "// generated\n"),
+            ("scripts/run.py", "print('x')\n"),
+            (".hidden/x.rs", "fn x() {}\n"),
+        ] {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        std::fs::write(dir.path().join(".gitignore"), "src/gen/\n").unwrap();
+        std::fs::write(dir.path().join("logo.bin"), [0xff, 0xfe, 0x00]).unwrap();
+        dir
+    }
+
+    fn names(sources: &WalkedSources, root: &Path) -> Vec<String> {
+        let root = display_path(root);
+        sources.files.iter().map(|file| file.path.trim_start_matches(&root).trim_start_matches('/').to_string()).collect()
+    }
+
+    #[test]
+    fn test_directory_walk_honours_ignore_files_and_filters() {
+        let dir = tree();
+        let root = dir.path().to_str().unwrap();
+
+        let all = SourceWalker::new().walk(root).unwrap();
+        assert_eq!(names(&all, dir.path()), vec!["scripts/run.py", "src/a.rs", "src/a_test.rs", "src/lib.rs"]);
+        assert_eq!(all.skipped.len(), 1);
+        assert_eq!(all.skipped[0].1, SkipReason::Binary);
+
+        let filtered = SourceWalker::new()
+            .with_includes(vec!["*.rs".to_string()])
+            .with_excludes(vec!["*_test.rs".to_string()])
+            .with_threads(2)
+            .walk(root)
+            .unwrap();
+        assert_eq!(names(&filtered, dir.path()), vec!["src/a.rs", "src/lib.rs"]);
+    }
+
+    #[test]
+    fn test_glob_and_single_file_inputs() {
+        let dir = tree();
+        let pattern = format!("{}/src/**/*.rs", dir.path().display());
+        assert_eq!(glob_root("src/**/*.rs"), PathBuf::from("src"));
+        assert_eq!(glob_root("*.py"), PathBuf::from("."));
+
+        let globbed = SourceWalker::new().walk(&pattern).unwrap();
+        assert_eq!(names(&globbed, dir.path()), vec!["src/a.rs", "src/a_test.rs", "src/lib.rs"]);
+
+        let single = SourceWalker::new().walk(dir.path().join("scripts/run.py").to_str().unwrap()).unwrap();
+        assert_eq!(single.files.len(), 1);
+        assert_eq!(single.files[0].content, "print('x')\n");
+
+        assert!(SourceWalker::new().walk(&dir.path().join("missing").display().to_string()).is_err());
+        assert!(matches!(
+            SourceWalker::new().with_includes(vec!["[".to_string()]).walk(dir.path().to_str().unwrap()),
+            Err(ProcessingError::InvalidPattern { .. })
+        ));
+    }
+}
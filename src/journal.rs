@@ -0,0 +1,250 @@
+//! On-disk run journal: finished chunks are recorded as they complete so an
+//! interrupted run can resume without redoing them
+//!
+//! Contracts:
+//! - One JSON object per line, appended and flushed as each chunk finishes
+//! - Every entry carries the config hash of the run that wrote it; resuming
+//!   only reuses entries whose chunk ID and config hash both match
+//! - Only clean completions are recorded: truncated, cancelled and failed
+//!   chunks are left out so a resumed run retries them
+//! - Lines that do not parse (e.g. cut short by a kill) are skipped, never fatal
+//! - Opening without resume starts the journal over
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use log::warn;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::chunking::ChunkId;
+use crate::errors::Result;
+
+/// A finished chunk as recorded in the journal
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    pub summary: String,
+    /// Per-task outputs as (task name, output), in task order
+    pub tasks: Vec<(String, String)>,
+    pub elapsed: Option<Duration>,
+    /// Why the summary is partial or an error report; such entries are never recorded
+    pub failure: Option<String>,
+}
+
+/// Append-only record of the chunks a run has finished
+#[derive(Debug)]
+pub struct RunJournal {
+    path: PathBuf,
+    config_hash: String,
+    done: HashMap<String, JournalEntry>,
+    file: File,
+}
+
+impl RunJournal {
+    /// Open the journal at `path` for a run with `config_hash`
+    ///
+    /// With `resume`, entries already recorded under the same hash are loaded
+    /// and kept; otherwise any existing journal is truncated.
+    pub fn open(path: impl Into<PathBuf>, config_hash: impl Into<String>, resume: bool) -> Result<Self> {
+        let path = path.into();
+        let config_hash = config_hash.into();
+        let done = if resume {
+            recorded(&path, &config_hash)?
+        } else {
+            HashMap::new()
+        };
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(resume)
+            .write(true)
+            .truncate(!resume)
+            .open(&path)?;
+        // A line cut short by a kill must not swallow the next entry
+        let len = file.metadata()?.len();
+        if len > 0 && std::fs::read(&path)?.last() != Some(&b'\n') {
+            writeln!(file)?;
+        }
+        Ok(Self { path, config_hash, done, file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn config_hash(&self) -> &str {
+        &self.config_hash
+    }
+
+    /// The recorded entry for `id`, if this configuration already finished it
+    pub fn get(&self, id: &ChunkId) -> Option<&JournalEntry> {
+        self.done.get(id.as_str())
+    }
+
+    /// Number of chunks finished under this configuration
+    pub fn len(&self) -> usize {
+        self.done.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.done.is_empty()
+    }
+
+    /// Append `entry` for `id` and flush it to disk
+    ///
+    /// Entries with a failure are dropped, so a resumed run retries the chunk;
+    /// returns whether the entry was recorded.
+    pub fn record(&mut self, id: &ChunkId, entry: JournalEntry) -> Result<bool> {
+        if let Some(failure) = &entry.failure {
+            warn!("⚠️ Not journaling chunk {}: {}", id, failure);
+            return Ok(false);
+        }
+        let mut line = json!({
+            "config_hash": self.config_hash,
+            "chunk_id": id.as_str(),
+            "summary": entry.summary,
+        });
+        if !entry.tasks.is_empty() {
+            line["tasks"] = Value::Array(entry.tasks.iter().map(|(name, output)| json!([name, output])).collect());
+        }
+        if let Some(elapsed) = entry.elapsed {
+            line["elapsed_ms"] = json!(elapsed.as_millis() as u64);
+        }
+        writeln!(self.file, "{}", line)?;
+        self.file.flush()?;
+        self.done.insert(id.as_str().to_string(), entry);
+        Ok(true)
+    }
+}
+
+/// SHA-256 over `parts`, as hex; any part changing changes the hash
+pub fn config_hash<S: AsRef<str>>(parts: &[S]) -> String {
+    let mut hasher = Sha256::new();
+    // Length prefixes keep ("ab", "c") and ("a", "bc") apart
+    for part in parts {
+        hasher.update((part.as_ref().len() as u64).to_le_bytes());
+        hasher.update(part.as_ref().as_bytes());
+    }
+    hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Entries of the journal at `path` recorded under `config_hash`, by chunk ID,
+/// later lines winning; a missing journal has none. Reads without modifying it.
+pub fn recorded(path: &Path, config_hash: &str) -> Result<HashMap<String, JournalEntry>> {
+    let mut done = HashMap::new();
+    if !path.exists() {
+        return Ok(done);
+    }
+    for (number, line) in std::fs::read_to_string(path)?.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            warn!("⚠️ Skipping unreadable journal line {} in {}", number + 1, path.display());
+            continue;
+        };
+        if value["config_hash"].as_str() != Some(config_hash) {
+            continue;
+        }
+        let (Some(chunk_id), Some(summary)) = (value["chunk_id"].as_str(), value["summary"].as_str()) else {
+            warn!("⚠️ Skipping incomplete journal line {} in {}", number + 1, path.display());
+            continue;
+        };
+        let tasks = value["tasks"]
+            .as_array()
+            .map(|tasks| {
+                tasks
+                    .iter()
+                    .filter_map(|task| Some((task[0].as_str()?.to_string(), task[1].as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let elapsed = value["elapsed_ms"].as_u64().map(Duration::from_millis);
+        done.insert(chunk_id.to_string(), JournalEntry { summary: summary.to_string(), tasks, elapsed, failure: None });
+    }
+    Ok(done)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunking::Chunk;
+
+    fn journal_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("dobby-journal-{}-{}.jsonl", name, std::process::id()))
+    }
+
+    fn entry(summary: &str) -> JournalEntry {
+        JournalEntry { summary: summary.to_string(), tasks: Vec::new(), elapsed: Some(Duration::from_millis(40)), failure: None }
+    }
+
+    fn id(content: &str) -> ChunkId {
+        Chunk::new(0, 10, content.to_string()).id()
+    }
+
+    #[test]
+    fn test_resume_after_cancelled_run_retries_unfinished_chunks() {
+        let path = journal_path("cancelled");
+        let hash = config_hash(&["model", "prompt"]);
+
+        // Ctrl-C: one chunk finished, the rest came back cancelled or failed
+        let mut journal = RunJournal::open(&path, hash.clone(), false).unwrap();
+        assert!(journal.record(&id("fn a() {}"), entry("Defines a")).unwrap());
+        let cancelled = JournalEntry { failure: Some("truncated: cancelled".to_string()), ..entry("Defines b [TRUNCATED: cancelled]") };
+        assert!(!journal.record(&id("fn b() {}"), cancelled).unwrap());
+        let failed = JournalEntry { failure: Some("model error".to_string()), ..entry("ERROR: Failed to process chunk - model error") };
+        assert!(!journal.record(&id("fn c() {}"), failed).unwrap());
+        drop(journal);
+
+        let resumed = RunJournal::open(&path, hash, true).unwrap();
+        assert_eq!(resumed.len(), 1);
+        assert_eq!(resumed.get(&id("fn a() {}")), Some(&entry("Defines a")));
+        assert_eq!(resumed.get(&id("fn b() {}")), None);
+        assert_eq!(resumed.get(&id("fn c() {}")), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_resume_with_another_config_hash_discards_the_journal() {
+        let path = journal_path("mismatch");
+        let mut journal = RunJournal::open(&path, config_hash(&["model", "prompt"]), false).unwrap();
+        journal.record(&id("fn a() {}"), entry("Defines a")).unwrap();
+        drop(journal);
+
+        // Any changed part, e.g. an output schema added, changes the hash
+        let changed = config_hash(&["model", "prompt", "schema"]);
+        assert_ne!(changed, config_hash(&["model", "prompt"]));
+        assert!(RunJournal::open(&path, changed.clone(), true).unwrap().is_empty());
+        assert!(recorded(&path, &changed).unwrap().is_empty());
+        // The old configuration's entries are still there for it
+        assert_eq!(recorded(&path, &config_hash(&["model", "prompt"])).unwrap().len(), 1);
+
+        let fresh = RunJournal::open(&path, config_hash(&["model", "prompt"]), false).unwrap();
+        assert!(fresh.is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_torn_trailing_line_is_skipped_and_not_glued_to_the_next_entry() {
+        let path = journal_path("torn");
+        let hash = config_hash(&["model", "prompt"]);
+        let mut journal = RunJournal::open(&path, hash.clone(), false).unwrap();
+        journal.record(&id("fn a() {}"), entry("Defines a")).unwrap();
+        drop(journal);
+        // A kill mid-write leaves a partial last line without its newline
+        let torn = format!("{{\"config_hash\": \"{}\", \"chunk_id\": \"{}\", \"summ", hash, id("fn b() {}"));
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(torn.as_bytes()).unwrap();
+
+        let mut resumed = RunJournal::open(&path, hash.clone(), true).unwrap();
+        assert_eq!(resumed.len(), 1);
+        assert_eq!(resumed.get(&id("fn b() {}")), None);
+        resumed.record(&id("fn b() {}"), entry("Defines b")).unwrap();
+        drop(resumed);
+
+        let reread = recorded(&path, &hash).unwrap();
+        assert_eq!(reread.len(), 2);
+        assert_eq!(reread.get(id("fn b() {}").as_str()), Some(&entry("Defines b")));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -30,6 +30,7 @@ use std::fmt::Debug;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use super::error::{DatabaseError, DobbyError};
+use crate::ingest::SkipReason;
 
 /// Strongly-typed database identifier
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -322,6 +323,16 @@ pub enum ProcessingState {
     },
 }
 
+impl ProcessingState {
+    /// `Skipped` as of now, with a human-readable reason
+    pub fn skipped(reason: &SkipReason) -> Self {
+        ProcessingState::Skipped {
+            skipped_at: Utc::now(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct SummaryId(pub uuid::Uuid);
 
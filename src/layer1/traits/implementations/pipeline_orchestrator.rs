@@ -9,6 +9,11 @@ use crate::layer1::traits::error::*;
 use crate::layer1::traits::implementations::inference_engine::TraitInferenceEngine;
 use crate::layer1::traits::implementations::database::MockDatabaseConnection;
 use crate::layer1::traits::database::{DatabaseHealth, DegradationImpact};
+use crate::config::LayeredConfig;
+use crate::chunking::{detect_language, CharacterChunker, Chunk, Chunker, LineChunker, StructuralChunker, Symbol, TokenBudgetChunker};
+use crate::ingest::{classify_text, split_repo_dump};
+use crate::structured::OutputSchema;
+use crate::summarization::{check_grounding, GroundingReport};
 use async_trait::async_trait;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -22,6 +27,8 @@ pub struct PipelineOrchestrator {
     database_provider: Arc<dyn DatabaseProvider<Error = DatabaseError, Connection = MockDatabaseConnection>>,
     config: PipelineConfig,
     semaphore: Arc<Semaphore>,
+    tokenizer: Option<Arc<tokenizers::Tokenizer>>,
+    chunker: Option<Arc<dyn Chunker>>,
 }
 
 /// Pipeline configuration with performance and resource limits
@@ -57,12 +64,14 @@ pub struct MonitoringConfig {
     pub enable_tracing: bool,
 }
 
-impl Default for PipelineConfig {
-    fn default() -> Self {
+impl PipelineConfig {
+    /// Concurrency, chunk size and the job deadline resolved by `LayeredConfig`
+    /// (`run.agent_count`, `chunking.loc`, `run.max_job_time_s`)
+    pub fn from_settings(settings: &LayeredConfig) -> Self {
         Self {
-            max_concurrent_jobs: 10,
-            chunk_size: 1000,
-            max_processing_time: Duration::from_secs(300), // 5 minutes
+            max_concurrent_jobs: settings.usize("run.agent_count").unwrap_or(10),
+            chunk_size: settings.usize("chunking.loc").unwrap_or(1000),
+            max_processing_time: settings.job_time_limit(),
             retry_config: RetryConfig {
                 max_retries: 3,
                 base_delay: Duration::from_millis(100),
@@ -83,6 +92,12 @@ impl Default for PipelineConfig {
     }
 }
 
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self::from_settings(&LayeredConfig::defaults())
+    }
+}
+
 /// Pipeline job representing a code summarization task
 #[derive(Debug, Clone)]
 pub struct PipelineJob {
@@ -106,7 +121,9 @@ pub enum JobInput {
 pub struct JobOptions {
     pub chunking_strategy: ChunkingStrategy,
     pub inference_config: InferenceParams,
-    pub output_format: OutputFormat,
+    /// Shape each summary into JSON valid against the schema after inference;
+    /// the trait engine has no constrained decoding
+    pub output_schema: Option<OutputSchema>,
     pub include_metadata: bool,
 }
 
@@ -117,14 +134,6 @@ pub enum ChunkingStrategy {
     Semantic { max_tokens: usize },
 }
 
-#[derive(Debug, Clone)]
-pub enum OutputFormat {
-    PlainText,
-    Markdown,
-    Json,
-    Structured,
-}
-
 /// Pipeline job result with comprehensive metadata
 #[derive(Debug, Clone)]
 pub struct PipelineJobResult {
@@ -137,6 +146,16 @@ pub struct PipelineJobResult {
     pub metadata: JobMetadata,
 }
 
+impl PipelineJobResult {
+    /// Summaries of the chunks defining `symbol`, e.g. `Foo::bar` or just `bar`
+    pub fn summaries_defining(&self, symbol: &str) -> Vec<&ChunkSummary> {
+        self.summaries
+            .iter()
+            .filter(|summary| summary.metadata.symbols.iter().any(|defined| defined.matches(symbol)))
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum JobStatus {
     Pending,
@@ -154,6 +173,10 @@ pub struct ChunkSummary {
     pub token_count: usize,
     pub processing_time: Duration,
     pub metadata: ChunkMetadata,
+    /// Schema-shaped summary when the job set `JobOptions::output_schema`
+    pub structured: Option<serde_json::Value>,
+    /// Identifiers in the summary checked against the chunk's tokens and symbols
+    pub grounding: GroundingReport,
 }
 
 #[derive(Debug, Clone)]
@@ -162,6 +185,8 @@ pub struct ChunkMetadata {
     pub end_line: Option<usize>,
     pub file_path: Option<String>,
     pub chunk_type: String,
+    /// Items defined in the chunk, with kinds and lines
+    pub symbols: Vec<Symbol>,
 }
 
 #[derive(Debug, Clone)]
@@ -171,6 +196,14 @@ pub struct JobMetadata {
     pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
     pub worker_id: Option<String>,
     pub performance_metrics: PerformanceMetrics,
+    pub skipped_inputs: Vec<SkippedInput>,
+}
+
+/// Source left out of summarization, recorded as `ProcessingState::Skipped`
+#[derive(Debug, Clone)]
+pub struct SkippedInput {
+    pub file_path: Option<String>,
+    pub state: ProcessingState,
 }
 
 #[derive(Debug, Clone)]
@@ -196,9 +229,23 @@ impl PipelineOrchestrator {
             database_provider,
             semaphore: Arc::new(Semaphore::new(config.max_concurrent_jobs)),
             config,
+            tokenizer: None,
+            chunker: None,
         }
     }
 
+    /// Measure `ChunkingStrategy::Semantic` budgets with the model's tokenizer
+    pub fn with_tokenizer(mut self, tokenizer: Arc<tokenizers::Tokenizer>) -> Self {
+        self.tokenizer = Some(tokenizer);
+        self
+    }
+
+    /// Chunk every job with `chunker`, overriding `JobOptions::chunking_strategy`
+    pub fn with_chunker(mut self, chunker: Arc<dyn Chunker>) -> Self {
+        self.chunker = Some(chunker);
+        self
+    }
+
     /// Process a single job
     pub async fn process_job(&self, job: PipelineJob) -> Result<PipelineJobResult, PipelineError> {
         let start_time = Instant::now();
@@ -225,6 +272,7 @@ impl PipelineOrchestrator {
                 total_database_time: Duration::ZERO,
                 memory_peak_mb: 0,
             },
+            skipped_inputs: Vec::new(),
         };
 
         // Extract content from job input
@@ -247,13 +295,40 @@ impl PipelineOrchestrator {
             }
         };
 
-        // Chunk the content
-        let chunks = self.chunk_content(&content, &job.options.chunking_strategy)
-            .map_err(|e| PipelineError::Configuration {
-                section: "chunking".to_string(),
-                field: "strategy".to_string(),
-                value: format!("{:?}", job.options.chunking_strategy),
-            })?;
+        // Repository dumps are split per file so no chunk crosses a file boundary
+        let input_path = match &job.input {
+            JobInput::File { path, .. } => Some(path.clone()),
+            JobInput::Text { filename, .. } => filename.clone(),
+            JobInput::Repository { .. } => None,
+        };
+        let sources: Vec<(Option<String>, String)> = match split_repo_dump(&content) {
+            Some(files) => files.into_iter().map(|file| (Some(file.path), file.content)).collect(),
+            None => vec![(input_path, content)],
+        };
+
+        // Chunk each source, following the structure of its language when known
+        let chunker = self.chunker_for(&job.options.chunking_strategy);
+        let mut chunks = Vec::new();
+        for (path, source) in &sources {
+            // Binary, generated and minified sources are recorded, not summarized
+            if let Some(reason) = classify_text(path.as_deref(), source) {
+                log::info!("⏭️ Skipped {}: {}", path.as_deref().unwrap_or("<input>"), reason);
+                metadata.skipped_inputs.push(SkippedInput {
+                    file_path: path.clone(),
+                    state: ProcessingState::skipped(&reason),
+                });
+                continue;
+            }
+
+            let detection = detect_language(path.as_deref(), source);
+            let file_chunks = chunker.chunk_detected(source, &detection, path.as_deref())
+                .map_err(|_| PipelineError::Configuration {
+                    section: "chunking".to_string(),
+                    field: "strategy".to_string(),
+                    value: chunker.name().to_string(),
+                })?;
+            chunks.extend(file_chunks);
+        }
 
         metadata.performance_metrics.total_chunks = chunks.len();
 
@@ -335,6 +410,7 @@ impl PipelineOrchestrator {
             let mut stream = Box::pin(content_stream);
             let mut chunk_buffer = String::new();
             let mut line_count = 0;
+            let mut line_start = 0;
 
             loop {
                 match stream.next().await {
@@ -345,12 +421,12 @@ impl PipelineOrchestrator {
 
                         // Process chunk when it reaches the configured size
                         if line_count >= orchestrator.config.chunk_size {
+                            let chunk = Chunk::new(line_start, line_start + line_count, chunk_buffer.clone());
                             let chunk_summary = orchestrator.process_single_chunk(
-                                &chunk_buffer,
+                                &chunk,
                                 &job.options,
                                 0,
                                 line_count,
-                                None,
                             ).await;
 
                             if tx.send(chunk_summary).await.is_err() {
@@ -358,18 +434,19 @@ impl PipelineOrchestrator {
                             }
 
                             chunk_buffer.clear();
+                            line_start += line_count;
                             line_count = 0;
                         }
                     }
                     None => {
                         // Process remaining content
                         if !chunk_buffer.is_empty() {
+                            let chunk = Chunk::new(line_start, line_start + line_count, chunk_buffer.clone());
                             let chunk_summary = orchestrator.process_single_chunk(
-                                &chunk_buffer,
+                                &chunk,
                                 &job.options,
                                 0,
                                 line_count,
-                                None,
                             ).await;
 
                             let _ = tx.send(chunk_summary).await;
@@ -438,44 +515,33 @@ impl PipelineOrchestrator {
 
     // Private helper methods
 
-    fn chunk_content(&self, content: &str, strategy: &ChunkingStrategy) -> Result<Vec<String>, PipelineError> {
-        match strategy {
-            ChunkingStrategy::Lines { max_lines } => {
-                let lines: Vec<&str> = content.lines().collect();
-                let mut chunks = Vec::new();
-
-                for chunk in lines.chunks(*max_lines) {
-                    chunks.push(chunk.join("\n"));
-                }
+    /// The configured chunker, or the built-in one for `strategy`
+    fn chunker_for(&self, strategy: &ChunkingStrategy) -> Arc<dyn Chunker> {
+        if let Some(chunker) = &self.chunker {
+            return Arc::clone(chunker);
+        }
 
-                Ok(chunks)
-            }
-            ChunkingStrategy::Characters { max_chars } => {
-                let mut chunks = Vec::new();
-                for chunk in content.as_bytes().chunks(*max_chars) {
-                    chunks.push(String::from_utf8_lossy(chunk).to_string());
-                }
-                Ok(chunks)
-            }
-            ChunkingStrategy::Semantic { max_tokens } => {
-                // Simplified semantic chunking - in practice would use sentence boundaries
-                let estimated_chars_per_token = 4;
-                let max_chars = max_tokens * estimated_chars_per_token;
-                self.chunk_content(content, &ChunkingStrategy::Characters { max_chars })
-            }
+        match strategy {
+            ChunkingStrategy::Lines { max_lines } => Arc::new(LineChunker::new(*max_lines)),
+            ChunkingStrategy::Characters { max_chars } => Arc::new(CharacterChunker::new(*max_chars)),
+            ChunkingStrategy::Semantic { max_tokens } => match &self.tokenizer {
+                Some(tokenizer) => Arc::new(TokenBudgetChunker::new(Arc::clone(tokenizer), *max_tokens)),
+                // Without a tokenizer: structural units packed by estimated tokens
+                None => Arc::new(StructuralChunker::by_estimated_tokens(*max_tokens)),
+            },
         }
     }
 
     async fn process_chunks_with_retry(
         &self,
-        chunks: &[String],
+        chunks: &[Chunk],
         options: &JobOptions,
     ) -> Result<Vec<ChunkSummary>, PipelineError> {
         let mut summaries = Vec::new();
         let mut failed_chunks = 0;
 
         for (i, chunk) in chunks.iter().enumerate() {
-            match self.process_single_chunk_with_retry(chunk, options, i, chunks.len(), None).await {
+            match self.process_single_chunk_with_retry(chunk, options, i, chunks.len()).await {
                 Ok(summary) => summaries.push(summary),
                 Err(e) => {
                     failed_chunks += 1;
@@ -496,16 +562,15 @@ impl PipelineOrchestrator {
 
     async fn process_single_chunk_with_retry(
         &self,
-        chunk: &str,
+        chunk: &Chunk,
         options: &JobOptions,
         chunk_index: usize,
         total_chunks: usize,
-        file_path: Option<String>,
     ) -> Result<ChunkSummary, PipelineError> {
         let mut last_error = None;
 
         for attempt in 0..=self.config.retry_config.max_retries {
-            match self.process_single_chunk(chunk, options, chunk_index, total_chunks, file_path.clone()).await {
+            match self.process_single_chunk(chunk, options, chunk_index, total_chunks).await {
                 Ok(summary) => return Ok(summary),
                 Err(e) => {
                     last_error = Some(e);
@@ -524,16 +589,15 @@ impl PipelineOrchestrator {
 
     async fn process_single_chunk(
         &self,
-        chunk: &str,
+        chunk: &Chunk,
         options: &JobOptions,
         chunk_index: usize,
         total_chunks: usize,
-        file_path: Option<String>,
     ) -> Result<ChunkSummary, PipelineError> {
         let start_time = Instant::now();
 
         // Perform inference
-        let inference_result = self.inference_engine.infer(chunk.to_string()).await
+        let inference_result = self.inference_engine.infer(chunk.content.clone()).await
             .map_err(|e| PipelineError::Inference {
                 model: "default".to_string(),
                 message: format!("Failed to process chunk {}: {}", chunk_index, e),
@@ -542,18 +606,34 @@ impl PipelineOrchestrator {
 
         let processing_time = start_time.elapsed();
 
+        let (summary, structured) = match &options.output_schema {
+            Some(schema) => {
+                let document = schema.constrain_text(&inference_result.content);
+                (document.to_string(), Some(document))
+            }
+            None => (inference_result.content, None),
+        };
+
+        let grounding = check_grounding(&summary, chunk);
+        if !grounding.ungrounded.is_empty() {
+            log::warn!("⚠️ Chunk {} names identifiers not in its code: {}", chunk_index, grounding.ungrounded.join(", "));
+        }
+
         Ok(ChunkSummary {
-            chunk_id: format!("chunk-{}", chunk_index),
-            summary: inference_result.content,
+            chunk_id: chunk.id().to_string(),
+            summary,
             confidence: inference_result.confidence,
             token_count: inference_result.token_count,
             processing_time,
             metadata: ChunkMetadata {
-                start_line: Some(chunk_index * 100), // Approximate
-                end_line: Some((chunk_index + 1) * 100),
-                file_path,
+                start_line: Some(chunk.line_start),
+                end_line: Some(chunk.line_end),
+                file_path: chunk.file_path.clone(),
                 chunk_type: "code".to_string(),
+                symbols: chunk.symbols.clone(),
             },
+            structured,
+            grounding,
         })
     }
 
@@ -603,6 +683,8 @@ impl Clone for PipelineOrchestrator {
             database_provider: Arc::clone(&self.database_provider),
             config: self.config.clone(),
             semaphore: Arc::clone(&self.semaphore),
+            tokenizer: self.tokenizer.clone(),
+            chunker: self.chunker.clone(),
         }
     }
 }
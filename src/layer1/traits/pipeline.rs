@@ -144,16 +144,6 @@ pub struct PipelineCheckpoint {
     pub failed_records: Vec<RecordId>,
 }
 
-/// Pipeline configuration with comprehensive settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PipelineConfiguration {
-    pub database_config: DatabaseConfig,
-    pub inference_config: InferenceConfig,
-    pub processing_config: ProcessingConfig,
-    pub monitoring_config: MonitoringConfig,
-    pub performance_config: PerformanceConfig,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseConfig {
     pub connection_string: String,
@@ -175,7 +175,7 @@ pub async fn process_user_data(user_id: Uuid) -> Result<UserProfile, Error> {
                 stop_sequences: vec!["\n\n".to_string()],
                 do_sample: true,
             },
-            output_format: OutputFormat::PlainText,
+            output_schema: None,
             include_metadata: true,
         },
         created_at: Utc::now(),
@@ -224,7 +224,7 @@ fn process_data_{}(data: &Vec<i32>) -> Vec<i32> {{
             options: JobOptions {
                 chunking_strategy: ChunkingStrategy::Lines { max_lines: 10 },
                 inference_config: InferenceParams::default(),
-                output_format: OutputFormat::PlainText,
+                output_schema: None,
                 include_metadata: false,
             },
             created_at: Utc::now(),
@@ -289,7 +289,7 @@ pub fn function_{}() -> Result<i32, Error> {{
         options: JobOptions {
             chunking_strategy: ChunkingStrategy::Lines { max_lines: 50 },
             inference_config: InferenceParams::default(),
-            output_format: OutputFormat::Markdown,
+            output_schema: None,
             include_metadata: true,
         },
         created_at: Utc::now(),
@@ -337,7 +337,7 @@ async fn test_pipeline_error_handling() {
         options: JobOptions {
             chunking_strategy: ChunkingStrategy::Lines { max_lines: 10 },
             inference_config: InferenceParams::default(),
-            output_format: OutputFormat::PlainText,
+            output_schema: None,
             include_metadata: false,
         },
         created_at: Utc::now(),
@@ -412,7 +412,7 @@ async fn test_pipeline_streaming() {
         options: JobOptions {
             chunking_strategy: ChunkingStrategy::Lines { max_lines: 5 },
             inference_config: InferenceParams::default(),
-            output_format: OutputFormat::PlainText,
+            output_schema: None,
             include_metadata: false,
         },
         created_at: Utc::now(),
@@ -475,7 +475,7 @@ async fn concurrent_task_{}() -> Result<String, Error> {{
             options: JobOptions {
                 chunking_strategy: ChunkingStrategy::Lines { max_lines: 10 },
                 inference_config: InferenceParams::default(),
-                output_format: OutputFormat::PlainText,
+                output_schema: None,
                 include_metadata: false,
             },
             created_at: Utc::now(),
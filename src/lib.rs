@@ -8,6 +8,14 @@ pub mod inference;  // Candle RS high-performance inference implementation
 pub mod parallel_agents;  // 20-agent parallel processing architecture
 pub mod config;
 pub mod errors;
+pub mod ingest;  // Repository dumps and other multi-file inputs
+pub mod summarization;  // Hierarchical roll-up of chunk summaries
+pub mod structured;  // Schema-constrained JSON summaries
+pub mod prompt;  // Per-chunk prompt templates
+pub mod eval;  // Offline summary-quality evaluation
+pub mod output;  // Markdown, JSON, JSONL and HTML renderings of a run
+pub mod journal;  // Resumable runs: finished chunks recorded as they complete
+pub mod plan;  // Dry-run token and time estimates
 
 // Layer 1 Core Traits (TDD-First Architecture)
 pub mod layer1;
@@ -16,8 +24,8 @@ pub mod layer1;
 pub mod cozodb;
 
 // Re-export main components for parallel_summarizer
-pub use chunking::{TextChunker, Chunk};
-pub use config::SystemConfig;
+pub use chunking::{TextChunker, Chunk, ChunkId, Chunker};
+pub use config::LayeredConfig;
 pub use errors::{ProcessingError, Result};
 pub use parallel_agents::{ParallelAgentSystem, ParallelConfig, ParallelMetrics};
 pub use inference::{OptimizedInferenceEngine}; // Working session reuse architecture
\ No newline at end of file
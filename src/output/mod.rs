@@ -0,0 +1,18 @@
+//! Rendering a run's chunk summaries to the file formats users ask for
+//!
+//! Contracts:
+//! - Chunks render in input order; files appear in order of their first chunk
+//! - Line ranges are 1-based and inclusive, as everywhere users see them
+//! - Anchors derive only from file path and line range, so links to a chunk
+//!   survive re-runs that leave its lines alone; paths that slug alike get
+//!   `-2`, `-3`, ... in order of their first chunk
+//! - JSON and JSONL carry the same per-chunk object; JSONL adds the model name
+//!   to each line so concatenated runs stay attributable
+//! - Files skipped before chunking are listed in every format, with the reason
+//! - HTML pages are self-contained: inline styles, no scripts, every piece of
+//!   model output escaped
+
+pub mod render;
+pub mod report;
+
+pub use report::{ChunkReport, SummaryReport};
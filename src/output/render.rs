@@ -0,0 +1,271 @@
+//! Text, Markdown and HTML renderings of a `SummaryReport`
+
+use std::fmt::Write;
+
+use super::report::{ChunkReport, SummaryReport};
+
+/// Summaries separated by blank lines; several files get `=== path ===` sections
+/// of `[lines a-b]` entries. A roll-up, when built, stands for the whole run.
+/// Skipped files close the output in their own section.
+pub fn text(report: &SummaryReport) -> String {
+    let mut sections = vec![summaries_text(report)];
+    if !report.skipped.is_empty() {
+        let entries: Vec<String> = report.skipped.iter().map(|(path, reason)| format!("{}: {}", path, reason)).collect();
+        sections.push(format!("=== Skipped files ===\n{}", entries.join("\n")));
+    }
+    sections.retain(|section| !section.is_empty());
+    sections.join("\n\n")
+}
+
+fn summaries_text(report: &SummaryReport) -> String {
+    if let Some(rollup) = &report.rollup {
+        return rollup.clone();
+    }
+    let files = report.files();
+    if files.len() <= 1 {
+        return report.chunks.iter().map(|chunk| chunk.summary.as_str()).collect::<Vec<_>>().join("\n\n");
+    }
+    files
+        .into_iter()
+        .map(|(path, chunks)| {
+            let entries: Vec<String> = chunks
+                .iter()
+                .map(|chunk| format!("[lines {}-{}]\n{}", chunk.first_line(), chunk.last_line(), chunk.summary))
+                .collect();
+            match path {
+                Some(path) => format!("=== {} ===\n{}", path, entries.join("\n\n")),
+                None => entries.join("\n\n"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// A contents list, then one `##` section per file and one anchored `###`
+/// section per chunk line range
+pub fn markdown(report: &SummaryReport) -> String {
+    let files = report.files();
+    let mut out = String::from("# Code summary\n\n");
+    let _ = writeln!(out, "- Model: `{}`", report.model);
+    let _ = writeln!(out, "- Generated: {}", report.generated_at.to_rfc3339());
+    let _ = writeln!(out, "- Chunks: {} across {} file(s)", report.chunks.len(), files.len());
+    let _ = writeln!(out, "- Processing time: {:.2}s", report.processing_time.as_secs_f64());
+    if !report.skipped.is_empty() {
+        let _ = writeln!(out, "- Skipped files: [{}](#skipped-files)", report.skipped.len());
+    }
+
+    if let Some(rollup) = &report.rollup {
+        let _ = write!(out, "\n## Overview\n\n{}\n", rollup.trim_end());
+    }
+
+    out.push_str("\n## Contents\n\n");
+    for (path, chunks) in &files {
+        let _ = writeln!(out, "- [{}](#{})", file_title(*path), chunks[0].file_anchor);
+        for chunk in chunks {
+            let _ = writeln!(out, "  - [{}](#{})", chunk_title(chunk), chunk.anchor());
+        }
+    }
+
+    for (path, chunks) in &files {
+        let _ = write!(out, "\n<a id=\"{}\"></a>\n\n## {}\n", chunks[0].file_anchor, file_title(*path));
+        for chunk in chunks {
+            let _ = write!(out, "\n<a id=\"{}\"></a>\n\n### {}\n\n_{}_\n\n", chunk.anchor(), chunk_title(chunk), chunk_details(chunk));
+            if chunk.tasks.is_empty() {
+                let _ = writeln!(out, "{}", chunk.summary.trim_end());
+            } else {
+                for (name, output) in &chunk.tasks {
+                    let _ = write!(out, "#### {}\n\n{}\n\n", name, output.trim_end());
+                }
+            }
+            if let Some(grounding) = &chunk.grounding {
+                let _ = writeln!(out, "\n> Grounding {:.2}{}", grounding.score,
+                    if grounding.ungrounded.is_empty() {
+                        String::new()
+                    } else {
+                        format!("; ungrounded: {}", grounding.ungrounded.iter().map(|name| format!("`{}`", name)).collect::<Vec<_>>().join(", "))
+                    });
+            }
+        }
+    }
+
+    if !report.skipped.is_empty() {
+        out.push_str("\n<a id=\"skipped-files\"></a>\n\n## Skipped files\n\n");
+        for (path, reason) in &report.skipped {
+            let _ = writeln!(out, "- `{}`: {}", path, reason);
+        }
+    }
+    out
+}
+
+/// One self-contained page: inline styles, a contents list and a section per file
+pub fn html(report: &SummaryReport) -> String {
+    let files = report.files();
+    let mut body = String::new();
+    let _ = writeln!(body, "<h1>Code summary</h1>");
+    let _ = writeln!(body, "<p class=\"meta\">Model <code>{}</code> · generated {} · {} chunks across {} file(s) · {:.2}s</p>",
+                     escape(&report.model), report.generated_at.to_rfc3339(), report.chunks.len(), files.len(),
+                     report.processing_time.as_secs_f64());
+
+    if let Some(rollup) = &report.rollup {
+        let _ = writeln!(body, "<h2>Overview</h2>\n<div class=\"summary\">{}</div>", escape(rollup.trim_end()));
+    }
+
+    body.push_str("<nav>\n<h2>Contents</h2>\n<ul>\n");
+    for (path, chunks) in &files {
+        let _ = writeln!(body, "<li><a href=\"#{}\">{}</a>\n<ul>", chunks[0].file_anchor, escape(&file_title(*path)));
+        for chunk in chunks {
+            let _ = writeln!(body, "<li><a href=\"#{}\">{}</a></li>", chunk.anchor(), escape(&chunk_title(chunk)));
+        }
+        body.push_str("</ul></li>\n");
+    }
+    body.push_str("</ul>\n</nav>\n");
+
+    for (path, chunks) in &files {
+        let _ = writeln!(body, "<section id=\"{}\">\n<h2>{}</h2>", chunks[0].file_anchor, escape(&file_title(*path)));
+        for chunk in chunks {
+            let _ = writeln!(body, "<article id=\"{}\">\n<h3><a href=\"#{}\">{}</a></h3>\n<p class=\"meta\">{}</p>",
+                             chunk.anchor(), chunk.anchor(), escape(&chunk_title(chunk)), escape(&chunk_details(chunk)));
+            if chunk.tasks.is_empty() {
+                let _ = writeln!(body, "<div class=\"summary\">{}</div>", escape(chunk.summary.trim_end()));
+            } else {
+                for (name, output) in &chunk.tasks {
+                    let _ = writeln!(body, "<h4>{}</h4>\n<div class=\"summary\">{}</div>", escape(name), escape(output.trim_end()));
+                }
+            }
+            if let Some(grounding) = &chunk.grounding {
+                let _ = writeln!(body, "<p class=\"grounding\">Grounding {:.2}{}</p>", grounding.score,
+                    if grounding.ungrounded.is_empty() {
+                        String::new()
+                    } else {
+                        format!("; ungrounded: {}", escape(&grounding.ungrounded.join(", ")))
+                    });
+            }
+            body.push_str("</article>\n");
+        }
+        body.push_str("</section>\n");
+    }
+
+    if !report.skipped.is_empty() {
+        body.push_str("<section id=\"skipped-files\">\n<h2>Skipped files</h2>\n<ul>\n");
+        for (path, reason) in &report.skipped {
+            let _ = writeln!(body, "<li><code>{}</code>: {}</li>", escape(path), escape(reason));
+        }
+        body.push_str("</ul>\n</section>\n");
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Code summary</title>\n<style>\n{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        STYLE, body
+    )
+}
+
+const STYLE: &str = "body { font-family: system-ui, sans-serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; line-height: 1.5; color: #222; }
+code { font-family: ui-monospace, monospace; }
+nav ul { list-style: none; padding-left: 1rem; }
+section { border-top: 1px solid #ddd; margin-top: 2rem; }
+article { margin: 1rem 0 1.5rem; }
+article:target { background: #fff8dc; }
+.meta { color: #666; font-size: 0.9rem; }
+.summary { white-space: pre-wrap; }
+.grounding { color: #a15c00; font-size: 0.9rem; }
+";
+
+fn file_title(path: Option<&str>) -> String {
+    path.unwrap_or("input").to_string()
+}
+
+/// `Lines a-b`, plus the symbol the chunk covers when known
+fn chunk_title(chunk: &ChunkReport) -> String {
+    match &chunk.chunk.symbol {
+        Some(symbol) => format!("Lines {}-{} · {}", chunk.first_line(), chunk.last_line(), symbol),
+        None => format!("Lines {}-{}", chunk.first_line(), chunk.last_line()),
+    }
+}
+
+/// Language, overlap, generation time and symbols, whichever are known
+fn chunk_details(chunk: &ChunkReport) -> String {
+    let mut details = vec![format!("{} ({:.2})", chunk.chunk.language, chunk.chunk.language_confidence)];
+    if chunk.chunk.overlap_lines > 0 {
+        details.push(format!("{} overlap lines", chunk.chunk.overlap_lines));
+    }
+    if let Some(elapsed) = chunk.elapsed {
+        details.push(format!("{:.2}s", elapsed.as_secs_f64()));
+    }
+    if !chunk.chunk.symbols.is_empty() {
+        details.push(chunk.chunk.symbols.iter().map(|symbol| symbol.name.as_str()).collect::<Vec<_>>().join(", "));
+    }
+    details.join(" · ")
+}
+
+/// `text` with HTML's special characters escaped
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::chunking::Chunk;
+    use super::*;
+
+    fn report() -> SummaryReport {
+        let chunk = |start: usize, end: usize, path: &str| {
+            let mut chunk = Chunk::new(start, end, format!("// {}", path));
+            chunk.file_path = Some(path.to_string());
+            chunk
+        };
+        SummaryReport::new("model", Duration::from_secs(1), vec![
+            ChunkReport::new(chunk(0, 40, "src/a.rs"), "Parses <input> & config"),
+            ChunkReport::new(chunk(0, 12, "src/b.rs"), "Entry point"),
+            ChunkReport::new(chunk(40, 80, "src/a.rs"), "Writes output"),
+        ])
+    }
+
+    #[test]
+    fn test_markdown_groups_chunks_by_file_with_line_range_anchors() {
+        let markdown = markdown(&report());
+        assert!(markdown.contains("- [src/a.rs](#src-a-rs)\n  - [Lines 1-40](#src-a-rs-L1-L40)\n  - [Lines 41-80](#src-a-rs-L41-L80)"));
+        assert!(markdown.contains("<a id=\"src-a-rs-L41-L80\"></a>\n\n### Lines 41-80"));
+        // A file's chunks stay together even when another file comes between them
+        let a = markdown.find("## src/a.rs").unwrap();
+        assert!(markdown.find("Writes output").unwrap() < markdown.find("## src/b.rs").unwrap());
+        assert!(a < markdown.find("## src/b.rs").unwrap());
+
+        assert!(text(&report()).starts_with("=== src/a.rs ===\n[lines 1-40]\nParses"));
+    }
+
+    #[test]
+    fn test_skipped_files_are_listed_in_every_format() {
+        let report = report().with_skipped(vec![("gen.rs".to_string(), "generated".to_string())]);
+        assert!(text(&report).ends_with("=== Skipped files ===\ngen.rs: generated"));
+        assert!(markdown(&report).contains("## Skipped files\n\n- `gen.rs`: generated"));
+        assert!(html(&report).contains("<li><code>gen.rs</code>: generated</li>"));
+        assert_eq!(report.to_json()["skipped"][0]["reason"], "generated");
+        assert!(report.to_jsonl().ends_with("{\"file\":\"gen.rs\",\"skipped\":\"generated\",\"model\":\"model\"}\n"));
+
+        // Nothing but skipped files still renders a report
+        let empty = SummaryReport::new("model", Duration::ZERO, Vec::new()).with_skipped(report.skipped.clone());
+        assert_eq!(text(&empty), "=== Skipped files ===\ngen.rs: generated");
+    }
+
+    #[test]
+    fn test_html_is_self_contained_and_escapes_model_output() {
+        let html = html(&report());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<style>"));
+        assert!(!html.contains("<script") && !html.contains("<link"));
+        assert!(html.contains("Parses &lt;input&gt; &amp; config"));
+        assert!(html.contains("<article id=\"src-b-rs-L1-L12\">"));
+    }
+}
@@ -0,0 +1,280 @@
+//! What a run produced: every chunk with its summary, and the run around them
+
+use std::collections::HashMap;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
+
+use crate::chunking::Chunk;
+use crate::config::OutputFormat;
+use crate::summarization::GroundingReport;
+
+use super::render;
+
+/// One summarized chunk with everything known about it
+#[derive(Debug, Clone)]
+pub struct ChunkReport {
+    pub chunk: Chunk,
+    pub summary: String,
+    /// The summary as a schema-shaped document, for structured runs
+    pub structured: Option<Value>,
+    /// Per-task outputs as (task name, output), in task order
+    pub tasks: Vec<(String, String)>,
+    pub grounding: Option<GroundingReport>,
+    /// Generation time once the chunk held an agent, when it was measured
+    pub elapsed: Option<Duration>,
+    /// Anchor of the chunk's file, made unique within a `SummaryReport`
+    pub file_anchor: String,
+}
+
+impl ChunkReport {
+    pub fn new(chunk: Chunk, summary: impl Into<String>) -> Self {
+        Self {
+            file_anchor: file_anchor(chunk.file_path.as_deref()),
+            chunk,
+            summary: summary.into(),
+            structured: None,
+            tasks: Vec::new(),
+            grounding: None,
+            elapsed: None,
+        }
+    }
+
+    pub fn with_structured(mut self, structured: Value) -> Self {
+        self.structured = Some(structured);
+        self
+    }
+
+    pub fn with_tasks(mut self, tasks: Vec<(String, String)>) -> Self {
+        self.tasks = tasks;
+        self
+    }
+
+    pub fn with_grounding(mut self, grounding: GroundingReport) -> Self {
+        self.grounding = Some(grounding);
+        self
+    }
+
+    pub fn with_elapsed(mut self, elapsed: Duration) -> Self {
+        self.elapsed = Some(elapsed);
+        self
+    }
+
+    /// 1-based, inclusive first line
+    pub fn first_line(&self) -> usize {
+        self.chunk.line_start + 1
+    }
+
+    /// 1-based, inclusive last line
+    pub fn last_line(&self) -> usize {
+        self.chunk.line_end.max(self.first_line())
+    }
+
+    /// Anchor of this chunk's line range, e.g. `src-main-rs-L1-L40`
+    pub fn anchor(&self) -> String {
+        format!("{}-L{}-L{}", self.file_anchor, self.first_line(), self.last_line())
+    }
+
+    /// Every field of the chunk and its summary as one JSON object
+    pub fn to_json(&self) -> Value {
+        let chunk = &self.chunk;
+        let mut object = json!({
+            "id": chunk.id().to_string(),
+            "file": chunk.file_path,
+            "anchor": self.anchor(),
+            "line_start": self.first_line(),
+            "line_end": self.last_line(),
+            "line_count": chunk.line_count,
+            "overlap_lines": chunk.overlap_lines,
+            "language": chunk.language.as_str(),
+            "language_confidence": chunk.language_confidence,
+            "symbol": chunk.symbol,
+            "symbols": chunk.symbols.iter().map(|symbol| symbol.to_json()).collect::<Vec<_>>(),
+            "summary": self.structured.clone().unwrap_or_else(|| Value::String(self.summary.clone())),
+        });
+        if !self.tasks.is_empty() {
+            object["tasks"] = Value::Object(
+                self.tasks.iter().map(|(name, output)| (name.clone(), Value::String(output.clone()))).collect(),
+            );
+        }
+        if let Some(grounding) = &self.grounding {
+            object["grounding"] = grounding.to_json();
+        }
+        if let Some(elapsed) = self.elapsed {
+            object["elapsed_ms"] = json!(elapsed.as_millis() as u64);
+        }
+        object
+    }
+}
+
+/// Every chunk report of a run, with the run's model, timing and roll-up
+#[derive(Debug, Clone)]
+pub struct SummaryReport {
+    pub model: String,
+    pub generated_at: DateTime<Utc>,
+    pub processing_time: Duration,
+    pub chunks: Vec<ChunkReport>,
+    /// Hierarchical roll-up of the chunk summaries, when one was built
+    pub rollup: Option<String>,
+    /// Input files left out before chunking, with the reason
+    pub skipped: Vec<(String, String)>,
+}
+
+impl SummaryReport {
+    pub fn new(model: impl Into<String>, processing_time: Duration, mut chunks: Vec<ChunkReport>) -> Self {
+        dedupe_file_anchors(&mut chunks);
+        Self {
+            model: model.into(),
+            generated_at: Utc::now(),
+            processing_time,
+            chunks,
+            rollup: None,
+            skipped: Vec::new(),
+        }
+    }
+
+    pub fn with_rollup(mut self, rollup: Option<String>) -> Self {
+        self.rollup = rollup;
+        self
+    }
+
+    pub fn with_skipped(mut self, skipped: Vec<(String, String)>) -> Self {
+        self.skipped = skipped;
+        self
+    }
+
+    /// Chunks grouped by file, files in order of their first chunk
+    pub fn files(&self) -> Vec<(Option<&str>, Vec<&ChunkReport>)> {
+        let mut files: Vec<(Option<&str>, Vec<&ChunkReport>)> = Vec::new();
+        for report in &self.chunks {
+            let path = report.chunk.file_path.as_deref();
+            match files.iter_mut().find(|(file, _)| *file == path) {
+                Some((_, reports)) => reports.push(report),
+                None => files.push((path, vec![report])),
+            }
+        }
+        files
+    }
+
+    /// Run metadata and every chunk as one JSON document
+    pub fn to_json(&self) -> Value {
+        let mut object = Map::new();
+        object.insert("model".to_string(), json!(self.model));
+        object.insert("generated_at".to_string(), json!(self.generated_at.to_rfc3339()));
+        object.insert("processing_time_ms".to_string(), json!(self.processing_time.as_millis() as u64));
+        object.insert("chunk_count".to_string(), json!(self.chunks.len()));
+        object.insert("file_count".to_string(), json!(self.files().len()));
+        if let Some(rollup) = &self.rollup {
+            object.insert("rollup".to_string(), json!(rollup));
+        }
+        object.insert("chunks".to_string(), self.chunks.iter().map(ChunkReport::to_json).collect());
+        object.insert("skipped".to_string(), self.skipped.iter().map(|(path, reason)| json!({"file": path, "reason": reason})).collect());
+        Value::Object(object)
+    }
+
+    /// One chunk object per line, each naming the model, then one
+    /// `{"file", "skipped"}` line per skipped file
+    pub fn to_jsonl(&self) -> String {
+        let chunks = self.chunks.iter().map(|report| {
+            let mut object = report.to_json();
+            object["model"] = json!(self.model);
+            object
+        });
+        let skipped = self.skipped.iter().map(|(path, reason)| json!({"file": path, "skipped": reason, "model": self.model}));
+        chunks.chain(skipped).map(|object| object.to_string() + "\n").collect()
+    }
+
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => render::text(self),
+            OutputFormat::Markdown => render::markdown(self),
+            OutputFormat::Json => serde_json::to_string_pretty(&self.to_json()).unwrap_or_default(),
+            OutputFormat::Jsonl => self.to_jsonl(),
+            OutputFormat::Html => render::html(self),
+        }
+    }
+}
+
+/// Give files whose anchors collide (`src/a_b.rs`, `src/A-b.rs`) `-2`, `-3`, ...
+/// in order of their first chunk
+fn dedupe_file_anchors(chunks: &mut [ChunkReport]) {
+    let mut owners: HashMap<String, Option<String>> = HashMap::new();
+    for report in chunks {
+        let path = report.chunk.file_path.clone();
+        let base = file_anchor(path.as_deref());
+        let mut anchor = base.clone();
+        for suffix in 2.. {
+            match owners.get(&anchor) {
+                Some(owner) if *owner != path => anchor = format!("{}-{}", base, suffix),
+                _ => break,
+            }
+        }
+        owners.insert(anchor.clone(), path);
+        report.file_anchor = anchor;
+    }
+}
+
+/// Anchor of a file: its path lowercased, runs of other characters as `-`
+pub fn file_anchor(path: Option<&str>) -> String {
+    let slug = path
+        .unwrap_or("input")
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() { "input".to_string() } else { slug }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_json_carries_metadata_and_keys_tasks_by_name() {
+        let mut chunk = Chunk::new(10, 20, "fn main() {}".to_string());
+        chunk.file_path = Some("src/Main.rs".to_string());
+        let report = ChunkReport::new(chunk, "[risks]\nnone")
+            .with_tasks(vec![("risks".to_string(), "none".to_string()), ("todo".to_string(), "tests".to_string())])
+            .with_elapsed(Duration::from_millis(1500));
+
+        let json = report.to_json();
+        assert_eq!(json["anchor"], "src-main-rs-L11-L20");
+        assert_eq!(json["line_start"], 11);
+        assert_eq!(json["line_end"], 20);
+        assert_eq!(json["elapsed_ms"], 1500);
+        assert_eq!(json["tasks"]["todo"], "tests");
+        assert_eq!(json["id"].as_str().map(str::len), Some(32));
+
+        let lines = SummaryReport::new("m", Duration::ZERO, vec![report]).to_jsonl();
+        let line: Value = serde_json::from_str(lines.trim_end()).unwrap();
+        assert_eq!(line["model"], "m");
+        assert_eq!(line["file"], "src/Main.rs");
+    }
+
+    #[test]
+    fn test_colliding_file_anchors_get_numeric_suffixes() {
+        let chunk = |path: &str, line_start: usize| {
+            let mut chunk = Chunk::new(line_start, line_start + 10, format!("// {}", path));
+            chunk.file_path = Some(path.to_string());
+            ChunkReport::new(chunk, "summary")
+        };
+        let report = SummaryReport::new("m", Duration::ZERO, vec![
+            chunk("src/a_b.rs", 0),
+            chunk("src/a-b.rs", 0),
+            chunk("src/a_b.rs", 10),
+            chunk("src/A.b.rs", 0),
+            chunk("src/a-b-rs-2", 0),
+        ]);
+
+        let anchors: Vec<&str> = report.chunks.iter().map(|chunk| chunk.file_anchor.as_str()).collect();
+        assert_eq!(anchors, ["src-a-b-rs", "src-a-b-rs-2", "src-a-b-rs", "src-a-b-rs-3", "src-a-b-rs-2-2"]);
+        assert_eq!(report.chunks[2].anchor(), "src-a-b-rs-L11-L20");
+        assert_eq!(report.to_json()["chunks"][1]["anchor"], "src-a-b-rs-2-L1-L10");
+
+        let markdown = render::markdown(&report);
+        for anchor in ["src-a-b-rs", "src-a-b-rs-2", "src-a-b-rs-3", "src-a-b-rs-2-2"] {
+            assert_eq!(markdown.matches(&format!("<a id=\"{}\">", anchor)).count(), 1, "{}", anchor);
+        }
+    }
+}
@@ -4,14 +4,17 @@
 //! Each agent gets its own RealInferencePipeline instance to avoid mutex conflicts
 
 use anyhow::Result;
+use futures::stream::{FuturesUnordered, StreamExt};
 use log::{info, error, warn};
 use tokio::task::JoinHandle;
 use tokio::sync::Semaphore;
 use std::sync::Arc;
 use std::path::PathBuf;
+use std::time::Duration;
 
-use crate::inference::OptimizedInferenceEngine;
-use crate::config::GenerationConfig;
+use crate::inference::{CancellationToken, GenerationControl, OptimizedInferenceEngine};
+use crate::config::{GenerationConfig, LayeredConfig};
+use crate::structured::OutputSchema;
 
 /// Configuration for 20-agent parallel processing system
 #[derive(Debug, Clone)]
@@ -26,20 +29,59 @@ pub struct ParallelConfig {
     pub max_concurrent: usize,
     /// Generation configuration for text generation
     pub generation_config: GenerationConfig,
+    /// Per-chunk generation deadline; chunks exceeding it return partial output
+    pub max_chunk_processing_time: Duration,
+    /// When set, every summary is a JSON document valid against this schema
+    pub output_schema: Option<Arc<OutputSchema>>,
 }
 
-impl Default for ParallelConfig {
-    fn default() -> Self {
+impl ParallelConfig {
+    /// Settings resolved by `LayeredConfig`: model, generation, agent count,
+    /// concurrency and the per-chunk deadline, so a file, profile, `DOBBY_*`
+    /// variable or flag reaches every agent
+    pub fn from_settings(settings: &LayeredConfig) -> Self {
+        let (model_dir, tokenizer_dir) = match settings.model_config() {
+            Some(model_config) => (model_config.model_path.clone(), model_config.tokenizer_path()),
+            None => (PathBuf::from("./models/qwen2.5-0.5b-int4"), PathBuf::from("./tokenizer_dir")),
+        };
         Self {
-            agent_count: 20,  // User-specified maximum parallelism
-            model_dir: PathBuf::from("./models/qwen2.5-0.5b-int4"),
-            tokenizer_dir: PathBuf::from("./tokenizer_dir"),
-            max_concurrent: num_cpus::get(), // Mac Mini core count (8-10)
-            generation_config: GenerationConfig::default(),
+            agent_count: settings.usize("run.agent_count").unwrap_or(20),  // User-specified maximum parallelism
+            model_dir,
+            tokenizer_dir,
+            max_concurrent: settings.usize("run.max_concurrent").unwrap_or_else(num_cpus::get), // Mac Mini core count (8-10)
+            generation_config: settings.generation_config(),
+            max_chunk_processing_time: settings.chunk_time_limit(),
+            output_schema: None,
         }
     }
 }
 
+impl Default for ParallelConfig {
+    fn default() -> Self {
+        Self::from_settings(&LayeredConfig::defaults())
+    }
+}
+
+/// One chunk's summary and how long its generation took
+#[derive(Debug, Clone)]
+pub struct TimedSummary {
+    pub chunk: String,
+    pub summary: String,
+    /// Time spent generating once the chunk held a permit, excluding queueing
+    pub elapsed: Duration,
+    /// Why the summary is partial or an error report; `None` for a clean completion
+    pub failure: Option<String>,
+}
+
+/// One chunk's task outputs, in task order
+#[derive(Debug, Clone)]
+pub struct TaskSummaries {
+    pub chunk: String,
+    pub outputs: Vec<String>,
+    /// Why any output is partial or an error report; `None` when every task completed cleanly
+    pub failure: Option<String>,
+}
+
 /// Multi-Agent Parallel Processing System
 ///
 /// Uses read-only session sharing strategy for 10x parallelism
@@ -51,6 +93,8 @@ pub struct ParallelAgentSystem {
     engine: Arc<OptimizedInferenceEngine>,
     /// Semaphore for controlling 10x parallelism
     semaphore: Arc<Semaphore>,
+    /// Run-wide cancellation checked by every in-flight generation
+    cancellation: CancellationToken,
 }
 
 impl ParallelAgentSystem {
@@ -85,9 +129,24 @@ impl ParallelAgentSystem {
             config,
             engine: Arc::new(engine),
             semaphore,
+            cancellation: CancellationToken::new(),
         })
     }
 
+    /// Cancel all in-flight and pending generations
+    ///
+    /// Chunks stop at their next token boundary and return partial output
+    /// marked as truncated.
+    pub fn cancel(&self) {
+        warn!("🛑 Cancellation requested - in-flight chunks will return partial output");
+        self.cancellation.cancel();
+    }
+
+    /// Token shared by all generations of this system
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
     /// Process multiple code chunks in parallel using 20 agents
     ///
     /// # Arguments
@@ -171,16 +230,58 @@ impl ParallelAgentSystem {
     /// # Returns
     /// `Result<Vec<(String, String)>>` - Vector of (chunk, summary) pairs
     pub async fn process_chunks_parallel_with_prompts(&self, chunks: Vec<String>, prompt: &str) -> Result<Vec<(String, String)>> {
+        let prompted = chunks.into_iter().map(|chunk| (chunk, prompt.to_string())).collect();
+        self.process_prompted_chunks_parallel(prompted).await
+    }
+
+    /// Process code chunks in parallel, each with its own rendered prompt
+    ///
+    /// # Arguments
+    /// * `chunks` - Vector of (chunk, prompt) pairs, e.g. from a `PromptTemplate`
+    ///
+    /// # Returns
+    /// `Result<Vec<(String, String)>>` - Vector of (chunk, summary) pairs
+    pub async fn process_prompted_chunks_parallel(&self, chunks: Vec<(String, String)>) -> Result<Vec<(String, String)>> {
+        let results = self.process_prompted_chunks_timed(chunks).await?;
+        Ok(results.into_iter().map(|timed| (timed.chunk, timed.summary)).collect())
+    }
+
+    /// Process code chunks in parallel like `process_prompted_chunks_parallel`,
+    /// also reporting how long each chunk's generation took
+    ///
+    /// # Returns
+    /// `Result<Vec<TimedSummary>>` - One entry per chunk, in input order; a
+    /// chunk whose task died fails the whole call
+    pub async fn process_prompted_chunks_timed(&self, chunks: Vec<(String, String)>) -> Result<Vec<TimedSummary>> {
+        let results = self.process_prompted_chunks_with(chunks, |_, _| Ok(())).await?;
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /// Process code chunks in parallel like `process_prompted_chunks_timed`,
+    /// calling `on_complete` with each chunk's input index as soon as it finishes
+    ///
+    /// An error from `on_complete` stops collection and is returned.
+    ///
+    /// # Returns
+    /// `Result<Vec<(usize, Result<TimedSummary>)>>` - Every chunk's input index and
+    /// summary, in input order; `Err` when the chunk's task died without one
+    pub async fn process_prompted_chunks_with(
+        &self,
+        chunks: Vec<(String, String)>,
+        on_complete: impl FnMut(usize, &TimedSummary) -> Result<()>,
+    ) -> Result<Vec<(usize, Result<TimedSummary>)>> {
         info!("🔄 Starting 10x parallel processing of {} chunks with semaphore control", chunks.len());
 
         // Phase 2: Spawn all chunks with semaphore-controlled parallelism
-        let mut handles: Vec<JoinHandle<(String, String)>> = Vec::new();
+        let mut handles: Vec<JoinHandle<TimedSummary>> = Vec::new();
 
-        for (chunk_index, chunk) in chunks.into_iter().enumerate() {
-            let prompt = prompt.to_string(); // Clone prompt for each task
+        for (chunk_index, (chunk, prompt)) in chunks.into_iter().enumerate() {
             let generation_config = self.config.generation_config.clone(); // Clone generation config
             let semaphore = self.semaphore.clone(); // Clone semaphore for this task
             let engine = self.engine.clone(); // Clone shared engine for this task
+            let cancellation = self.cancellation.clone();
+            let chunk_timeout = self.config.max_chunk_processing_time;
+            let output_schema = self.config.output_schema.clone();
 
             info!("🚀 Launching chunk {} ({} chars) with strategy: {:?}, temp: {:.2}",
                   chunk_index, chunk.len(), generation_config.strategy, generation_config.temperature);
@@ -194,35 +295,60 @@ impl ParallelAgentSystem {
                     .expect("Semaphore should not be closed");
 
                 info!("🔓 Chunk {} acquired semaphore permit - starting inference", chunk_index);
+                let generation_start = std::time::Instant::now();
+
+                // Deadline starts once the chunk holds a permit, not while queued
+                let control = GenerationControl::with_timeout(cancellation, chunk_timeout);
 
                 // Process chunk using shared engine in spawn_blocking (CPU-bound work)
                 let chunk_clone = chunk.clone(); // Clone to avoid move issues
-                let result = tokio::task::spawn_blocking(move || {
-                    engine.summarize_chunk_with_generation_config(&chunk_clone, &prompt, &generation_config)
+                let task_schema = output_schema.clone();
+                let result = tokio::task::spawn_blocking(move || match &task_schema {
+                    Some(schema) => engine.summarize_chunk_structured(&chunk_clone, &prompt, &generation_config, schema, &control),
+                    None => engine.summarize_chunk_with_control(&chunk_clone, &prompt, &generation_config, &control),
                 }).await;
 
-                let summary = match result {
-                    Ok(Ok(summary)) => {
-                        info!("✅ Chunk {} completed successfully", chunk_index);
-                        summary
-                    }
+                // Structured summaries stay parseable JSON, failures included
+                let report = |message: String| match &output_schema {
+                    Some(schema) => schema.fill(&message).to_string(),
+                    None => message,
+                };
+
+                let (summary, failure) = match result {
+                    Ok(Ok(output)) => match output.truncated {
+                        None => {
+                            info!("✅ Chunk {} completed successfully", chunk_index);
+                            (output.text, None)
+                        }
+                        Some(reason) => {
+                            warn!("✂️ Chunk {} truncated after {} tokens: {}",
+                                  chunk_index, output.tokens_generated, reason);
+                            let summary = match output_schema {
+                                // Already closed into a valid document
+                                Some(_) => output.text,
+                                None => format!("{} [TRUNCATED: {}]", output.text.trim_end(), reason),
+                            };
+                            (summary, Some(format!("truncated: {}", reason)))
+                        }
+                    },
                     Ok(Err(e)) => {
                         error!("❌ Chunk {} inference failed: {}", chunk_index, e);
-                        format!("ERROR: Failed to process chunk - {}", e)
+                        (report(format!("ERROR: Failed to process chunk - {}", e)), Some(e.to_string()))
                     }
                     Err(e) => {
                         error!("❌ Chunk {} task failed: {}", chunk_index, e);
-                        format!("ERROR: Task failed - {}", e)
+                        (report(format!("ERROR: Task failed - {}", e)), Some(e.to_string()))
                     }
                 };
 
+                let elapsed = generation_start.elapsed();
                 let duration = start_time.elapsed();
                 info!("⏱️ Chunk {} completed in {:?} with 10x parallelism", chunk_index, duration);
 
                 // Release semaphore permit automatically when permit goes out of scope
                 drop(permit);
 
-                (chunk, summary)
+                TimedSummary { chunk, summary, elapsed, failure }
             });
 
             handles.push(handle);
@@ -230,21 +356,119 @@ impl ParallelAgentSystem {
 
         // Phase 3: Collect all results (true parallelism - no waiting for individual tasks)
         info!("📊 Collecting results from {} concurrent 10x parallel tasks", handles.len());
-        let mut results = Vec::new();
+        let results = collect_as_completed(handles, on_complete).await?;
 
-        for handle in handles {
-            match handle.await {
-                Ok((chunk, summary)) => {
-                    results.push((chunk, summary));
-                }
-                Err(e) => {
-                    warn!("⚠️ Task failed: {}", e);
-                    // Continue processing other tasks even if one fails
-                }
-            }
+        info!("🎉 10x parallel processing completed - {} results collected", results.len());
+        Ok(results)
+    }
+
+    /// Process code chunks in parallel, running several task prompts per chunk
+    ///
+    /// Each chunk holds one permit while its tasks run in order, so one
+    /// chunk's tasks never compete with each other for agents. Each task gets
+    /// the full per-chunk deadline.
+    ///
+    /// # Arguments
+    /// * `chunks` - Vector of (chunk, [(prompt, generation config)]) with tasks in output order
+    ///
+    /// # Returns
+    /// `Result<Vec<(String, Vec<String>)>>` - Vector of (chunk, one output per task) pairs
+    pub async fn process_chunk_tasks_parallel(
+        &self,
+        chunks: Vec<(String, Vec<(String, GenerationConfig)>)>,
+    ) -> Result<Vec<(String, Vec<String>)>> {
+        let results = self.process_chunk_tasks_with(chunks, |_, _| Ok(())).await?;
+        results
+            .into_iter()
+            .map(|(_, result)| result.map(|tasked| (tasked.chunk, tasked.outputs)))
+            .collect()
+    }
+
+    /// Process code chunks like `process_chunk_tasks_parallel`, calling
+    /// `on_complete` with each chunk's input index as soon as all its tasks finish
+    ///
+    /// An error from `on_complete` stops collection and is returned.
+    ///
+    /// # Returns
+    /// `Result<Vec<(usize, Result<TaskSummaries>)>>` - Every chunk's input index and
+    /// outputs, in input order; `Err` when the chunk's task died without them
+    pub async fn process_chunk_tasks_with(
+        &self,
+        chunks: Vec<(String, Vec<(String, GenerationConfig)>)>,
+        on_complete: impl FnMut(usize, &TaskSummaries) -> Result<()>,
+    ) -> Result<Vec<(usize, Result<TaskSummaries>)>> {
+        info!("🔄 Starting parallel processing of {} chunks with multiple tasks each", chunks.len());
+
+        let mut handles: Vec<JoinHandle<TaskSummaries>> = Vec::new();
+
+        for (chunk_index, (chunk, tasks)) in chunks.into_iter().enumerate() {
+            let semaphore = self.semaphore.clone();
+            let engine = self.engine.clone();
+            let cancellation = self.cancellation.clone();
+            let chunk_timeout = self.config.max_chunk_processing_time;
+
+            info!("🚀 Launching chunk {} ({} chars) with {} tasks", chunk_index, chunk.len(), tasks.len());
+
+            let handle = tokio::spawn(async move {
+                let start_time = std::time::Instant::now();
+                let permit = semaphore.acquire().await
+                    .expect("Semaphore should not be closed");
+
+                let task_count = tasks.len();
+                let chunk_clone = chunk.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    engine.summarize_chunk_tasks(&chunk_clone, &tasks, || {
+                        GenerationControl::with_timeout(cancellation.clone(), chunk_timeout)
+                    })
+                }).await;
+
+                // The first partial or failed task marks the whole chunk
+                let mut failure = None;
+                let outputs = match result {
+                    Ok(Ok(outputs)) => outputs
+                        .into_iter()
+                        .enumerate()
+                        .map(|(task_index, output)| match output {
+                            Ok(output) => match output.truncated {
+                                None => output.text,
+                                Some(reason) => {
+                                    warn!("✂️ Chunk {} task {} truncated after {} tokens: {}",
+                                          chunk_index, task_index, output.tokens_generated, reason);
+                                    failure.get_or_insert_with(|| format!("truncated: {}", reason));
+                                    format!("{} [TRUNCATED: {}]", output.text.trim_end(), reason)
+                                }
+                            },
+                            Err(e) => {
+                                error!("❌ Chunk {} task {} inference failed: {}", chunk_index, task_index, e);
+                                failure.get_or_insert_with(|| e.to_string());
+                                format!("ERROR: Failed to process chunk - {}", e)
+                            }
+                        })
+                        .collect(),
+                    Ok(Err(e)) => {
+                        error!("❌ Chunk {} inference failed: {}", chunk_index, e);
+                        failure = Some(e.to_string());
+                        vec![format!("ERROR: Failed to process chunk - {}", e); task_count]
+                    }
+                    Err(e) => {
+                        error!("❌ Chunk {} task failed: {}", chunk_index, e);
+                        failure = Some(e.to_string());
+                        vec![format!("ERROR: Task failed - {}", e); task_count]
+                    }
+                };
+
+                info!("⏱️ Chunk {} finished {} tasks in {:?}", chunk_index, task_count, start_time.elapsed());
+                drop(permit);
+
+                TaskSummaries { chunk, outputs, failure }
+            });
+
+            handles.push(handle);
         }
 
-        info!("🎉 10x parallel processing completed - {} results collected", results.len());
+        let results = collect_as_completed(handles, on_complete).await?;
+
+        info!("🎉 Multi-task processing completed - {} results collected", results.len());
         Ok(results)
     }
 
@@ -259,6 +483,40 @@ impl ParallelAgentSystem {
     }
 }
 
+/// Await `handles` as they finish, passing each result and its input index to
+/// `on_complete`; every handle comes back with its input index, in input order,
+/// and a task that died (panicked or was aborted) as an `Err`
+async fn collect_as_completed<T>(
+    handles: Vec<JoinHandle<T>>,
+    mut on_complete: impl FnMut(usize, &T) -> Result<()>,
+) -> Result<Vec<(usize, Result<T>)>> {
+    let mut slots: Vec<Option<Result<T>>> = handles.iter().map(|_| None).collect();
+    let mut pending: FuturesUnordered<_> = handles
+        .into_iter()
+        .enumerate()
+        .map(|(index, handle)| async move { (index, handle.await) })
+        .collect();
+
+    while let Some((index, joined)) = pending.next().await {
+        match joined {
+            Ok(result) => {
+                on_complete(index, &result)?;
+                slots[index] = Some(Ok(result));
+            }
+            Err(e) => {
+                // Continue processing other tasks even if one fails
+                warn!("⚠️ Task for chunk {} failed: {}", index, e);
+                slots[index] = Some(Err(anyhow::anyhow!("Task for chunk {} failed: {}", index, e)));
+            }
+        }
+    }
+    Ok(slots
+        .into_iter()
+        .enumerate()
+        .map(|(index, slot)| (index, slot.expect("every handle is awaited")))
+        .collect())
+}
+
 /// Performance metrics for the 20-agent system
 #[derive(Debug, Clone)]
 pub struct ParallelMetrics {
@@ -334,4 +592,37 @@ mod tests {
             }
         }
     }
+    #[tokio::test]
+    async fn test_collect_as_completed_keeps_indices_and_failures() {
+        let handles: Vec<JoinHandle<&str>> = vec![
+            tokio::spawn(async { tokio::time::sleep(Duration::from_millis(20)).await; "same" }),
+            tokio::spawn(async { panic!("agent crashed") }),
+            tokio::spawn(async { "same" }),
+        ];
+        let mut completed = Vec::new();
+        let results = collect_as_completed(handles, |index, _| {
+            completed.push(index);
+            Ok(())
+        }).await.unwrap();
+
+        // Identical outputs stay at their own index; the dead task is reported, not dropped
+        assert_eq!(results.iter().map(|(index, _)| *index).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(results[0].1.as_ref().unwrap(), &"same");
+        assert!(results[1].1.as_ref().unwrap_err().to_string().contains("chunk 1"));
+        assert_eq!(results[2].1.as_ref().unwrap(), &"same");
+        completed.sort_unstable();
+        assert_eq!(completed, vec![0, 2]);
+    }
+
+    #[test]
+    fn test_config_follows_layered_settings() {
+        let settings = LayeredConfig::load(None, Some("quality"), [("DOBBY_RUN_AGENT_COUNT".to_string(), "6".to_string())]).unwrap();
+        let config = ParallelConfig::from_settings(&settings);
+
+        // The profile's deadline and the variable's agent count reach the agents
+        assert_eq!(config.max_chunk_processing_time, Duration::from_secs(10));
+        assert_eq!(config.agent_count, 6);
+        assert_eq!(config.generation_config.num_beams, 5);
+        assert_eq!(ParallelConfig::default().max_chunk_processing_time, Duration::from_secs(2));
+    }
 }
\ No newline at end of file
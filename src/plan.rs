@@ -0,0 +1,265 @@
+//! Dry-run planning: what a run would cost, measured before any full model pass
+//!
+//! Contracts:
+//! - Token counts are supplied by the caller, measured with the model's real tokenizer
+//! - A chunk is over budget when any of its prompts, plus `max_new_tokens` and
+//!   the chat-template reserve, exceeds the context window
+//! - Output tokens are upper bounds: every prompt's `max_new_tokens`
+//! - Wall time scales a measured throughput to the chunks that would run;
+//!   chunks a resumed journal already finished cost nothing
+//! - Only the per-chunk pass is planned: refine steps and hierarchical
+//!   roll-ups depend on summaries a dry run never produces
+
+use std::path::Path;
+use std::time::Duration;
+use log::warn;
+use serde_json::{json, Value};
+
+use crate::chunking::token_budget::PROMPT_TEMPLATE_RESERVE_TOKENS;
+use crate::chunking::{Chunk, ChunkId};
+use crate::errors::{ProcessingError, Result};
+
+/// Token cost of one chunk across all of its prompts
+#[derive(Debug, Clone)]
+pub struct ChunkPlan {
+    pub id: ChunkId,
+    pub file: Option<String>,
+    /// 1-based, inclusive line range
+    pub lines: (usize, usize),
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    /// Largest prompt input plus generation and reserve, in tokens
+    pub peak_tokens: usize,
+    /// Already finished in a resumed journal; will not run
+    pub resumed: bool,
+}
+
+/// Measured chunks per second and where the measurement came from
+#[derive(Debug, Clone)]
+pub struct Throughput {
+    pub chunks_per_second: f64,
+    pub source: String,
+}
+
+impl Throughput {
+    /// Chunks per second from a `bench --output-file` report
+    ///
+    /// A report of another model or agent count still gives an order of
+    /// magnitude, so a mismatch is only warned about.
+    ///
+    /// # Errors
+    /// * `ProcessingError::Io` - the report cannot be read
+    /// * `ProcessingError::InvalidBenchReport` - not JSON, or no `chunks_per_second`
+    pub fn from_bench_report(path: &Path, model: &str, agent_count: usize) -> Result<Self> {
+        let invalid = |reason: String| ProcessingError::InvalidBenchReport { path: path.display().to_string(), reason };
+        let bench: Value = serde_json::from_str(&std::fs::read_to_string(path)?).map_err(|e| invalid(e.to_string()))?;
+        let chunks_per_second = bench["chunks_per_second"]
+            .as_f64()
+            .ok_or_else(|| invalid("no numeric \"chunks_per_second\"".to_string()))?;
+
+        if bench["model"].as_str().is_some_and(|name| name != model) {
+            warn!("⚠️ Benchmark report was measured with model {}, not {}", bench["model"], model);
+        }
+        if bench["agent_count"].as_u64().is_some_and(|count| count as usize != agent_count) {
+            warn!("⚠️ Benchmark report was measured with {} agents, not {}", bench["agent_count"], agent_count);
+        }
+        Ok(Self { chunks_per_second, source: format!("benchmark {}", path.display()) })
+    }
+}
+
+/// Planned cost of a whole run
+#[derive(Debug, Clone)]
+pub struct RunPlan {
+    pub context_window: usize,
+    pub chunks: Vec<ChunkPlan>,
+    /// Files left out before chunking, with the reason
+    pub skipped: Vec<(String, String)>,
+    pub throughput: Option<Throughput>,
+}
+
+impl RunPlan {
+    pub fn new(context_window: usize) -> Self {
+        Self { context_window, chunks: Vec::new(), skipped: Vec::new(), throughput: None }
+    }
+
+    /// Add `chunk` with one `(input tokens, max_new_tokens)` pair per prompt it will run
+    pub fn add_chunk(&mut self, chunk: &Chunk, prompts: &[(usize, usize)], resumed: bool) {
+        self.chunks.push(ChunkPlan {
+            id: chunk.id(),
+            file: chunk.file_path.clone(),
+            lines: (chunk.line_start + 1, chunk.line_end.max(chunk.line_start + 1)),
+            input_tokens: prompts.iter().map(|(input, _)| input).sum(),
+            output_tokens: prompts.iter().map(|(_, output)| output).sum(),
+            peak_tokens: prompts
+                .iter()
+                .map(|(input, output)| input + output + PROMPT_TEMPLATE_RESERVE_TOKENS)
+                .max()
+                .unwrap_or(0),
+            resumed,
+        });
+    }
+
+    pub fn skip_file(&mut self, path: impl Into<String>, reason: impl Into<String>) {
+        self.skipped.push((path.into(), reason.into()));
+    }
+
+    pub fn with_throughput(mut self, throughput: Option<Throughput>) -> Self {
+        self.throughput = throughput;
+        self
+    }
+
+    /// Chunks the run would send to the model
+    pub fn to_run(&self) -> impl Iterator<Item = &ChunkPlan> {
+        self.chunks.iter().filter(|chunk| !chunk.resumed)
+    }
+
+    /// Chunks whose prompts would not fit the context window
+    pub fn over_budget(&self) -> impl Iterator<Item = &ChunkPlan> {
+        self.to_run().filter(|chunk| chunk.peak_tokens > self.context_window)
+    }
+
+    pub fn input_tokens(&self) -> usize {
+        self.to_run().map(|chunk| chunk.input_tokens).sum()
+    }
+
+    pub fn output_tokens(&self) -> usize {
+        self.to_run().map(|chunk| chunk.output_tokens).sum()
+    }
+
+    /// Wall time at the measured throughput, when one was measured
+    pub fn estimated_time(&self) -> Option<Duration> {
+        let throughput = self.throughput.as_ref().filter(|throughput| throughput.chunks_per_second > 0.0)?;
+        Some(Duration::from_secs_f64(self.to_run().count() as f64 / throughput.chunks_per_second))
+    }
+
+    /// Human-readable report, over-budget chunks and skipped files listed
+    pub fn render(&self) -> String {
+        let run = self.to_run().count();
+        let mut lines = vec![
+            format!("Chunks: {} ({} to run, {} already in the journal)", self.chunks.len(), run, self.chunks.len() - run),
+            format!("Input tokens: {}", self.input_tokens()),
+            format!("Output tokens: up to {}", self.output_tokens()),
+            format!("Context window: {} tokens", self.context_window),
+        ];
+        lines.push(match (self.estimated_time(), &self.throughput) {
+            (Some(estimate), Some(throughput)) => format!("Estimated time: {:.1}s at {:.2} chunks/second ({})",
+                                                          estimate.as_secs_f64(), throughput.chunks_per_second, throughput.source),
+            _ => "Estimated time: unknown (no calibration or benchmark)".to_string(),
+        });
+
+        let over_budget: Vec<&ChunkPlan> = self.over_budget().collect();
+        lines.push(format!("Over context window: {}", over_budget.len()));
+        for chunk in over_budget {
+            lines.push(format!("  {} lines {}-{}: {} tokens", chunk.file.as_deref().unwrap_or("input"),
+                               chunk.lines.0, chunk.lines.1, chunk.peak_tokens));
+        }
+        lines.push(format!("Skipped files: {}", self.skipped.len()));
+        for (path, reason) in &self.skipped {
+            lines.push(format!("  {}: {}", path, reason));
+        }
+        lines.join("\n")
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "chunks": self.chunks.len(),
+            "chunks_to_run": self.to_run().count(),
+            "input_tokens": self.input_tokens(),
+            "output_tokens": self.output_tokens(),
+            "context_window": self.context_window,
+            "estimated_time_s": self.estimated_time().map(|estimate| estimate.as_secs_f64()),
+            "throughput": self.throughput.as_ref().map(|throughput| json!({
+                "chunks_per_second": throughput.chunks_per_second,
+                "source": throughput.source,
+            })),
+            "over_budget": self.over_budget().map(|chunk| json!({
+                "id": chunk.id.as_str(),
+                "file": chunk.file,
+                "line_start": chunk.lines.0,
+                "line_end": chunk.lines.1,
+                "tokens": chunk.peak_tokens,
+            })).collect::<Vec<_>>(),
+            "skipped": self.skipped.iter().map(|(path, reason)| json!({"file": path, "reason": reason})).collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_totals_flags_over_budget_and_estimates_remaining_chunks() {
+        let chunk = |start: usize, content: &str| Chunk::new(start, start + 10, content.to_string());
+        let mut plan = RunPlan::new(512);
+        plan.add_chunk(&chunk(0, "fn a() {}"), &[(100, 60), (120, 40)], false);
+        plan.add_chunk(&chunk(10, "fn b() {}"), &[(480, 60)], false);
+        plan.add_chunk(&chunk(20, "fn c() {}"), &[(100, 60)], true);
+        plan.skip_file("dist/app.min.js", "minified");
+
+        assert_eq!(plan.input_tokens(), 700);
+        assert_eq!(plan.output_tokens(), 160);
+        let over: Vec<_> = plan.over_budget().map(|chunk| chunk.lines).collect();
+        assert_eq!(over, vec![(11, 20)]);
+        assert_eq!(plan.estimated_time(), None);
+
+        let plan = plan.with_throughput(Some(Throughput { chunks_per_second: 4.0, source: "calibration".to_string() }));
+        assert_eq!(plan.estimated_time(), Some(Duration::from_millis(500)));
+        assert!(plan.render().contains("Chunks: 3 (2 to run, 1 already in the journal)"));
+        assert_eq!(plan.to_json()["skipped"][0]["reason"], "minified");
+    }
+
+    #[test]
+    fn test_chunks_in_a_resumed_journal_are_planned_but_not_run() {
+        use crate::journal::{config_hash, recorded, JournalEntry, RunJournal};
+
+        let path = std::env::temp_dir().join(format!("dobby-plan-journal-{}.jsonl", std::process::id()));
+        let hash = config_hash(&["model", "prompt"]);
+        let chunks: Vec<Chunk> = ["fn a() {}", "fn b() {}", "fn c() {}"]
+            .iter()
+            .enumerate()
+            .map(|(index, content)| Chunk::new(index * 10, index * 10 + 10, content.to_string()))
+            .collect();
+        let mut journal = RunJournal::open(&path, hash.clone(), false).unwrap();
+        let entry = JournalEntry { summary: "Defines a".to_string(), tasks: Vec::new(), elapsed: None, failure: None };
+        journal.record(&chunks[0].id(), entry).unwrap();
+        drop(journal);
+
+        // What `summarize --dry-run --resume` does: read the journal, never write it
+        let finished = recorded(&path, &hash).unwrap();
+        let mut plan = RunPlan::new(512);
+        for chunk in &chunks {
+            plan.add_chunk(chunk, &[(100, 60)], finished.contains_key(chunk.id().as_str()));
+        }
+        let plan = plan.with_throughput(Some(Throughput { chunks_per_second: 2.0, source: "calibration".to_string() }));
+
+        assert_eq!(plan.to_run().map(|chunk| chunk.lines.0).collect::<Vec<_>>(), vec![11, 21]);
+        assert_eq!(plan.input_tokens(), 200);
+        assert_eq!(plan.estimated_time(), Some(Duration::from_secs(1)));
+        assert_eq!(plan.to_json()["chunks_to_run"], 2);
+        assert_eq!(recorded(&path, &hash).unwrap().len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_throughput_from_bench_report() {
+        let path = std::env::temp_dir().join(format!("dobby-plan-bench-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"model": "smollm2-135m", "agent_count": 4, "chunks_per_second": 2.5}"#).unwrap();
+
+        // Another agent count is warned about, not rejected
+        let throughput = Throughput::from_bench_report(&path, "smollm2-135m", 8).unwrap();
+        assert_eq!(throughput.chunks_per_second, 2.5);
+        assert!(throughput.source.starts_with("benchmark "));
+        let plan = RunPlan::new(512).with_throughput(Some(throughput));
+        assert!(plan.render().contains("at 2.50 chunks/second (benchmark "));
+
+        std::fs::write(&path, r#"{"model": "smollm2-135m", "iterations": 3}"#).unwrap();
+        let missing = Throughput::from_bench_report(&path, "smollm2-135m", 4).unwrap_err();
+        assert!(matches!(missing, ProcessingError::InvalidBenchReport { .. }));
+        assert!(missing.to_string().contains("chunks_per_second"));
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(Throughput::from_bench_report(&path, "smollm2-135m", 4).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,19 @@
+//! Prompt templates rendered per chunk
+//!
+//! Contracts:
+//! - Variables are written `{{name}}`; known names are `language`,
+//!   `language_confidence`, `file_path`, `line_start`, `line_end`, `symbols`
+//!   and `chunk`
+//! - Unknown variables and unclosed braces are rejected when the template is
+//!   parsed, before any model is loaded
+//! - `line_start`/`line_end` are 1-based and inclusive, as an editor shows them
+//! - `symbols` lists the items the chunk defines as `kind name`, comma-separated
+//! - A template without variables renders to itself, so plain prompts keep working
+//! - Tasks are named templates with their own generation settings; every task
+//!   runs against every chunk and results stay keyed by task name
+
+pub mod tasks;
+pub mod template;
+
+pub use tasks::{load_tasks, parse_tasks, render_task_outputs, task_outputs_json, PromptTask};
+pub use template::{PromptTemplate, PromptVariable, UNKNOWN_FILE};
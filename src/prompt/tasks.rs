@@ -0,0 +1,163 @@
+//! Named tasks run against every chunk: a summary, a risk list, a tagline, ...
+
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use super::template::PromptTemplate;
+use crate::config::{GenerationConfig, SamplingStrategy};
+use crate::errors::{ProcessingError, Result};
+
+/// One named prompt with its own generation settings
+#[derive(Debug, Clone)]
+pub struct PromptTask {
+    pub name: String,
+    pub template: PromptTemplate,
+    pub generation: GenerationConfig,
+}
+
+/// Load a tasks file; see `parse_tasks` for the format
+pub fn load_tasks(path: impl AsRef<Path>, base: &GenerationConfig) -> Result<Vec<PromptTask>> {
+    let path = path.as_ref();
+    parse_tasks(&std::fs::read_to_string(path)?, &path.display().to_string(), base)
+}
+
+/// Parse a JSON object mapping task names to `{"prompt": ..., <overrides>}`
+///
+/// Overrides are any of `sampling_strategy`, `temperature`, `top_p`, `top_k`,
+/// `num_beams`, `max_new_tokens`, `min_length`, `repetition_penalty` and
+/// `stop_sequences`; the rest comes from `base`. Tasks keep file order.
+///
+/// # Errors
+/// * `ProcessingError::InvalidTasks` - malformed JSON, no tasks, bad or unknown fields
+/// * `ProcessingError::InvalidPromptTemplate` - a task prompt fails to parse
+pub fn parse_tasks(text: &str, source: &str, base: &GenerationConfig) -> Result<Vec<PromptTask>> {
+    let invalid = |reason: String| ProcessingError::InvalidTasks {
+        path: source.to_string(),
+        reason,
+    };
+
+    let value: Value = serde_json::from_str(text).map_err(|e| invalid(e.to_string()))?;
+    let Value::Object(entries) = value else {
+        return Err(invalid("expected an object of task name to task".to_string()));
+    };
+    if entries.is_empty() {
+        return Err(invalid("no tasks defined".to_string()));
+    }
+
+    entries
+        .into_iter()
+        .map(|(name, task)| {
+            let Value::Object(fields) = task else {
+                return Err(invalid(format!("task \"{}\" must be an object", name)));
+            };
+            let prompt = fields
+                .get("prompt")
+                .and_then(Value::as_str)
+                .ok_or_else(|| invalid(format!("task \"{}\" is missing string field \"prompt\"", name)))?;
+            let template = PromptTemplate::parse(prompt)?;
+            let generation =
+                generation_overrides(base, &fields).map_err(|reason| invalid(format!("task \"{}\": {}", name, reason)))?;
+            Ok(PromptTask { name, template, generation })
+        })
+        .collect()
+}
+
+/// `base` with the task's generation fields applied
+fn generation_overrides(base: &GenerationConfig, fields: &Map<String, Value>) -> std::result::Result<GenerationConfig, String> {
+    let mut config = base.clone();
+    for (key, value) in fields {
+        let float = || value.as_f64().map(|v| v as f32).ok_or_else(|| format!("\"{}\" must be a number", key));
+        let count = || value.as_u64().map(|v| v as usize).ok_or_else(|| format!("\"{}\" must be a non-negative integer", key));
+        match key.as_str() {
+            "prompt" => {}
+            "sampling_strategy" => {
+                config.strategy = match value.as_str() {
+                    Some("sampling") => SamplingStrategy::Sampling,
+                    Some("beam") => SamplingStrategy::Beam,
+                    _ => return Err("\"sampling_strategy\" must be \"sampling\" or \"beam\"".to_string()),
+                }
+            }
+            "temperature" => config.temperature = float()?,
+            "top_p" => config.top_p = float()?,
+            "top_k" => config.top_k = count()?,
+            "num_beams" => config.num_beams = count()?,
+            "max_new_tokens" => config.max_new_tokens = count()?,
+            "min_length" => config.min_length = count()?,
+            "repetition_penalty" => config.repetition_penalty = float()?,
+            "stop_sequences" => {
+                config.stop_sequences = value
+                    .as_array()
+                    .and_then(|items| items.iter().map(|item| item.as_str().map(str::to_string)).collect())
+                    .ok_or_else(|| "\"stop_sequences\" must be an array of strings".to_string())?
+            }
+            unknown => return Err(format!("unknown field \"{}\"", unknown)),
+        }
+    }
+    if config.min_length > config.max_new_tokens {
+        return Err(format!("min_length ({}) exceeds max_new_tokens ({})", config.min_length, config.max_new_tokens));
+    }
+    Ok(config)
+}
+
+/// Task outputs of one chunk as `[name]` sections, in task order
+pub fn render_task_outputs(tasks: &[PromptTask], outputs: &[String]) -> String {
+    tasks
+        .iter()
+        .zip(outputs)
+        .map(|(task, output)| format!("[{}]\n{}", task.name, output.trim_end()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Task outputs of one chunk as a JSON object keyed by task name
+pub fn task_outputs_json(tasks: &[PromptTask], outputs: &[String]) -> Value {
+    Value::Object(
+        tasks
+            .iter()
+            .zip(outputs)
+            .map(|(task, output)| (task.name.clone(), Value::String(output.clone())))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tasks_keeps_order_and_inherits_generation_settings() {
+        let text = r#"{
+            "summary": {"prompt": "Summarize {{file_path}}."},
+            "risks": {"prompt": "List risks and TODOs.", "temperature": 0.1, "max_new_tokens": 120},
+            "tagline": {"prompt": "One line.", "max_new_tokens": 20, "min_length": 5, "stop_sequences": ["\n"]}
+        }"#;
+        let tasks = parse_tasks(text, "tasks.json", &GenerationConfig::default()).unwrap();
+
+        let names: Vec<&str> = tasks.iter().map(|task| task.name.as_str()).collect();
+        assert_eq!(names, vec!["summary", "risks", "tagline"]);
+        assert_eq!(tasks[0].generation.max_new_tokens, 60);
+        assert!((tasks[1].generation.temperature - 0.1).abs() < 1e-6);
+        assert_eq!(tasks[1].generation.max_new_tokens, 120);
+        assert_eq!(tasks[2].generation.stop_sequences, vec!["\n"]);
+
+        let outputs = vec!["Parses input.".to_string(), "None.\n".to_string(), "A parser.".to_string()];
+        assert_eq!(render_task_outputs(&tasks, &outputs), "[summary]\nParses input.\n[risks]\nNone.\n[tagline]\nA parser.");
+        assert_eq!(task_outputs_json(&tasks, &outputs)["tagline"], "A parser.");
+    }
+
+    #[test]
+    fn test_parse_tasks_rejects_bad_definitions() {
+        let base = GenerationConfig::default();
+        let error = |text: &str| parse_tasks(text, "tasks.json", &base).unwrap_err().to_string();
+
+        assert!(error("{}").contains("no tasks defined"));
+        assert!(error(r#"{"a": {"temperature": 0.2}}"#).contains("task \"a\" is missing string field \"prompt\""));
+        assert!(error(r#"{"a": {"prompt": "x", "temprature": 0.2}}"#).contains("unknown field \"temprature\""));
+        assert!(error(r#"{"a": {"prompt": "x", "max_new_tokens": 10}}"#).contains("min_length (35) exceeds"));
+        assert!(matches!(
+            parse_tasks(r#"{"a": {"prompt": "{{nope}}"}}"#, "tasks.json", &base),
+            Err(ProcessingError::InvalidPromptTemplate(_))
+        ));
+    }
+}
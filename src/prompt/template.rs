@@ -0,0 +1,204 @@
+//! `{{variable}}` prompt templates filled from chunk metadata
+
+use crate::chunking::Chunk;
+use crate::errors::{ProcessingError, Result};
+
+/// Placeholder a template may reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptVariable {
+    Language,
+    LanguageConfidence,
+    FilePath,
+    LineStart,
+    LineEnd,
+    Symbols,
+    Chunk,
+}
+
+impl PromptVariable {
+    pub const ALL: [PromptVariable; 7] = [
+        PromptVariable::Language,
+        PromptVariable::LanguageConfidence,
+        PromptVariable::FilePath,
+        PromptVariable::LineStart,
+        PromptVariable::LineEnd,
+        PromptVariable::Symbols,
+        PromptVariable::Chunk,
+    ];
+
+    /// Name used between the braces
+    pub fn name(&self) -> &'static str {
+        match self {
+            PromptVariable::Language => "language",
+            PromptVariable::LanguageConfidence => "language_confidence",
+            PromptVariable::FilePath => "file_path",
+            PromptVariable::LineStart => "line_start",
+            PromptVariable::LineEnd => "line_end",
+            PromptVariable::Symbols => "symbols",
+            PromptVariable::Chunk => "chunk",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|variable| variable.name() == name)
+    }
+
+    /// Value for `chunk`; lines are 1-based and inclusive
+    fn value(&self, chunk: &Chunk) -> String {
+        match self {
+            PromptVariable::Language => chunk.language.to_string(),
+            PromptVariable::LanguageConfidence => format!("{:.2}", chunk.language_confidence),
+            PromptVariable::FilePath => chunk.file_path.clone().unwrap_or_else(|| UNKNOWN_FILE.to_string()),
+            PromptVariable::LineStart => (chunk.line_start + 1).to_string(),
+            PromptVariable::LineEnd => chunk.line_end.to_string(),
+            PromptVariable::Symbols if chunk.symbols.is_empty() => chunk.symbol.clone().unwrap_or_default(),
+            PromptVariable::Symbols => chunk.symbols.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "),
+            PromptVariable::Chunk => chunk.content.clone(),
+        }
+    }
+}
+
+/// `{{file_path}}` for chunks that did not come from a named file
+pub const UNKNOWN_FILE: &str = "<input>";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Variable(PromptVariable),
+}
+
+/// Prompt compiled once at startup and rendered per chunk
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplate {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl PromptTemplate {
+    /// Compile `source`; `{{ name }}` may carry surrounding spaces
+    ///
+    /// # Errors
+    /// * `ProcessingError::InvalidPromptTemplate` - unknown variable or unclosed `{{`
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut rest = source;
+
+        while let Some(open) = rest.find("{{") {
+            if open > 0 {
+                segments.push(Segment::Text(rest[..open].to_string()));
+            }
+            let after = &rest[open + 2..];
+            let close = after.find("}}").ok_or_else(|| {
+                ProcessingError::InvalidPromptTemplate(format!("unclosed \"{{{{\" at byte {}", source.len() - rest.len() + open))
+            })?;
+            let name = after[..close].trim();
+            let variable = PromptVariable::from_name(name).ok_or_else(|| {
+                let known: Vec<&str> = PromptVariable::ALL.iter().map(PromptVariable::name).collect();
+                ProcessingError::InvalidPromptTemplate(format!(
+                    "unknown variable \"{{{{{}}}}}\" (expected one of: {})",
+                    name,
+                    known.join(", ")
+                ))
+            })?;
+            segments.push(Segment::Variable(variable));
+            rest = &after[close + 2..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+
+        Ok(Self { source: source.to_string(), segments })
+    }
+
+    /// Template text as written, including appended text
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether `variable` occurs anywhere in the template
+    pub fn uses(&self, variable: PromptVariable) -> bool {
+        self.segments.contains(&Segment::Variable(variable))
+    }
+
+    /// Whether every chunk gets the same prompt
+    pub fn is_static(&self) -> bool {
+        self.segments.iter().all(|segment| matches!(segment, Segment::Text(_)))
+    }
+
+    /// Append literal text; braces in `text` are not interpreted
+    pub fn append_text(&mut self, text: &str) {
+        self.source.push_str(text);
+        match self.segments.last_mut() {
+            Some(Segment::Text(last)) => last.push_str(text),
+            _ => self.segments.push(Segment::Text(text.to_string())),
+        }
+    }
+
+    /// Prompt for one chunk
+    pub fn render(&self, chunk: &Chunk) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.clone(),
+                Segment::Variable(variable) => variable.value(chunk),
+            })
+            .collect()
+    }
+
+    /// Prompt for one chunk without its code: `{{chunk}}` renders empty, the
+    /// other variables are filled in as by `render`
+    pub fn render_frame(&self, chunk: &Chunk) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.clone(),
+                Segment::Variable(PromptVariable::Chunk) => String::new(),
+                Segment::Variable(variable) => variable.value(chunk),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunking::semantic::ItemKind;
+    use crate::chunking::{SourceLanguage, Symbol};
+
+    #[test]
+    fn test_render_fills_chunk_metadata() {
+        let template = PromptTemplate::parse(
+            "Summarize {{language}} from {{ file_path }}:{{line_start}}-{{line_end}} ({{symbols}}):\n{{chunk}}",
+        )
+        .unwrap();
+        let chunk = Chunk::new(9, 12, "fn run() {}\n".to_string())
+            .with_language(SourceLanguage::Rust)
+            .with_file_path("src/main.rs")
+            .with_symbol(Some("run".to_string()));
+
+        assert!(template.uses(PromptVariable::Chunk));
+        assert_eq!(template.render(&chunk), "Summarize rust from src/main.rs:10-12 (run):\nfn run() {}\n");
+        assert_eq!(template.render_frame(&chunk), "Summarize rust from src/main.rs:10-12 (run):\n");
+
+        let mut listed = chunk.clone();
+        listed.symbols = vec![Symbol::new("run", ItemKind::Function, 9, 10), Symbol::new("Args", ItemKind::Struct, 10, 12)];
+        assert!(template.render(&listed).contains("(fn run, struct Args)"));
+
+        let mut plain = PromptTemplate::parse("Summarize this code.").unwrap();
+        assert!(plain.is_static());
+        plain.append_text(" Respond with {\"a\":{{}}}");
+        assert_eq!(plain.render(&chunk), "Summarize this code. Respond with {\"a\":{{}}}");
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_and_unclosed_variables() {
+        let unknown = PromptTemplate::parse("Explain {{filename}}").unwrap_err();
+        assert!(unknown.to_string().contains("{{filename}}"));
+        assert!(unknown.to_string().contains("file_path"));
+
+        assert!(matches!(
+            PromptTemplate::parse("Explain {{chunk"),
+            Err(ProcessingError::InvalidPromptTemplate(_))
+        ));
+    }
+}
@@ -0,0 +1,513 @@
+//! Incremental JSON validator used to mask decoder candidates
+//!
+//! A pushdown automaton over characters: each frame is the innermost thing
+//! still open (a literal to finish, a value to start, an object between
+//! members, ...). A piece is allowed when every character it holds keeps
+//! the automaton alive, which is exactly the token mask a sampler needs.
+//! JSON whitespace is accepted between tokens, not before or after the document.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use super::schema::SchemaNode;
+
+/// Position inside a string being generated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    /// Just after a backslash; `low` when it must open a low surrogate
+    Start { low: bool },
+    /// Inside `\uXXXX` with `seen` hex digits making up `value`
+    Unicode { seen: u8, value: u16, low: bool },
+    /// After a high surrogate escape, before the `\` of its low half
+    Surrogate,
+}
+
+/// Position inside a number, following the JSON number grammar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumberState {
+    Sign,
+    Zero,
+    Int,
+    Dot,
+    Frac,
+    Exp,
+    ExpSign,
+    ExpDigits,
+}
+
+impl NumberState {
+    fn is_accepting(self) -> bool {
+        matches!(self, NumberState::Zero | NumberState::Int | NumberState::Frac | NumberState::ExpDigits)
+    }
+
+    fn next(self, c: char, integer: bool) -> Option<NumberState> {
+        use NumberState::*;
+        match (self, c) {
+            (Sign, '0') => Some(Zero),
+            (Sign, '1'..='9') => Some(Int),
+            (Int, '0'..='9') => Some(Int),
+            (Zero | Int, '.') if !integer => Some(Dot),
+            (Zero | Int | Frac, 'e' | 'E') if !integer => Some(Exp),
+            (Dot | Frac, '0'..='9') => Some(Frac),
+            (Exp, '+' | '-') => Some(ExpSign),
+            (Exp | ExpSign | ExpDigits, '0'..='9') => Some(ExpDigits),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Frame {
+    /// A value of this schema must start next
+    Value(Arc<SchemaNode>),
+    /// These exact characters must follow
+    Literal(String),
+    /// A structural token or key, which whitespace may precede
+    Token(String),
+    /// Between members; `next` is the index of the next property
+    Object { node: Arc<SchemaNode>, next: usize },
+    /// Between elements; `count` elements started so far
+    Array { node: Arc<SchemaNode>, count: usize },
+    Str { node: Arc<SchemaNode>, content: String, escape: Escape },
+    Number { integer: bool, state: NumberState },
+}
+
+/// Accepts exactly the prefixes of documents valid against a schema
+#[derive(Debug, Clone)]
+pub struct JsonConstraint {
+    stack: Vec<Frame>,
+    output: String,
+}
+
+impl JsonConstraint {
+    pub fn new(root: Arc<SchemaNode>) -> Self {
+        Self {
+            stack: vec![Frame::Value(root)],
+            output: String::new(),
+        }
+    }
+
+    /// Text accepted so far
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    /// True once the accepted text is a complete document
+    pub fn is_complete(&self) -> bool {
+        self.stack
+            .iter()
+            .all(|frame| matches!(frame, Frame::Number { state, .. } if state.is_accepting()))
+    }
+
+    /// Whether appending `piece` keeps the output a valid prefix
+    pub fn allows(&self, piece: &str) -> bool {
+        !piece.is_empty() && self.clone().feed(piece)
+    }
+
+    /// Indices of the vocabulary pieces allowed next (the sampler's token mask)
+    pub fn allowed_tokens(&self, vocabulary: &[String]) -> Vec<usize> {
+        vocabulary
+            .iter()
+            .enumerate()
+            .filter(|(_, piece)| self.allows(piece))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Append `piece` if allowed; returns whether it was accepted
+    pub fn push(&mut self, piece: &str) -> bool {
+        let mut next = self.clone();
+        if piece.is_empty() || !next.feed(piece) {
+            return false;
+        }
+        *self = next;
+        true
+    }
+
+    /// Shortest text that turns the accepted prefix into a complete document
+    pub fn completion(&self) -> String {
+        let mut probe = self.clone();
+        let mut suffix = String::new();
+
+        while let Some(frame) = probe.stack.last() {
+            let closing = match frame {
+                Frame::Number { state, .. } if state.is_accepting() => {
+                    probe.stack.pop();
+                    continue;
+                }
+                // A lone sign takes "1" ("-0" would parse as a float); other states take a digit
+                Frame::Number { state: NumberState::Sign, .. } => "1".to_string(),
+                Frame::Number { .. } => "0".to_string(),
+                Frame::Literal(text) | Frame::Token(text) => text.clone(),
+                Frame::Value(node) => node.default_json(),
+                Frame::Object { node, next } => {
+                    let SchemaNode::Object(fields) = &**node else { unreachable!("object frame on object node") };
+                    let mut closing: String = (*next..fields.len())
+                        .map(|index| format!("{}{}", member_prefix(fields, index), fields[index].1.default_json()))
+                        .collect();
+                    closing.push('}');
+                    closing
+                }
+                Frame::Array { .. } => "]".to_string(),
+                Frame::Str { node, content, escape } => {
+                    let mut closing = match *escape {
+                        Escape::None => String::new(),
+                        Escape::Start { low: false } => "n".to_string(),
+                        Escape::Start { low: true } => "udc00".to_string(),
+                        Escape::Unicode { seen, value, low } => {
+                            let first = unicode_range(seen, value).start;
+                            let target = if low { first.max(0xDC00) } else { first };
+                            let mut digits = format!("{:04x}", target)[seen as usize..].to_string();
+                            if (0xD800..0xDC00).contains(&target) {
+                                digits.push_str("\\udc00");
+                            }
+                            digits
+                        }
+                        Escape::Surrogate => "\\udc00".to_string(),
+                    };
+                    if let SchemaNode::String { choices: Some(choices), .. } = &**node {
+                        if let Some(choice) = choices.iter().find(|choice| choice.starts_with(content.as_str())) {
+                            closing.push_str(&choice[content.len()..]);
+                        }
+                    }
+                    closing.push('"');
+                    closing
+                }
+            };
+
+            let accepted = probe.feed(&closing);
+            debug_assert!(accepted, "completion {:?} rejected", closing);
+            suffix.push_str(&closing);
+        }
+
+        suffix
+    }
+
+    /// Accepted text closed into a complete document
+    pub fn finish(self) -> String {
+        let completion = self.completion();
+        self.output + &completion
+    }
+
+    /// Feed characters in place; the state is unspecified after a rejection
+    fn feed(&mut self, piece: &str) -> bool {
+        for c in piece.chars() {
+            if !self.feed_char(c) {
+                return false;
+            }
+            self.output.push(c);
+        }
+        true
+    }
+
+    fn feed_char(&mut self, c: char) -> bool {
+        loop {
+            let Some(frame) = self.stack.pop() else { return false };
+
+            // Between tokens whitespace leaves the frame waiting as it was
+            if is_json_whitespace(c) && self.between_tokens(&frame) {
+                self.stack.push(frame);
+                return true;
+            }
+
+            match frame {
+                Frame::Literal(mut text) => {
+                    if !text.starts_with(c) {
+                        return false;
+                    }
+                    text.remove(0);
+                    if !text.is_empty() {
+                        self.stack.push(Frame::Literal(text));
+                    }
+                    return true;
+                }
+                Frame::Token(text) => {
+                    self.stack.push(Frame::Literal(text));
+                    // Re-feed `c` to the token's first character
+                }
+                Frame::Value(node) => return self.start_value(node, c),
+                Frame::Object { node, next } => {
+                    let SchemaNode::Object(fields) = &*node else { unreachable!("object frame on object node") };
+                    if next == fields.len() {
+                        self.stack.push(Frame::Token("}".to_string()));
+                    } else {
+                        let child = Arc::clone(&fields[next].1);
+                        self.stack.push(Frame::Object { node: Arc::clone(&node), next: next + 1 });
+                        self.stack.push(Frame::Value(child));
+                        self.stack.push(Frame::Token(":".to_string()));
+                        self.stack.push(Frame::Token(Value::from(fields[next].0.as_str()).to_string()));
+                        if next > 0 {
+                            self.stack.push(Frame::Token(",".to_string()));
+                        }
+                    }
+                    // Re-feed `c` to the frame just pushed
+                }
+                Frame::Array { node, count } => {
+                    let SchemaNode::Array { items, max_items } = &*node else { unreachable!("array frame on array node") };
+                    if c == ']' {
+                        return true;
+                    }
+                    if max_items.is_some_and(|max| count >= max) {
+                        return false;
+                    }
+                    let items = Arc::clone(items);
+                    self.stack.push(Frame::Array { node: Arc::clone(&node), count: count + 1 });
+                    if count > 0 {
+                        if c != ',' {
+                            return false;
+                        }
+                        self.stack.push(Frame::Value(items));
+                        return true;
+                    }
+                    self.stack.push(Frame::Value(items));
+                }
+                Frame::Str { node, content, escape } => return self.string_char(node, content, escape, c),
+                Frame::Number { integer, state } => match state.next(c, integer) {
+                    Some(state) => {
+                        self.stack.push(Frame::Number { integer, state });
+                        return true;
+                    }
+                    // The number ended; `c` belongs to the enclosing frame
+                    None if state.is_accepting() => {}
+                    None => return false,
+                },
+            }
+        }
+    }
+
+    /// Whether whitespace may come next while `frame` is on top of the stack
+    fn between_tokens(&self, frame: &Frame) -> bool {
+        match frame {
+            Frame::Token(_) | Frame::Object { .. } | Frame::Array { .. } => true,
+            // Not before the document's first character
+            Frame::Value(_) => !self.output.is_empty(),
+            Frame::Literal(_) | Frame::Str { .. } | Frame::Number { .. } => false,
+        }
+    }
+
+    fn start_value(&mut self, node: Arc<SchemaNode>, c: char) -> bool {
+        let frame = match (&*node, c) {
+            (SchemaNode::Object(_), '{') => Frame::Object { node: Arc::clone(&node), next: 0 },
+            (SchemaNode::Array { .. }, '[') => Frame::Array { node: Arc::clone(&node), count: 0 },
+            (SchemaNode::String { .. }, '"') => Frame::Str {
+                node: Arc::clone(&node),
+                content: String::new(),
+                escape: Escape::None,
+            },
+            (SchemaNode::Number { integer }, '-') => Frame::Number { integer: *integer, state: NumberState::Sign },
+            (SchemaNode::Number { integer }, '0') => Frame::Number { integer: *integer, state: NumberState::Zero },
+            (SchemaNode::Number { integer }, '1'..='9') => Frame::Number { integer: *integer, state: NumberState::Int },
+            (SchemaNode::Boolean, 't') => Frame::Literal("rue".to_string()),
+            (SchemaNode::Boolean, 'f') => Frame::Literal("alse".to_string()),
+            (SchemaNode::Null, 'n') => Frame::Literal("ull".to_string()),
+            _ => return false,
+        };
+        self.stack.push(frame);
+        true
+    }
+
+    fn string_char(&mut self, node: Arc<SchemaNode>, mut content: String, escape: Escape, c: char) -> bool {
+        let SchemaNode::String { choices, max_length } = &*node else { unreachable!("string frame on string node") };
+        let has_room = max_length.is_none_or(|max| content.chars().count() < max);
+
+        let escape = match escape {
+            Escape::Start { low } => match c {
+                'u' => Escape::Unicode { seen: 0, value: 0, low },
+                '"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' if !low => {
+                    content.push(c);
+                    Escape::None
+                }
+                _ => return false,
+            },
+            Escape::Unicode { seen, value, low } => {
+                let Some(digit) = c.to_digit(16) else { return false };
+                let (seen, value) = (seen + 1, value * 16 + digit as u16);
+                if !unicode_prefix_allowed(seen, value, low) {
+                    return false;
+                }
+                match seen {
+                    4 if (0xD800..0xDC00).contains(&value) => Escape::Surrogate,
+                    4 => {
+                        // Stands in for the escaped character when counting length
+                        content.push('?');
+                        Escape::None
+                    }
+                    _ => Escape::Unicode { seen, value, low },
+                }
+            }
+            Escape::Surrogate => match c {
+                '\\' => Escape::Start { low: true },
+                _ => return false,
+            },
+            Escape::None => match c {
+                '"' => {
+                    return choices.as_ref().is_none_or(|choices| choices.contains(&content));
+                }
+                // Enum values are matched literally, so they never take escapes
+                '\\' if choices.is_none() && has_room => Escape::Start { low: false },
+                c if c < ' ' || c == '\\' || !has_room => return false,
+                c => {
+                    content.push(c);
+                    if let Some(choices) = choices {
+                        if !choices.iter().any(|choice| choice.starts_with(content.as_str())) {
+                            return false;
+                        }
+                    }
+                    Escape::None
+                }
+            },
+        };
+
+        self.stack.push(Frame::Str { node, content, escape });
+        true
+    }
+}
+
+/// Space, tab, line feed or carriage return
+fn is_json_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
+
+/// Code units a `\uXXXX` escape starting with `seen` digits of `value` can reach
+fn unicode_range(seen: u8, value: u16) -> std::ops::Range<u32> {
+    let shift = 4 * (4 - u32::from(seen));
+    let first = u32::from(value) << shift;
+    first..first + (1 << shift)
+}
+
+/// A low surrogate exactly when `low`; otherwise anything but a lone low surrogate
+fn unicode_prefix_allowed(seen: u8, value: u16, low: bool) -> bool {
+    let range = unicode_range(seen, value);
+    if low {
+        range.start < 0xE000 && range.end > 0xDC00
+    } else {
+        !(range.start >= 0xDC00 && range.end <= 0xE000)
+    }
+}
+
+/// `,"name":` (no comma before the first property)
+fn member_prefix(fields: &[(String, Arc<SchemaNode>)], index: usize) -> String {
+    let separator = if index > 0 { "," } else { "" };
+    format!("{}{}:", separator, Value::from(fields[index].0.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structured::OutputSchema;
+    use serde_json::json;
+
+    fn schema() -> OutputSchema {
+        OutputSchema::from_json(json!({
+            "type": "object",
+            "properties": {
+                "kind": { "type": "string", "enum": ["function", "type"] },
+                "lines": { "type": "integer" },
+                "tags": { "type": "array", "items": { "type": "string", "maxLength": 3 }, "maxItems": 2 },
+                "pure": { "type": "boolean" },
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_masks_pieces_that_break_the_schema() {
+        let mut constraint = schema().constraint();
+
+        assert!(!constraint.allows(" {"));
+        assert!(constraint.push("{\"kind\":\"fun"));
+        assert!(!constraint.allows("x"));
+        assert!(constraint.push("ction\",\"lines\":42"));
+        assert!(!constraint.allows(".5"));
+        assert!(constraint.push(",\"tags\":[\"ab\",\"cde\""));
+        assert!(!constraint.allows(",\"x\""));
+        assert!(!constraint.allows("f\""));
+        assert!(constraint.push("],\"pure\":tr"));
+        assert!(!constraint.is_complete());
+        assert!(constraint.push("ue}"));
+        assert!(constraint.is_complete());
+        assert!(!constraint.allows("}"));
+
+        let value: Value = serde_json::from_str(constraint.output()).unwrap();
+        assert_eq!(value, json!({ "kind": "function", "lines": 42, "tags": ["ab", "cde"], "pure": true }));
+
+        let vocabulary: Vec<String> = ["}", "ab", "{\"", "{\"kind\""].iter().map(|s| s.to_string()).collect();
+        assert_eq!(schema().constraint().allowed_tokens(&vocabulary), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_any_accepted_prefix_completes_to_valid_json() {
+        let document = r#"{"kind":"type","lines":-12,"tags":["a\"b","xé"],"pure":false}"#;
+
+        for end in (0..=document.len()).filter(|&end| document.is_char_boundary(end)) {
+            let mut constraint = schema().constraint();
+            assert!(constraint.push(&document[..end]) || end == 0, "prefix {:?} rejected", &document[..end]);
+            let finished = constraint.finish();
+            let value: Value = serde_json::from_str(&finished).unwrap_or_else(|e| panic!("{:?}: {}", finished, e));
+            assert!(value["kind"] == "type" || value["kind"] == "function");
+            assert!(value["lines"].is_i64());
+        }
+    }
+
+    #[test]
+    fn test_accepts_whitespace_between_tokens_only() {
+        let value = json!({ "kind": "type", "lines": 7, "tags": ["a"], "pure": true });
+        let pretty = serde_json::to_string_pretty(&value).unwrap();
+
+        let mut constraint = schema().constraint();
+        assert!(constraint.push(&pretty));
+        assert!(constraint.is_complete());
+        assert!(!constraint.allows(" "));
+
+        let mut constraint = schema().constraint();
+        assert!(constraint.push("{ \"kind\"\t:\n\"type\" ,\r\n \"lines\" : 7 "));
+        assert!(!constraint.allows("\"ki nd\""));
+        assert!(constraint.push(", \"tags\": [ \"a\" , \"b\" ] , \"pure\": "));
+        assert!(!constraint.allows("t rue"));
+        assert!(constraint.push("true }"));
+        let value: Value = serde_json::from_str(constraint.output()).unwrap();
+        assert_eq!(value["tags"], json!(["a", "b"]));
+
+        for end in (0..=pretty.len()).filter(|&end| pretty.is_char_boundary(end)) {
+            let mut constraint = schema().constraint();
+            assert!(constraint.push(&pretty[..end]) || end == 0, "prefix {:?} rejected", &pretty[..end]);
+            assert!(serde_json::from_str::<Value>(&constraint.finish()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_requires_low_surrogate_after_high() {
+        let schema = OutputSchema::from_json(json!({
+            "type": "object",
+            "properties": { "note": { "type": "string" } }
+        }))
+        .unwrap();
+
+        let mut constraint = schema.constraint();
+        assert!(constraint.push(r#"{"note":"\ud83d"#));
+        assert!(!constraint.allows("\""));
+        assert!(!constraint.allows("x"));
+        assert!(!constraint.allows(r"\n"));
+        assert!(!constraint.allows(r"\u0041"));
+        assert!(constraint.push(r"\ude00"));
+        assert!(constraint.push("\"}"));
+        let value: Value = serde_json::from_str(constraint.output()).unwrap();
+        assert_eq!(value["note"], "😀");
+
+        // A low surrogate never stands alone
+        let mut constraint = schema.constraint();
+        assert!(constraint.push(r#"{"note":"\ud"#));
+        assert!(!constraint.allows("c"));
+        assert!(constraint.allows("7"));
+
+        let document = r#"{"note":"a\ud83d\ude00\u00e9"}"#;
+        for end in 0..=document.len() {
+            let mut constraint = schema.constraint();
+            assert!(constraint.push(&document[..end]) || end == 0, "prefix {:?} rejected", &document[..end]);
+            let finished = constraint.finish();
+            serde_json::from_str::<Value>(&finished).unwrap_or_else(|e| panic!("{:?}: {}", finished, e));
+        }
+    }
+}
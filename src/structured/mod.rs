@@ -0,0 +1,16 @@
+//! Structured JSON summaries constrained by a caller-supplied JSON schema
+//!
+//! Contracts:
+//! - Schemas use a JSON Schema subset: `object` (`properties`), `array`
+//!   (`items`, `maxItems`), `string` (`enum`, `maxLength`), `integer`,
+//!   `number`, `boolean` and `null`
+//! - Object properties are emitted in declaration order and are all required
+//! - Output is compact JSON: no whitespace outside strings
+//! - `JsonConstraint` accepts only prefixes of valid documents and can close
+//!   any accepted prefix, so constrained output always parses
+
+pub mod constraint;
+pub mod schema;
+
+pub use constraint::JsonConstraint;
+pub use schema::{OutputSchema, SchemaNode};
@@ -0,0 +1,274 @@
+//! JSON schema subset understood by the constrained decoder
+
+use std::path::Path;
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use super::constraint::JsonConstraint;
+use crate::errors::{ProcessingError, Result};
+
+/// One node of a compiled output schema
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaNode {
+    /// Properties in emission order
+    Object(Vec<(String, Arc<SchemaNode>)>),
+    Array {
+        items: Arc<SchemaNode>,
+        max_items: Option<usize>,
+    },
+    String {
+        choices: Option<Vec<String>>,
+        max_length: Option<usize>,
+    },
+    Number {
+        integer: bool,
+    },
+    Boolean,
+    Null,
+}
+
+impl SchemaNode {
+    /// Compile a JSON Schema node
+    ///
+    /// # Errors
+    /// * `ProcessingError::InvalidSchema` - missing or unsupported `type`, or malformed keywords
+    pub fn from_json(schema: &Value) -> Result<Self> {
+        let kind = schema
+            .get("type")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ProcessingError::InvalidSchema(format!("missing \"type\" in {}", schema)))?;
+
+        match kind {
+            "object" => {
+                let properties = match schema.get("properties") {
+                    Some(Value::Object(properties)) => properties,
+                    None => return Ok(SchemaNode::Object(Vec::new())),
+                    Some(other) => {
+                        return Err(ProcessingError::InvalidSchema(format!("\"properties\" must be an object, got {}", other)))
+                    }
+                };
+                let fields = properties
+                    .iter()
+                    .map(|(name, node)| Ok((name.clone(), Arc::new(SchemaNode::from_json(node)?))))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(SchemaNode::Object(fields))
+            }
+            "array" => {
+                let items = schema
+                    .get("items")
+                    .ok_or_else(|| ProcessingError::InvalidSchema("array without \"items\"".to_string()))?;
+                Ok(SchemaNode::Array {
+                    items: Arc::new(SchemaNode::from_json(items)?),
+                    max_items: usize_keyword(schema, "maxItems")?,
+                })
+            }
+            "string" => {
+                let choices = match schema.get("enum") {
+                    Some(Value::Array(values)) if !values.is_empty() => Some(
+                        values
+                            .iter()
+                            .map(|value| {
+                                value.as_str().map(str::to_string).ok_or_else(|| {
+                                    ProcessingError::InvalidSchema(format!("string enum value {} is not a string", value))
+                                })
+                            })
+                            .collect::<Result<Vec<_>>>()?,
+                    ),
+                    None => None,
+                    Some(other) => {
+                        return Err(ProcessingError::InvalidSchema(format!("\"enum\" must be a non-empty array, got {}", other)))
+                    }
+                };
+                Ok(SchemaNode::String {
+                    choices,
+                    max_length: usize_keyword(schema, "maxLength")?,
+                })
+            }
+            "integer" => Ok(SchemaNode::Number { integer: true }),
+            "number" => Ok(SchemaNode::Number { integer: false }),
+            "boolean" => Ok(SchemaNode::Boolean),
+            "null" => Ok(SchemaNode::Null),
+            other => Err(ProcessingError::InvalidSchema(format!("unsupported type \"{}\"", other))),
+        }
+    }
+
+    /// Smallest valid document for this node
+    pub fn default_json(&self) -> String {
+        match self {
+            SchemaNode::Object(fields) => {
+                let members: Vec<String> = fields
+                    .iter()
+                    .map(|(name, node)| format!("{}:{}", Value::from(name.as_str()), node.default_json()))
+                    .collect();
+                format!("{{{}}}", members.join(","))
+            }
+            SchemaNode::Array { .. } => "[]".to_string(),
+            SchemaNode::String { choices: Some(choices), .. } => Value::from(choices[0].as_str()).to_string(),
+            SchemaNode::String { .. } => "\"\"".to_string(),
+            SchemaNode::Number { .. } => "0".to_string(),
+            SchemaNode::Boolean => "false".to_string(),
+            SchemaNode::Null => "null".to_string(),
+        }
+    }
+
+    /// Valid value holding `text` in the first free-form string field, defaults elsewhere
+    fn fill(&self, text: &mut Option<&str>) -> Value {
+        match self {
+            SchemaNode::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(name, node)| (name.clone(), node.fill(text)))
+                    .collect(),
+            ),
+            SchemaNode::String { choices: None, max_length } => {
+                let content = text.take().unwrap_or_default();
+                let content: String = match max_length {
+                    Some(max_length) => content.chars().take(*max_length).collect(),
+                    None => content.to_string(),
+                };
+                Value::String(content)
+            }
+            _ => serde_json::from_str(&self.default_json()).unwrap_or(Value::Null),
+        }
+    }
+}
+
+fn usize_keyword(schema: &Value, keyword: &str) -> Result<Option<usize>> {
+    match schema.get(keyword) {
+        None => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .map(|n| Some(n as usize))
+            .ok_or_else(|| ProcessingError::InvalidSchema(format!("\"{}\" must be a non-negative integer", keyword))),
+    }
+}
+
+/// Caller-supplied schema for structured summaries
+#[derive(Debug, Clone)]
+pub struct OutputSchema {
+    root: Arc<SchemaNode>,
+    source: Value,
+}
+
+impl OutputSchema {
+    /// Compile `schema`; the root must be an object
+    ///
+    /// # Errors
+    /// * `ProcessingError::InvalidSchema` - unsupported schema or non-object root
+    pub fn from_json(schema: Value) -> Result<Self> {
+        let root = SchemaNode::from_json(&schema)?;
+        if !matches!(root, SchemaNode::Object(_)) {
+            return Err(ProcessingError::InvalidSchema("root schema must be an object".to_string()));
+        }
+        Ok(Self { root: Arc::new(root), source: schema })
+    }
+
+    /// Load and compile a schema file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let raw = std::fs::read_to_string(path.as_ref())?;
+        let schema = serde_json::from_str(&raw)
+            .map_err(|e| ProcessingError::InvalidSchema(format!("{}: {}", path.as_ref().display(), e)))?;
+        Self::from_json(schema)
+    }
+
+    /// Purpose, public items, side effects, error paths and dependencies of a chunk
+    pub fn code_summary() -> Self {
+        let list = json!({ "type": "array", "items": { "type": "string" } });
+        Self::from_json(json!({
+            "type": "object",
+            "properties": {
+                "purpose": { "type": "string" },
+                "public_items": list,
+                "side_effects": list,
+                "error_paths": list,
+                "dependencies": list,
+            }
+        }))
+        .expect("built-in schema compiles")
+    }
+
+    pub fn root(&self) -> &Arc<SchemaNode> {
+        &self.root
+    }
+
+    /// The schema as supplied
+    pub fn as_json(&self) -> &Value {
+        &self.source
+    }
+
+    /// Fresh decoder constraint for one document
+    pub fn constraint(&self) -> JsonConstraint {
+        JsonConstraint::new(Arc::clone(&self.root))
+    }
+
+    /// Instruction appended to the prompt so the model aims for the schema
+    pub fn prompt_hint(&self) -> String {
+        format!("Respond only with compact JSON matching this schema: {}", self.source)
+    }
+
+    /// Valid document with `text` in the first free-form string field
+    pub fn fill(&self, text: &str) -> Value {
+        self.root.fill(&mut Some(text))
+    }
+
+    /// Force already generated text into the schema
+    ///
+    /// Text that starts like JSON is replayed character by character through
+    /// the constraint, dropping characters it would have masked, then closed.
+    /// Prose is placed into the first free-form string field.
+    pub fn constrain_text(&self, text: &str) -> Value {
+        let text = text.trim();
+        if !text.starts_with('{') {
+            return self.fill(text);
+        }
+
+        let mut constraint = self.constraint();
+        let mut buffer = [0u8; 4];
+        for c in text.chars() {
+            constraint.push(c.encode_utf8(&mut buffer));
+        }
+        serde_json::from_str(&constraint.finish()).unwrap_or_else(|_| self.fill(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compiles_schema_subset_and_rejects_unsupported() {
+        let schema = OutputSchema::code_summary();
+        let SchemaNode::Object(fields) = &**schema.root() else { panic!("object root") };
+        let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["purpose", "public_items", "side_effects", "error_paths", "dependencies"]);
+        assert_eq!(
+            schema.root().default_json(),
+            r#"{"purpose":"","public_items":[],"side_effects":[],"error_paths":[],"dependencies":[]}"#
+        );
+
+        assert!(matches!(OutputSchema::from_json(json!({ "type": "string" })), Err(ProcessingError::InvalidSchema(_))));
+        assert!(matches!(
+            OutputSchema::from_json(json!({ "type": "object", "properties": { "a": { "type": "tuple" } } })),
+            Err(ProcessingError::InvalidSchema(_))
+        ));
+    }
+
+    #[test]
+    fn test_constrain_text_always_yields_schema_shaped_json() {
+        let schema = OutputSchema::code_summary();
+
+        let prose = schema.constrain_text("Parses CLI arguments.");
+        assert_eq!(prose["purpose"], "Parses CLI arguments.");
+        assert_eq!(prose["public_items"], json!([]));
+
+        let cut_off = schema.constrain_text(r#"{"purpose":"Parses args","public_items":["Args","par"#);
+        assert_eq!(cut_off["purpose"], "Parses args");
+        assert_eq!(cut_off["public_items"], json!(["Args", "par"]));
+        assert_eq!(cut_off["dependencies"], json!([]));
+
+        let spaced = schema.constrain_text("{ \"purpose\": \"x\", \"extra\": 1 }");
+        assert!(spaced.is_object());
+        assert_eq!(spaced.as_object().unwrap().len(), 5);
+    }
+}
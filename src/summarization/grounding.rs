@@ -0,0 +1,274 @@
+//! Identifier grounding: flag summaries that name things the chunk never mentions
+//!
+//! Contracts:
+//! - Identifiers are backticked names, CamelCase words and snake_case words;
+//!   plain capitalized words ("Parses", "JSON") are prose, not identifiers
+//! - A path (`Foo::bar`, `cfg.name`, `run()`) is grounded when every segment
+//!   occurs in the chunk's tokens or defined symbols
+//! - The score is the grounded fraction; a summary naming nothing scores 1.0
+//! - Regeneration keeps whichever attempt scored best, so it never makes a
+//!   summary less grounded
+
+use std::collections::HashSet;
+
+use crate::chunking::Chunk;
+use crate::errors::Result;
+
+use super::hierarchy::Reducer;
+
+/// Grounding of one summary against its chunk
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroundingReport {
+    /// Grounded identifiers over all identifiers, in `[0, 1]`
+    pub score: f64,
+    /// Every identifier found in the summary, in order of first mention
+    pub identifiers: Vec<String>,
+    /// Identifiers with a segment the chunk never mentions
+    pub ungrounded: Vec<String>,
+}
+
+impl GroundingReport {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "score": self.score,
+            "ungrounded": self.ungrounded,
+        })
+    }
+}
+
+/// Identifiers mentioned by `summary`, deduplicated
+pub fn summary_identifiers(summary: &str) -> Vec<String> {
+    let mut identifiers: Vec<String> = Vec::new();
+    let mut push = |identifier: &str| {
+        let identifier = identifier.trim_end_matches("()").trim_matches(|c: char| !is_identifier_char(c));
+        if !identifier.is_empty() && !identifiers.iter().any(|known| known == identifier) {
+            identifiers.push(identifier.to_string());
+        }
+    };
+
+    // Backticked spans are identifiers whatever their shape
+    let mut prose = String::with_capacity(summary.len());
+    for (index, part) in summary.split('`').enumerate() {
+        if index % 2 == 1 {
+            push(part.trim());
+            prose.push(' ');
+        } else {
+            prose.push_str(part);
+        }
+    }
+
+    for word in prose.split(|c: char| !(is_identifier_char(c) || c == ':' || c == '.')) {
+        let word = word.trim_matches(|c: char| c == ':' || c == '.');
+        if segments(word).any(|segment| is_camel_case(segment) || is_snake_case(segment)) {
+            push(word);
+        }
+    }
+    identifiers
+}
+
+/// Check every identifier in `summary` against the tokens and symbols of `chunk`
+pub fn check_grounding(summary: &str, chunk: &Chunk) -> GroundingReport {
+    let mut known: HashSet<&str> = chunk
+        .content
+        .split(|c: char| !is_identifier_char(c))
+        .filter(|token| !token.is_empty())
+        .collect();
+    for symbol in &chunk.symbols {
+        known.extend(symbol.name.split(|c: char| !is_identifier_char(c)).filter(|s| !s.is_empty()));
+    }
+
+    let identifiers = summary_identifiers(summary);
+    let ungrounded: Vec<String> = identifiers
+        .iter()
+        .filter(|identifier| !segments(identifier).all(|segment| known.contains(segment)))
+        .cloned()
+        .collect();
+    let score = match identifiers.len() {
+        0 => 1.0,
+        total => (total - ungrounded.len()) as f64 / total as f64,
+    };
+
+    GroundingReport {
+        score,
+        identifiers,
+        ungrounded,
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Non-empty name segments of a path such as `Foo::bar` or `cfg.name`
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split(|c: char| !is_identifier_char(c)).filter(|segment| !segment.is_empty())
+}
+
+/// `HashMap`, `parseArgs`: a lowercase letter followed later by an uppercase one
+fn is_camel_case(word: &str) -> bool {
+    let mut seen_lower = false;
+    for c in word.chars() {
+        if c.is_lowercase() {
+            seen_lower = true;
+        } else if c.is_uppercase() && seen_lower {
+            return true;
+        }
+    }
+    false
+}
+
+/// `read_config`, `MAX_LINES`: letters joined by an inner underscore
+fn is_snake_case(word: &str) -> bool {
+    let inner = word.trim_matches('_');
+    inner.contains('_') && inner.chars().any(char::is_alphabetic)
+}
+
+/// Post-generation grounding check with optional regeneration
+#[derive(Debug, Clone)]
+pub struct GroundingCheck {
+    threshold: f64,
+    max_attempts: usize,
+}
+
+impl GroundingCheck {
+    /// Regenerate summaries scoring below `threshold`, once by default
+    pub fn new(threshold: f64) -> Self {
+        Self {
+            threshold,
+            max_attempts: 1,
+        }
+    }
+
+    /// Regeneration rounds for low-scoring summaries (0 = report only)
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// Prompt asking the model to drop the names it made up
+    pub fn correction_prompt(prompt: &str, ungrounded: &[String]) -> String {
+        format!(
+            "{}\nOnly mention functions, types and variables that appear in the code. \
+             These do not appear and must not be mentioned: {}.",
+            prompt,
+            ungrounded.join(", ")
+        )
+    }
+
+    /// Final summary and grounding for each `(chunk, prompt, summary)`, in order
+    ///
+    /// # Errors
+    /// * Any error returned by `reducer`
+    pub async fn enforce<R>(&self, items: Vec<(Chunk, String, String)>, reducer: &R) -> Result<Vec<(String, GroundingReport)>>
+    where
+        R: Reducer + ?Sized,
+    {
+        let mut best: Vec<(String, GroundingReport)> = items
+            .iter()
+            .map(|(chunk, _, summary)| (summary.clone(), check_grounding(summary, chunk)))
+            .collect();
+        // Every name any attempt made up, so retries cannot swap one invention for another
+        let mut banned: Vec<Vec<String>> = best.iter().map(|(_, report)| report.ungrounded.clone()).collect();
+
+        for _ in 0..self.max_attempts {
+            let pending: Vec<usize> = (0..items.len()).filter(|&i| best[i].1.score < self.threshold).collect();
+            if pending.is_empty() {
+                break;
+            }
+
+            let inputs = pending
+                .iter()
+                .map(|&i| {
+                    let (chunk, prompt, _) = &items[i];
+                    (chunk.content.clone(), Self::correction_prompt(prompt, &banned[i]))
+                })
+                .collect();
+            let outputs = reducer.reduce_each(inputs).await?;
+
+            for (i, summary) in pending.into_iter().zip(outputs) {
+                let report = check_grounding(&summary, &items[i].0);
+                for name in &report.ungrounded {
+                    if !banned[i].contains(name) {
+                        banned[i].push(name.clone());
+                    }
+                }
+                if report.score > best[i].1.score {
+                    best[i] = (summary, report);
+                }
+            }
+        }
+
+        Ok(best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunking::{Chunker, LineChunker, SourceLanguage};
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    const CODE: &str = "pub struct ConfigLoader { path: PathBuf }\n\nimpl ConfigLoader {\n    pub fn load_file(&self) -> Config { todo!() }\n}\n";
+
+    fn chunk() -> Chunk {
+        LineChunker::new(100).chunk(CODE, SourceLanguage::Rust, Some("src/config.rs")).unwrap().remove(0)
+    }
+
+    #[test]
+    fn test_flags_identifiers_missing_from_the_chunk() {
+        let summary = "Defines `ConfigLoader::load_file`, which reads a PathBuf via read_to_string and \
+                       returns a ConfigCache. Parses JSON.";
+        assert_eq!(
+            summary_identifiers(summary),
+            vec!["ConfigLoader::load_file", "PathBuf", "read_to_string", "ConfigCache"]
+        );
+
+        let report = check_grounding(summary, &chunk());
+        assert_eq!(report.ungrounded, vec!["read_to_string", "ConfigCache"]);
+        assert!((report.score - 0.5).abs() < 1e-9);
+
+        assert_eq!(check_grounding("Loads configuration from disk.", &chunk()).score, 1.0);
+    }
+
+    /// Returns queued answers and records the prompts it was given
+    struct Scripted {
+        answers: Mutex<Vec<String>>,
+        prompts: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Reducer for Scripted {
+        async fn reduce(&self, prompt: &str, inputs: Vec<String>) -> Result<Vec<String>> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            Ok(inputs.iter().map(|_| self.answers.lock().unwrap().remove(0)).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_regenerates_below_threshold_and_keeps_the_best_attempt() {
+        let reducer = Scripted {
+            answers: Mutex::new(vec!["Uses `FileCache` and `lazy_reader`.".to_string(), "Defines ConfigLoader.".to_string()]),
+            prompts: Mutex::new(Vec::new()),
+        };
+        let items = vec![
+            (chunk(), "Summarize.".to_string(), "Wraps `Loader` with `Cache`.".to_string()),
+            (chunk(), "Summarize.".to_string(), "Defines `ConfigLoader::load_file`.".to_string()),
+        ];
+
+        let results = GroundingCheck::new(0.8).with_max_attempts(2).enforce(items, &reducer).await.unwrap();
+
+        assert_eq!(results[0].0, "Defines ConfigLoader.");
+        assert_eq!(results[0].1.score, 1.0);
+        assert_eq!(results[1].0, "Defines `ConfigLoader::load_file`.");
+
+        let prompts = reducer.prompts.lock().unwrap();
+        assert_eq!(prompts.len(), 2, "only the ungrounded summary is regenerated");
+        assert!(prompts[0].ends_with("must not be mentioned: Loader, Cache."));
+        assert!(prompts[1].ends_with("must not be mentioned: Loader, Cache, FileCache, lazy_reader."));
+    }
+}
@@ -0,0 +1,494 @@
+//! Hierarchical map-reduce summarization: chunk → file → module → crate
+//!
+//! Contracts:
+//! - Chunk summaries are reduced per file, file summaries per module (parent
+//!   directory), and module summaries into one crate overview
+//! - Every reduce input, prompt included, fits the level's token budget;
+//!   larger groups are reduced in rounds until one summary remains
+//! - A group with a single child is passed through without a model call
+//! - Files and modules keep the order in which they first appear
+
+use std::path::Path;
+
+use async_trait::async_trait;
+
+use crate::chunking::structural::ESTIMATED_CHARS_PER_TOKEN;
+use crate::chunking::token_budget::PROMPT_TEMPLATE_RESERVE_TOKENS;
+use crate::chunking::Chunk;
+use crate::errors::{ProcessingError, Result};
+use crate::parallel_agents::ParallelAgentSystem;
+
+/// File label for chunks that carry no path (single-file input)
+pub const UNNAMED_SOURCE: &str = "<input>";
+
+/// Module label for files at the repository root
+pub const ROOT_MODULE: &str = ".";
+
+/// Levels produced by a reduce step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryLevel {
+    File,
+    Module,
+    Crate,
+}
+
+impl SummaryLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SummaryLevel::File => "file",
+            SummaryLevel::Module => "module",
+            SummaryLevel::Crate => "crate",
+        }
+    }
+
+    /// Prompt used to combine summaries into one of this level
+    pub fn default_prompt(&self) -> &'static str {
+        match self {
+            SummaryLevel::File => "Combine these summaries of consecutive parts of one source file into a single \
+                summary of the whole file: its purpose, its main types and functions, and how they fit together.",
+            SummaryLevel::Module => "Combine these file summaries into a summary of the module: its responsibility \
+                and how its files work together.",
+            SummaryLevel::Crate => "Combine these module summaries into a top-level overview of the codebase: what \
+                it does, its main components and how they interact.",
+        }
+    }
+
+    fn heading(&self) -> &'static str {
+        match self {
+            SummaryLevel::File => "File",
+            SummaryLevel::Module => "Module",
+            SummaryLevel::Crate => "Crate",
+        }
+    }
+}
+
+/// Token budget for the prompt plus input of each reduce call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelBudgets {
+    pub file: usize,
+    pub module: usize,
+    pub crate_overview: usize,
+}
+
+impl LevelBudgets {
+    pub fn uniform(tokens: usize) -> Self {
+        Self {
+            file: tokens,
+            module: tokens,
+            crate_overview: tokens,
+        }
+    }
+
+    /// Whatever the context window leaves after generation and the chat template
+    pub fn for_context(context_window: usize, max_new_tokens: usize) -> Self {
+        Self::uniform(context_window.saturating_sub(max_new_tokens + PROMPT_TEMPLATE_RESERVE_TOKENS))
+    }
+
+    pub fn for_level(&self, level: SummaryLevel) -> usize {
+        match level {
+            SummaryLevel::File => self.file,
+            SummaryLevel::Module => self.module,
+            SummaryLevel::Crate => self.crate_overview,
+        }
+    }
+}
+
+/// Model access for reduce steps
+#[async_trait]
+pub trait Reducer: Send + Sync {
+    /// Summarize each input with `prompt`; outputs are in input order
+    async fn reduce(&self, prompt: &str, inputs: Vec<String>) -> Result<Vec<String>>;
+
+    /// Summarize each `(input, prompt)` pair; outputs are in input order
+    ///
+    /// The default issues one `reduce` call per pair, one after another.
+    async fn reduce_each(&self, inputs: Vec<(String, String)>) -> Result<Vec<String>> {
+        let mut outputs = Vec::with_capacity(inputs.len());
+        for (input, prompt) in inputs {
+            outputs.extend(self.reduce(&prompt, vec![input]).await?);
+        }
+        Ok(outputs)
+    }
+}
+
+#[async_trait]
+impl Reducer for ParallelAgentSystem {
+    async fn reduce(&self, prompt: &str, inputs: Vec<String>) -> Result<Vec<String>> {
+        let inputs = inputs.into_iter().map(|input| (input, prompt.to_string())).collect();
+        self.reduce_each(inputs).await
+    }
+
+    async fn reduce_each(&self, inputs: Vec<(String, String)>) -> Result<Vec<String>> {
+        let expected = inputs.len();
+        let results = self
+            .process_prompted_chunks_parallel(inputs)
+            .await
+            .map_err(|e| ProcessingError::InferenceError(e.to_string()))?;
+        if results.len() != expected {
+            return Err(ProcessingError::InferenceError(format!(
+                "{} of {} reduce tasks failed",
+                expected - results.len(),
+                expected
+            )));
+        }
+        Ok(results.into_iter().map(|(_, summary)| summary).collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSummary {
+    pub path: String,
+    pub summary: String,
+    pub chunk_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleSummary {
+    pub path: String,
+    pub summary: String,
+    pub files: Vec<FileSummary>,
+}
+
+/// Crate overview with the module and file summaries it was built from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HierarchicalSummary {
+    pub overview: String,
+    pub modules: Vec<ModuleSummary>,
+}
+
+impl HierarchicalSummary {
+    /// Overview first, then each module followed by its files
+    pub fn render(&self) -> String {
+        let mut out = format!("# Overview\n\n{}\n", self.overview.trim());
+        for module in &self.modules {
+            out.push_str(&format!("\n## Module {}\n\n{}\n", module.path, module.summary.trim()));
+            for file in &module.files {
+                out.push_str(&format!("\n### {}\n\n{}\n", file.path, file.summary.trim()));
+            }
+        }
+        out
+    }
+}
+
+/// A group of summaries being reduced into one
+struct ReduceGroup {
+    label: String,
+    children: Vec<String>,
+}
+
+/// Rolls chunk summaries up into file, module and crate summaries
+pub struct HierarchicalSummarizer {
+    budgets: LevelBudgets,
+    measure: Box<dyn Fn(&str) -> usize + Send + Sync>,
+    file_prompt: String,
+    module_prompt: String,
+    crate_prompt: String,
+}
+
+impl HierarchicalSummarizer {
+    /// Summarizer measuring text in estimated tokens
+    pub fn new(budgets: LevelBudgets) -> Self {
+        Self {
+            budgets,
+            measure: Box::new(|text: &str| text.len() / ESTIMATED_CHARS_PER_TOKEN + 1),
+            file_prompt: SummaryLevel::File.default_prompt().to_string(),
+            module_prompt: SummaryLevel::Module.default_prompt().to_string(),
+            crate_prompt: SummaryLevel::Crate.default_prompt().to_string(),
+        }
+    }
+
+    /// Measure budgets with `measure` (e.g. the model tokenizer's token count)
+    pub fn with_measure<M>(mut self, measure: M) -> Self
+    where
+        M: Fn(&str) -> usize + Send + Sync + 'static,
+    {
+        self.measure = Box::new(measure);
+        self
+    }
+
+    /// Replace the reduce prompt of `level`
+    pub fn with_prompt(mut self, level: SummaryLevel, prompt: impl Into<String>) -> Self {
+        match level {
+            SummaryLevel::File => self.file_prompt = prompt.into(),
+            SummaryLevel::Module => self.module_prompt = prompt.into(),
+            SummaryLevel::Crate => self.crate_prompt = prompt.into(),
+        }
+        self
+    }
+
+    pub fn prompt(&self, level: SummaryLevel) -> &str {
+        match level {
+            SummaryLevel::File => &self.file_prompt,
+            SummaryLevel::Module => &self.module_prompt,
+            SummaryLevel::Crate => &self.crate_prompt,
+        }
+    }
+
+    /// Roll `chunk_summaries` (in source order) up to a crate overview
+    ///
+    /// # Errors
+    /// * `ProcessingError::ContractViolation` - a level's prompt leaves no room for input
+    /// * Any error returned by `reducer`
+    pub async fn summarize<R>(&self, chunk_summaries: &[(Chunk, String)], reducer: &R) -> Result<HierarchicalSummary>
+    where
+        R: Reducer + ?Sized,
+    {
+        let mut files: Vec<ReduceGroup> = Vec::new();
+        for (chunk, summary) in chunk_summaries {
+            let path = chunk.file_path.as_deref().unwrap_or(UNNAMED_SOURCE);
+            push_child(&mut files, path, summary.clone());
+        }
+        let chunk_counts: Vec<usize> = files.iter().map(|file| file.children.len()).collect();
+        let file_paths: Vec<String> = files.iter().map(|file| file.label.clone()).collect();
+        let file_summaries = self.reduce_level(SummaryLevel::File, files, reducer).await?;
+
+        let mut modules: Vec<ReduceGroup> = Vec::new();
+        let mut module_files: Vec<Vec<FileSummary>> = Vec::new();
+        for ((path, summary), chunk_count) in file_paths.into_iter().zip(file_summaries).zip(chunk_counts) {
+            let module = module_of(&path);
+            let index = push_child(&mut modules, &module, summary.clone());
+            if index == module_files.len() {
+                module_files.push(Vec::new());
+            }
+            module_files[index].push(FileSummary { path, summary, chunk_count });
+        }
+        let module_paths: Vec<String> = modules.iter().map(|module| module.label.clone()).collect();
+        let module_summaries = self.reduce_level(SummaryLevel::Module, modules, reducer).await?;
+
+        let overview_group = ReduceGroup {
+            label: "overview".to_string(),
+            children: module_summaries.clone(),
+        };
+        let overview = self
+            .reduce_level(SummaryLevel::Crate, vec![overview_group], reducer)
+            .await?
+            .pop()
+            .unwrap_or_default();
+
+        let modules = module_paths
+            .into_iter()
+            .zip(module_summaries)
+            .zip(module_files)
+            .map(|((path, summary), files)| ModuleSummary { path, summary, files })
+            .collect();
+
+        Ok(HierarchicalSummary { overview, modules })
+    }
+
+    /// Reduce every group to one summary, in rounds, batching all groups per round
+    async fn reduce_level<R>(&self, level: SummaryLevel, mut groups: Vec<ReduceGroup>, reducer: &R) -> Result<Vec<String>>
+    where
+        R: Reducer + ?Sized,
+    {
+        let prompt = self.prompt(level);
+        let budget = self.budgets.for_level(level).saturating_sub((self.measure)(prompt));
+
+        loop {
+            let mut inputs = Vec::new();
+            let mut batch_counts = Vec::with_capacity(groups.len());
+
+            for group in &groups {
+                if group.children.len() < 2 {
+                    batch_counts.push(0);
+                    continue;
+                }
+
+                let heading = format!("{}: {}\nPart summaries:", level.heading(), group.label);
+                let batches = self.pack(&group.children, budget.saturating_sub((self.measure)(&heading)))?;
+                batch_counts.push(batches.len());
+                inputs.extend(batches.into_iter().map(|batch| format!("{}\n{}", heading, batch.join("\n"))));
+            }
+
+            if inputs.is_empty() {
+                break;
+            }
+
+            log::info!("🧮 Reducing {} {} inputs", inputs.len(), level.as_str());
+            let expected = inputs.len();
+            let mut outputs = reducer.reduce(prompt, inputs).await?.into_iter();
+            if outputs.len() != expected {
+                return Err(ProcessingError::InferenceError(format!(
+                    "reducer returned {} summaries for {} {} inputs",
+                    outputs.len(),
+                    expected,
+                    level.as_str()
+                )));
+            }
+
+            for (group, count) in groups.iter_mut().zip(batch_counts) {
+                if count > 0 {
+                    group.children = outputs.by_ref().take(count).collect();
+                }
+            }
+        }
+
+        Ok(groups.into_iter().map(|group| group.children.into_iter().next().unwrap_or_default()).collect())
+    }
+
+    /// Bullet lines packed into batches that fit `budget`, at least two per batch
+    fn pack(&self, children: &[String], budget: usize) -> Result<Vec<Vec<String>>> {
+        // Half the budget per line guarantees every batch takes two, so rounds shrink
+        let line_budget = budget / 2;
+        if line_budget == 0 {
+            return Err(ProcessingError::ContractViolation {
+                contract_name: "reduce_budget".to_string(),
+                violation: format!("reduce prompt leaves {} tokens for input", budget),
+            });
+        }
+
+        let mut batches: Vec<Vec<String>> = Vec::new();
+        let mut used = 0;
+        for child in children {
+            let line = self.truncate(&format!("- {}", child.trim()), line_budget);
+            let size = (self.measure)(&line);
+            match batches.last_mut() {
+                Some(batch) if used + size <= budget => batch.push(line),
+                _ => {
+                    batches.push(vec![line]);
+                    used = 0;
+                }
+            }
+            used += size;
+        }
+        Ok(batches)
+    }
+
+    /// Longest prefix of `text` that fits `budget`
+    fn truncate(&self, text: &str, budget: usize) -> String {
+        truncate_to_budget(text, budget, &*self.measure)
+    }
+}
+
+/// Longest prefix of `text` that `measure`s within `budget`
+pub(crate) fn truncate_to_budget(text: &str, budget: usize, measure: &dyn Fn(&str) -> usize) -> String {
+    if measure(text) <= budget {
+        return text.to_string();
+    }
+
+    // Byte length of the prefix holding `k + 1` characters
+    let prefix_ends: Vec<usize> = text.char_indices().map(|(index, c)| index + c.len_utf8()).collect();
+    let (mut kept, mut high) = (0, prefix_ends.len());
+    while kept < high {
+        let mid = (kept + high).div_ceil(2);
+        if measure(&text[..prefix_ends[mid - 1]]) <= budget {
+            kept = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    match kept {
+        0 => String::new(),
+        kept => text[..prefix_ends[kept - 1]].to_string(),
+    }
+}
+
+/// Append `child` to the group labelled `label`, creating it if needed; returns its index
+fn push_child(groups: &mut Vec<ReduceGroup>, label: &str, child: String) -> usize {
+    match groups.iter().position(|group| group.label == label) {
+        Some(index) => {
+            groups[index].children.push(child);
+            index
+        }
+        None => {
+            groups.push(ReduceGroup {
+                label: label.to_string(),
+                children: vec![child],
+            });
+            groups.len() - 1
+        }
+    }
+}
+
+/// Parent directory of `path`, or `ROOT_MODULE`
+fn module_of(path: &str) -> String {
+    match Path::new(path).parent().and_then(|parent| parent.to_str()) {
+        Some(parent) if !parent.is_empty() => parent.to_string(),
+        _ => ROOT_MODULE.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Reducer that names each output after the number of parts it combined
+    #[derive(Default)]
+    struct CountingReducer {
+        calls: Mutex<Vec<(String, Vec<String>)>>,
+    }
+
+    #[async_trait]
+    impl Reducer for CountingReducer {
+        async fn reduce(&self, prompt: &str, inputs: Vec<String>) -> Result<Vec<String>> {
+            let outputs = inputs
+                .iter()
+                .map(|input| {
+                    let label = input.lines().next().unwrap_or_default();
+                    format!("{} ({} parts)", label, input.lines().filter(|line| line.starts_with("- ")).count())
+                })
+                .collect();
+            self.calls.lock().unwrap().push((prompt.to_string(), inputs));
+            Ok(outputs)
+        }
+    }
+
+    fn chunk(path: &str, line_start: usize) -> Chunk {
+        Chunk::new(line_start, line_start + 10, format!("// {}", line_start)).with_file_path(path)
+    }
+
+    #[tokio::test]
+    async fn test_rolls_chunks_up_to_files_modules_and_crate() {
+        let summaries = vec![
+            (chunk("src/lib.rs", 0), "lib part one".to_string()),
+            (chunk("src/lib.rs", 10), "lib part two".to_string()),
+            (chunk("src/cli/args.rs", 0), "argument parsing".to_string()),
+            (chunk("README.md", 0), "project readme".to_string()),
+        ];
+        let reducer = CountingReducer::default();
+
+        let result = HierarchicalSummarizer::new(LevelBudgets::uniform(1000))
+            .summarize(&summaries, &reducer)
+            .await
+            .unwrap();
+
+        let modules: Vec<&str> = result.modules.iter().map(|module| module.path.as_str()).collect();
+        assert_eq!(modules, vec!["src", "src/cli", ROOT_MODULE]);
+        assert_eq!(result.modules[0].files[0].summary, "File: src/lib.rs (2 parts)");
+        assert_eq!(result.modules[0].files[0].chunk_count, 2);
+        // Single children pass through untouched
+        assert_eq!(result.modules[1].summary, "argument parsing");
+        assert_eq!(result.overview, "Crate: overview (3 parts)");
+
+        let calls = reducer.calls.lock().unwrap();
+        let prompts: Vec<&str> = calls.iter().map(|(prompt, _)| prompt.as_str()).collect();
+        assert_eq!(
+            prompts,
+            vec![SummaryLevel::File.default_prompt(), SummaryLevel::Crate.default_prompt()]
+        );
+        assert!(result.render().starts_with("# Overview\n\nCrate: overview (3 parts)\n\n## Module src\n"));
+    }
+
+    #[tokio::test]
+    async fn test_tight_budgets_reduce_in_rounds_within_budget() {
+        let summaries: Vec<(Chunk, String)> = (0..12)
+            .map(|i| (chunk("src/big.rs", i * 10), format!("summary {} {}", i, "detail ".repeat(20))))
+            .collect();
+        let reducer = CountingReducer::default();
+        let budget = 60;
+
+        let summarizer = HierarchicalSummarizer::new(LevelBudgets::uniform(budget))
+            .with_measure(|text: &str| text.split_whitespace().count())
+            .with_prompt(SummaryLevel::File, "Merge.");
+        let result = summarizer.summarize(&summaries, &reducer).await.unwrap();
+
+        let calls = reducer.calls.lock().unwrap();
+        assert!(calls.len() > 1, "expected several reduce rounds");
+        for (prompt, inputs) in calls.iter() {
+            for input in inputs {
+                assert!((summarizer.measure)(prompt) + (summarizer.measure)(input) <= budget);
+            }
+        }
+        assert!(result.overview.starts_with("File: src/big.rs"));
+    }
+}
@@ -0,0 +1,16 @@
+//! Summarization strategies built on top of chunk summaries
+//!
+//! Contracts:
+//! - Strategies never call a model directly; they go through `Reducer`, so the
+//!   parallel agent system and test doubles are interchangeable
+//! - Every model input respects the token budget of the level it is built for
+
+pub mod grounding;
+pub mod hierarchy;
+pub mod refine;
+
+pub use grounding::{check_grounding, summary_identifiers, GroundingCheck, GroundingReport};
+pub use hierarchy::{
+    FileSummary, HierarchicalSummarizer, HierarchicalSummary, LevelBudgets, ModuleSummary, Reducer, SummaryLevel,
+};
+pub use refine::{file_summaries, RefineSummarizer};
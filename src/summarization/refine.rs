@@ -0,0 +1,209 @@
+//! Refine strategy: each chunk is summarized with the file's summary so far
+//!
+//! Contracts:
+//! - Chunks of one file are summarized in source order; each output replaces
+//!   the file's running summary
+//! - Different files advance together, one chunk per file per round, so
+//!   throughput still scales with the number of files
+//! - The running summary is cut to whatever the budget leaves after the
+//!   chunk and its prompt; with no room left it is omitted, never overflowed
+
+use crate::chunking::structural::ESTIMATED_CHARS_PER_TOKEN;
+use crate::chunking::Chunk;
+use crate::errors::Result;
+use crate::prompt::PromptTemplate;
+
+use super::hierarchy::{truncate_to_budget, Reducer, UNNAMED_SOURCE};
+
+/// Heading that introduces the running summary in a refine prompt
+pub const DEFAULT_REFINE_INSTRUCTION: &str = "Summary of the earlier parts of this file:";
+
+/// Summarizes chunks sequentially per file, carrying a rolling summary
+pub struct RefineSummarizer {
+    template: PromptTemplate,
+    budget: usize,
+    measure: Box<dyn Fn(&str) -> usize + Send + Sync>,
+    instruction: String,
+}
+
+impl RefineSummarizer {
+    /// Refine with `template` as the per-chunk prompt; `budget` covers prompt plus chunk
+    pub fn new(template: PromptTemplate, budget: usize) -> Self {
+        Self {
+            template,
+            budget,
+            measure: Box::new(|text: &str| text.len() / ESTIMATED_CHARS_PER_TOKEN + 1),
+            instruction: DEFAULT_REFINE_INSTRUCTION.to_string(),
+        }
+    }
+
+    /// Measure budgets with `measure` (e.g. the model tokenizer's token count)
+    pub fn with_measure<M>(mut self, measure: M) -> Self
+    where
+        M: Fn(&str) -> usize + Send + Sync + 'static,
+    {
+        self.measure = Box::new(measure);
+        self
+    }
+
+    /// Replace the heading placed before the running summary
+    pub fn with_instruction(mut self, instruction: impl Into<String>) -> Self {
+        self.instruction = instruction.into();
+        self
+    }
+
+    /// Prompt for `chunk` given the file's summary so far
+    pub fn prompt(&self, chunk: &Chunk, running: Option<&str>) -> String {
+        let prompt = self.template.render(chunk);
+        let Some(running) = running.map(str::trim).filter(|running| !running.is_empty()) else {
+            return prompt;
+        };
+
+        let heading = format!("{}\n\n{}\n", prompt, self.instruction);
+        let used = (self.measure)(&heading) + (self.measure)(&chunk.content);
+        let kept = truncate_to_budget(running, self.budget.saturating_sub(used), &*self.measure);
+        match kept.trim_end() {
+            "" => prompt,
+            kept => format!("{}{}", heading, kept),
+        }
+    }
+
+    /// Running summary after each chunk, in chunk order
+    ///
+    /// # Errors
+    /// * Any error returned by `reducer`
+    pub async fn summarize<R>(&self, chunks: &[Chunk], reducer: &R) -> Result<Vec<String>>
+    where
+        R: Reducer + ?Sized,
+    {
+        let files = group_by_file(chunks);
+        let mut running: Vec<Option<String>> = vec![None; files.len()];
+        let mut steps = vec![String::new(); chunks.len()];
+
+        let rounds = files.iter().map(|(_, indices)| indices.len()).max().unwrap_or(0);
+        for round in 0..rounds {
+            let active: Vec<(usize, usize)> = files
+                .iter()
+                .enumerate()
+                .filter_map(|(file, (_, indices))| indices.get(round).map(|&index| (file, index)))
+                .collect();
+            let inputs = active
+                .iter()
+                .map(|&(file, index)| {
+                    let chunk = &chunks[index];
+                    (chunk.content.clone(), self.prompt(chunk, running[file].as_deref()))
+                })
+                .collect();
+
+            let outputs = reducer.reduce_each(inputs).await?;
+            for ((file, index), output) in active.into_iter().zip(outputs) {
+                steps[index] = output.clone();
+                running[file] = Some(output);
+            }
+        }
+
+        Ok(steps)
+    }
+}
+
+/// One chunk per file spanning all of its chunks, paired with its final summary
+///
+/// `steps` are the outputs of `RefineSummarizer::summarize` for `chunks`.
+pub fn file_summaries(chunks: &[Chunk], steps: &[String]) -> Vec<(Chunk, String)> {
+    group_by_file(chunks)
+        .into_iter()
+        .filter_map(|(_, indices)| {
+            let (&first, rest) = indices.split_first()?;
+            let whole = rest.iter().fold(chunks[first].clone(), |whole, &index| whole.merge(&chunks[index]));
+            let last = *indices.last()?;
+            Some((whole, steps.get(last).cloned().unwrap_or_default()))
+        })
+        .collect()
+}
+
+/// Chunk indices per file, files in order of first appearance
+fn group_by_file(chunks: &[Chunk]) -> Vec<(&str, Vec<usize>)> {
+    let mut files: Vec<(&str, Vec<usize>)> = Vec::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let path = chunk.file_path.as_deref().unwrap_or(UNNAMED_SOURCE);
+        match files.iter_mut().find(|(file, _)| *file == path) {
+            Some((_, indices)) => indices.push(index),
+            None => files.push((path, vec![index])),
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// Reducer that records prompts and answers with a running count per file
+    #[derive(Default)]
+    struct EchoReducer {
+        rounds: Mutex<Vec<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Reducer for EchoReducer {
+        async fn reduce(&self, _prompt: &str, inputs: Vec<String>) -> Result<Vec<String>> {
+            Ok(inputs)
+        }
+
+        async fn reduce_each(&self, inputs: Vec<(String, String)>) -> Result<Vec<String>> {
+            let prompts: Vec<String> = inputs.iter().map(|(_, prompt)| prompt.clone()).collect();
+            self.rounds.lock().unwrap().push(prompts);
+            Ok(inputs.into_iter().map(|(input, _)| format!("covers {}", input.trim())).collect())
+        }
+    }
+
+    fn chunk(path: &str, line_start: usize, content: &str) -> Chunk {
+        Chunk::new(line_start, line_start + 1, content.to_string()).with_file_path(path)
+    }
+
+    #[tokio::test]
+    async fn test_chunks_of_a_file_see_the_running_summary() {
+        let chunks = vec![
+            chunk("src/a.rs", 0, "fn a1() {}"),
+            chunk("src/b.rs", 0, "fn b1() {}"),
+            chunk("src/a.rs", 1, "fn a2() {}"),
+        ];
+        let reducer = EchoReducer::default();
+        let summarizer = RefineSummarizer::new(PromptTemplate::parse("Summarize {{file_path}}.").unwrap(), 1000);
+
+        let steps = summarizer.summarize(&chunks, &reducer).await.unwrap();
+        assert_eq!(steps, vec!["covers fn a1() {}", "covers fn b1() {}", "covers fn a2() {}"]);
+
+        let rounds = reducer.rounds.lock().unwrap();
+        assert_eq!(rounds.len(), 2, "files advance together");
+        assert_eq!(rounds[0], vec!["Summarize src/a.rs.", "Summarize src/b.rs."]);
+        assert_eq!(
+            rounds[1],
+            vec![format!("Summarize src/a.rs.\n\n{}\ncovers fn a1() {{}}", DEFAULT_REFINE_INSTRUCTION)]
+        );
+
+        let files = file_summaries(&chunks, &steps);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].0.content, "fn a1() {}\nfn a2() {}");
+        assert_eq!(files[0].1, "covers fn a2() {}");
+    }
+
+    #[test]
+    fn test_running_summary_is_cut_to_the_budget() {
+        let words = |text: &str| text.split_whitespace().count();
+        let summarizer = RefineSummarizer::new(PromptTemplate::parse("Summarize.").unwrap(), 12)
+            .with_measure(words)
+            .with_instruction("Earlier:");
+        let next = chunk("src/a.rs", 5, "let x = 1;");
+        let running = "one two three four five six seven eight nine ten";
+
+        let prompt = summarizer.prompt(&next, Some(running));
+        assert!(words(&prompt) + words(&next.content) <= 12);
+        assert!(prompt.ends_with("Earlier:\none two three four five six"));
+
+        let crowded = RefineSummarizer::new(PromptTemplate::parse("Summarize.").unwrap(), 4).with_measure(words);
+        assert_eq!(crowded.prompt(&next, Some(running)), "Summarize.");
+    }
+}